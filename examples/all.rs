@@ -1,4 +1,4 @@
-use easyeda2kicad_rs::import_component;
+use easyeda2kicad_rs::{api::EasyedaApi, import_component_with_api};
 use std::{path::Path, time::Instant};
 
 #[tokio::main]
@@ -24,10 +24,12 @@ async fn main() {
         "C2939557", // part with Oval holes
     ]; // Example LCSC IDs
 
+    // Share one client so every part reuses the same connection pool.
+    let api = EasyedaApi::new();
     let start_time = Instant::now();
     for lcsc_id in &lcsc_ids {
         let entry_start_time = Instant::now();
-        match import_component(lcsc_id, Path::new("example_lib")).await {
+        match import_component_with_api(&api, lcsc_id, Path::new("example_lib")).await {
             Ok(_) => println!("Imported component: {:}", lcsc_id),
             Err(e) => eprintln!("Error importing component: {}", e),
         }
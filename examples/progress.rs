@@ -0,0 +1,90 @@
+//! Batch import with a progress bar, driven by `ProgressEvent`s.
+//!
+//! The bar is drawn by hand on stderr; a crate such as indicatif can be fed from
+//! the same events.
+
+use easyeda2kicad_rs::{
+    ImportOptions, import_components_with_progress,
+    progress::{ProgressEvent, ProgressSink},
+};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Renders `[#####.....] 3/10 C2040 (1.2 MB)` on a single line.
+#[derive(Default)]
+struct ProgressBar {
+    state: Mutex<BarState>,
+}
+
+#[derive(Default)]
+struct BarState {
+    total: usize,
+    done: usize,
+    current: String,
+    downloaded: u64,
+}
+
+impl ProgressBar {
+    fn draw(state: &BarState) {
+        const WIDTH: usize = 30;
+        let filled = (state.done * WIDTH).checked_div(state.total).unwrap_or(0);
+        let download = if state.downloaded > 0 {
+            format!(" ({:.1} MB)", state.downloaded as f64 / 1e6)
+        } else {
+            String::new()
+        };
+        eprint!(
+            "\r[{}{}] {}/{} {}{}\x1b[K",
+            "#".repeat(filled),
+            ".".repeat(WIDTH - filled),
+            state.done,
+            state.total,
+            state.current,
+            download
+        );
+        let _ = std::io::stderr().flush();
+    }
+}
+
+impl ProgressSink for ProgressBar {
+    fn event(&self, event: ProgressEvent<'_>) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            ProgressEvent::BatchStarted { total } => state.total = total,
+            ProgressEvent::PartStarted { lcsc_id, .. } => {
+                state.current = lcsc_id.to_string();
+                state.downloaded = 0;
+            }
+            ProgressEvent::ModelDownload { downloaded, .. } => state.downloaded = downloaded,
+            ProgressEvent::PartFinished {
+                lcsc_id, success, ..
+            } => {
+                state.done += 1;
+                if !success {
+                    eprintln!("\r{} failed\x1b[K", lcsc_id);
+                }
+            }
+            ProgressEvent::BatchFinished { succeeded, failed } => {
+                state.current = format!("done: {} imported, {} failed", succeeded, failed);
+                state.downloaded = 0;
+            }
+        }
+        Self::draw(&state);
+        if matches!(event, ProgressEvent::BatchFinished { .. }) {
+            eprintln!();
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let lcsc_ids = ["C8952", "C2040", "C5659", "C1002", "C128958", "C2939557"];
+    import_components_with_progress(
+        &lcsc_ids,
+        Path::new("example_lib"),
+        &ImportOptions::default(),
+        &ProgressBar::default(),
+    )
+    .await;
+}
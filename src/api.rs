@@ -1,10 +1,22 @@
 use crate::error::{Error, Result};
 use serde::Deserialize;
 use serde_json::Value;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
 
-const API_ENDPOINT: &str = "https://easyeda.com/api/products/{lcsc_id}/components?version=6.4.19.5";
+/// EasyEDA editor version sent with component requests; the response layout depends on it.
+pub const API_VERSION: &str = "6.4.19.5";
+const API_ENDPOINT: &str =
+    "https://easyeda.com/api/products/{lcsc_id}/components?version={version}";
+const ENDPOINT_COMPONENT_BY_UUID: &str =
+    "https://easyeda.com/api/components/{uuid}?version={version}&uuid={uuid}";
 const ENDPOINT_3D_MODEL: &str = "https://modules.easyeda.com/3dmodel/{uuid}";
-const ENDPOINT_3D_MODEL_STEP: &str = "https://modules.easyeda.com/qAxj6KHrDKw4blvCG8QJPs7Y/{uuid}";
+/// STEP model URL templates, tried in order. The path token is rotated by EasyEDA
+/// from time to time; the mirror on lceda.cn sometimes still serves the old one.
+pub const STEP_ENDPOINTS: &[&str] = &[
+    "https://modules.easyeda.com/qAxj6KHrDKw4blvCG8QJPs7Y/{uuid}",
+    "https://modules.lceda.cn/qAxj6KHrDKw4blvCG8QJPs7Y/{uuid}",
+];
 // const USER_AGENT: &str = concat!("easyeda2kicad_rs/", env!("CARGO_PKG_VERSION"));
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 #[derive(Deserialize, Debug)]
@@ -15,49 +27,365 @@ pub struct ApiResponse {
     pub message: Option<String>,
 }
 
+/// Revision of fetched component data: EasyEDA's `updateTime` stamp, which changes
+/// whenever the part is edited. `None` if the data carries no stamp.
+pub fn component_revision(cad_data: &Value) -> Option<String> {
+    ["updateTime", "updated_at"]
+        .iter()
+        .find_map(|key| match &cad_data[*key] {
+            Value::Number(n) => Some(n.to_string()),
+            Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            _ => None,
+        })
+}
+
+/// Checks that component data is at revision `rev` (see [`component_revision`]).
+pub fn check_component_revision(lcsc_id: &str, cad_data: &Value, rev: &str) -> Result<()> {
+    match component_revision(cad_data) {
+        Some(found) if found == rev.trim() => Ok(()),
+        Some(found) => Err(Error::RevisionMismatch(format!(
+            "{} is at revision {} upstream, not {}",
+            lcsc_id, found, rev
+        ))),
+        None => Err(Error::RevisionMismatch(format!(
+            "{} has no revision stamp to check against {}",
+            lcsc_id, rev
+        ))),
+    }
+}
+
+/// Checks that a downloaded OBJ model has at least one vertex and one face, which a
+/// truncated or error response doesn't.
+pub fn check_obj_model(obj: &str) -> Result<()> {
+    let has = |keyword: &str| {
+        obj.lines()
+            .any(|line| line.split_whitespace().next() == Some(keyword))
+    };
+    if !has("v") || !has("f") {
+        return Err(Error::CorruptDownload(format!(
+            "OBJ model of {} bytes has no {}",
+            obj.len(),
+            if has("v") { "faces" } else { "vertices" }
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that a downloaded STEP model starts with the `ISO-10303-21` header and
+/// still has the closing `END-ISO-10303-21` line a truncated file lacks.
+///
+/// Only the beginning and end of the file are looked at, so `head` and `tail` can be
+/// the first and last kilobyte or so of a file too large to load.
+pub fn check_step_model(head: &[u8], tail: &[u8]) -> Result<()> {
+    let head = String::from_utf8_lossy(head);
+    if !head
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with("ISO-10303")
+    {
+        return Err(Error::CorruptDownload(
+            "STEP model doesn't start with ISO-10303".to_string(),
+        ));
+    }
+    if !String::from_utf8_lossy(tail).contains("END-ISO-10303") {
+        return Err(Error::CorruptDownload(
+            "STEP model ends before END-ISO-10303".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Fails if the server announced a different size than was received.
+fn check_length(what: &str, expected: Option<u64>, received: u64) -> Result<()> {
+    match expected {
+        Some(expected) if expected != received => Err(Error::CorruptDownload(format!(
+            "{} is {} bytes, expected {}",
+            what, received, expected
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Bytes at each end of a STEP file read by [`check_step_model`].
+const STEP_CHECK_BYTES: usize = 1024;
+
+/// Parses the body of a component API response into its `result` value.
+///
+/// When EasyEDA rate-limits or shows a captcha it can answer with an HTML page and a
+/// 200 status. That is reported as [`Error::ApiRejected`] instead of an opaque JSON error.
+pub fn parse_component_response(body: &str) -> Result<Value> {
+    let trimmed = body.trim_start();
+    if trimmed.starts_with('<') {
+        return Err(Error::ApiRejected(
+            "received an HTML page instead of JSON. EasyEDA is likely rate-limiting or \
+             asking for a captcha; wait a few minutes, or open easyeda.com in a browser \
+             from the same network, then retry."
+                .to_string(),
+        ));
+    }
+    if !trimmed.starts_with('{') {
+        let preview: String = trimmed.chars().take(80).collect();
+        return Err(Error::ApiRejected(format!(
+            "unexpected non-JSON response: {:?}",
+            preview
+        )));
+    }
+
+    let res: ApiResponse = serde_json::from_str(body)?;
+    if res.success {
+        res.result
+            .ok_or_else(|| Error::MissingData("API response missing 'result' field".to_string()))
+    } else {
+        let _msg = res
+            .message
+            .unwrap_or_else(|| "Unknown API error".to_string());
+        Err(Error::MissingData("API Error".to_string()))
+    }
+}
+
+/// Reads a component API response: rejects rate-limit statuses, then returns the
+/// `result` of its JSON envelope.
+async fn read_api_result(res: reqwest::Response) -> Result<Value> {
+    let status = res.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::FORBIDDEN
+    {
+        return Err(Error::ApiRejected(format!(
+            "HTTP {} from the component API. You are probably being rate-limited; \
+             wait a few minutes and retry, and slow down batch imports.",
+            status
+        )));
+    }
+
+    parse_component_response(&res.text().await?)
+}
+
+/// Part fetched by [`EasyedaApi::health_check`]: a 0603 resistor that has been on
+/// LCSC's basic list for years, with a small component document.
+pub const HEALTH_CHECK_LCSC_ID: &str = "C21190";
+
+/// Outcome of [`EasyedaApi::health_check`].
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    /// URL that was requested.
+    pub url: String,
+    /// Whether an HTTP response came back at all.
+    pub reachable: bool,
+    /// Time until the response headers arrived.
+    pub latency: Option<std::time::Duration>,
+    /// HTTP status of the response.
+    pub status: Option<u16>,
+    /// Whether the body parsed as a successful component API envelope.
+    pub json_ok: bool,
+    /// The first thing that went wrong, if anything.
+    pub error: Option<String>,
+}
+
+impl HealthReport {
+    /// True when the API answered with usable component JSON.
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.json_ok
+    }
+}
+
+impl std::fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "url:       {}", self.url)?;
+        writeln!(
+            f,
+            "reachable: {}",
+            if self.reachable { "yes" } else { "no" }
+        )?;
+        if let Some(latency) = self.latency {
+            writeln!(f, "latency:   {} ms", latency.as_millis())?;
+        }
+        if let Some(status) = self.status {
+            writeln!(f, "status:    HTTP {}", status)?;
+        }
+        write!(
+            f,
+            "json:      {}",
+            if self.json_ok { "ok" } else { "failed" }
+        )?;
+        if let Some(error) = &self.error {
+            write!(f, "\nerror:     {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// 3D model downloads an [`EasyedaApi`] runs at once unless told otherwise: the OBJ
+/// and STEP files of two parts.
+pub const DEFAULT_MODEL_DOWNLOADS: usize = 4;
+
 pub struct EasyedaApi {
     client: reqwest::Client,
+    step_endpoints: Vec<String>,
+    /// Limits the 3D model requests in flight across every import using this client.
+    model_downloads: tokio::sync::Semaphore,
+}
+
+impl Default for EasyedaApi {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl EasyedaApi {
     pub fn new() -> Self {
+        Self::with_headers(reqwest::header::HeaderMap::new())
+    }
+
+    /// Creates a client that sends `headers` with every request, to the component
+    /// and 3D model endpoints alike. A `User-Agent` in `headers` replaces the default.
+    pub fn with_headers(headers: reqwest::header::HeaderMap) -> Self {
         EasyedaApi {
             client: reqwest::Client::builder()
                 .user_agent(USER_AGENT)
+                // Component JSON for large parts runs to megabytes and compresses well.
+                .gzip(true)
+                .deflate(true)
+                .brotli(true)
+                .default_headers(headers)
                 .build()
                 .unwrap(),
+            step_endpoints: STEP_ENDPOINTS.iter().map(|s| s.to_string()).collect(),
+            model_downloads: tokio::sync::Semaphore::new(DEFAULT_MODEL_DOWNLOADS),
         }
     }
 
+    /// Sets how many 3D model downloads (OBJ or STEP) may be in flight at once
+    /// (default [`DEFAULT_MODEL_DOWNLOADS`]). Further requests wait for a free slot,
+    /// so batch imports sharing this client don't hammer the model server. At least
+    /// one download is always allowed.
+    pub fn with_max_model_downloads(mut self, max: usize) -> Self {
+        self.model_downloads = tokio::sync::Semaphore::new(max.max(1));
+        self
+    }
+
+    /// Replaces the STEP model URL templates (default [`STEP_ENDPOINTS`]), e.g. with
+    /// a newer token. `{uuid}` in each template is replaced by the model's uuid; they
+    /// are tried in order until one has the model.
+    pub fn with_step_endpoints<S: Into<String>>(
+        mut self,
+        endpoints: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.step_endpoints = endpoints.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Creates a client that sends the session cookie of a logged-in EasyEDA account,
+    /// e.g. `"session=abc123"` copied from the browser, so private library parts can
+    /// be fetched.
+    pub fn with_cookie(cookie: &str) -> Result<Self> {
+        let mut value = reqwest::header::HeaderValue::from_str(cookie.trim())
+            .map_err(|e| Error::ParseError(format!("Invalid cookie: {}", e)))?;
+        // Keeps the session out of debug output.
+        value.set_sensitive(true);
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::COOKIE, value);
+        Ok(Self::with_headers(headers))
+    }
+
     pub async fn get_cad_data_of_component(&self, lcsc_id: &str) -> Result<Value> {
-        let url = API_ENDPOINT.replace("{lcsc_id}", lcsc_id);
-        let res = self
-            .client
-            .get(&url)
-            .header("Referer", "https://easyeda.com/")
-            .header("Origin", "https://easyeda.com")
-            .send()
-            .await?
-            .json::<ApiResponse>()
+        self.get_api_result(&API_ENDPOINT.replace("{lcsc_id}", lcsc_id))
+            .await
+    }
+
+    /// Fetches a component, pinned to revision `rev` as reported by
+    /// [`component_revision`].
+    ///
+    /// EasyEDA only serves the latest revision of a part, so this fails with
+    /// [`Error::RevisionMismatch`] once the part has changed upstream instead of
+    /// quietly returning different data.
+    pub async fn get_cad_data_of_component_rev(&self, lcsc_id: &str, rev: &str) -> Result<Value> {
+        let cad_data = self.get_cad_data_of_component(lcsc_id).await?;
+        check_component_revision(lcsc_id, &cad_data, rev)?;
+        Ok(cad_data)
+    }
+
+    /// Fetches a symbol directly by its EasyEDA UUID, bypassing the LCSC lookup.
+    ///
+    /// Useful for private or custom library parts without an LCSC number. The returned
+    /// value can be passed straight to [`crate::importer::import_symbol`].
+    pub async fn get_symbol_by_uuid(&self, uuid: &str) -> Result<Value> {
+        self.get_api_result(&ENDPOINT_COMPONENT_BY_UUID.replace("{uuid}", uuid))
+            .await
+    }
+
+    /// Fetches a footprint directly by its EasyEDA UUID, bypassing the LCSC lookup.
+    ///
+    /// The footprint is wrapped as `{"packageDetail": ...}`, the layout of a full
+    /// component response, so it can be passed straight to
+    /// [`crate::importer::import_footprint`] and [`crate::importer::import_3d_model_info`].
+    pub async fn get_footprint_by_uuid(&self, uuid: &str) -> Result<Value> {
+        let footprint = self
+            .get_api_result(&ENDPOINT_COMPONENT_BY_UUID.replace("{uuid}", uuid))
             .await?;
+        Ok(serde_json::json!({ "packageDetail": footprint }))
+    }
 
-        if res.success {
-            res.result.ok_or_else(|| {
-                Error::MissingData("API response missing 'result' field".to_string())
-            })
-        } else {
-            let _msg = res
-                .message
-                .unwrap_or_else(|| "Unknown API error".to_string());
-            Err(Error::MissingData("API Error".to_string()))
+    /// Checks that the component API can be reached and answers sensibly, by
+    /// fetching a small, long-listed part ([`HEALTH_CHECK_LCSC_ID`]).
+    ///
+    /// Never fails; whatever went wrong is described in the report. Meant for telling
+    /// network, proxy and rate-limit problems apart from conversion bugs.
+    pub async fn health_check(&self) -> HealthReport {
+        self.health_check_url(&API_ENDPOINT.replace("{lcsc_id}", HEALTH_CHECK_LCSC_ID))
+            .await
+    }
+
+    /// Same as [`EasyedaApi::health_check`], against another component API URL, e.g. a
+    /// mirror. `{version}` in `url` is replaced by [`API_VERSION`].
+    pub async fn health_check_url(&self, url: &str) -> HealthReport {
+        let mut report = HealthReport {
+            url: url.replace("{version}", API_VERSION),
+            reachable: false,
+            latency: None,
+            status: None,
+            json_ok: false,
+            error: None,
+        };
+        let started = std::time::Instant::now();
+        let res = match self.api_request(url).send().await {
+            Ok(res) => res,
+            Err(e) => {
+                report.error = Some(e.to_string());
+                return report;
+            }
+        };
+        report.reachable = true;
+        report.latency = Some(started.elapsed());
+        report.status = Some(res.status().as_u16());
+        match read_api_result(res).await {
+            Ok(_) => report.json_ok = true,
+            Err(e) => report.error = Some(e.to_string()),
         }
+        report
+    }
+
+    /// A GET request for an EasyEDA API URL, with the headers the API expects.
+    fn api_request(&self, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .get(url.replace("{version}", API_VERSION))
+            .header("Referer", "https://easyeda.com/")
+            .header("Origin", "https://easyeda.com")
+    }
+
+    /// GETs an EasyEDA API URL and returns the `result` of its JSON envelope.
+    async fn get_api_result(&self, url: &str) -> Result<Value> {
+        read_api_result(self.api_request(url).send().await?).await
     }
 
     pub async fn get_raw_3d_model_obj(&self, uuid: &str) -> Result<String> {
+        let _slot = self.model_downloads.acquire().await.ok();
         let url = ENDPOINT_3D_MODEL.replace("{uuid}", uuid);
         let res = self.client.get(&url).send().await?;
         if res.status().is_success() {
-            Ok(res.text().await?)
+            let expected = res.content_length();
+            let obj = res.text().await?;
+            check_length("OBJ model", expected, obj.len() as u64)?;
+            check_obj_model(&obj)?;
+            Ok(obj)
         } else {
             Err(Error::MissingData(format!(
                 "No raw 3D model (OBJ) found for uuid: {}",
@@ -66,16 +394,90 @@ impl EasyedaApi {
         }
     }
 
+    /// Requests the STEP model from each endpoint in turn and returns the first
+    /// successful response.
+    async fn step_response(&self, uuid: &str) -> Result<reqwest::Response> {
+        let mut failures = Vec::new();
+        for template in &self.step_endpoints {
+            let url = template.replace("{uuid}", uuid);
+            match self.client.get(&url).send().await {
+                Ok(res) if res.status().is_success() => return Ok(res),
+                Ok(res) => failures.push(format!("{} ({})", url, res.status())),
+                Err(e) => failures.push(format!("{} ({})", url, e)),
+            }
+            log::debug!("STEP model not at {}", url);
+        }
+        Err(Error::MissingData(format!(
+            "No STEP 3D model found for uuid: {}; tried {}",
+            uuid,
+            failures.join(", ")
+        )))
+    }
+
+    /// Downloads the STEP model for `uuid`, failing with [`Error::CorruptDownload`] if
+    /// it is cut short or isn't a STEP file.
     pub async fn get_step_3d_model(&self, uuid: &str) -> Result<bytes::Bytes> {
-        let url = ENDPOINT_3D_MODEL_STEP.replace("{uuid}", uuid);
-        let res = self.client.get(&url).send().await?;
-        if res.status().is_success() {
-            Ok(res.bytes().await?)
-        } else {
-            Err(Error::MissingData(format!(
-                "No STEP 3D model found for uuid: {}",
-                uuid
-            )))
+        let _slot = self.model_downloads.acquire().await.ok();
+        let res = self.step_response(uuid).await?;
+        let expected = res.content_length();
+        let data = res.bytes().await?;
+        check_length("STEP model", expected, data.len() as u64)?;
+        let split = data.len().saturating_sub(STEP_CHECK_BYTES);
+        check_step_model(&data[..data.len().min(STEP_CHECK_BYTES)], &data[split..])?;
+        Ok(data)
+    }
+
+    /// Streams the STEP model for `uuid` straight into `dest`, chunk by chunk.
+    ///
+    /// Unlike [`EasyedaApi::get_step_3d_model`], the file is never held in memory, which
+    /// keeps batch imports of large connector/module models flat. The finished file is
+    /// checked like [`EasyedaApi::get_step_3d_model`]'s download. On failure any partial
+    /// file is removed. Returns the number of bytes written.
+    pub async fn download_step_3d_model(&self, uuid: &str, dest: &Path) -> Result<u64> {
+        self.download_step_3d_model_with_progress(uuid, dest, |_, _| {})
+            .await
+    }
+
+    /// Same as [`EasyedaApi::download_step_3d_model`], calling `on_progress` after each
+    /// chunk with the bytes written so far and the file size, if the server sent one.
+    pub async fn download_step_3d_model_with_progress(
+        &self,
+        uuid: &str,
+        dest: &Path,
+        mut on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<u64> {
+        let _slot = self.model_downloads.acquire().await.ok();
+        let mut res = self.step_response(uuid).await?;
+
+        let total = res.content_length();
+        let mut file = tokio::fs::File::create(dest).await?;
+        let mut written = 0u64;
+        // The first and most recent bytes, for `check_step_model`.
+        let mut head = Vec::new();
+        let mut tail = Vec::new();
+        let result: Result<()> = async {
+            while let Some(chunk) = res.chunk().await? {
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+                on_progress(written, total);
+                if head.len() < STEP_CHECK_BYTES {
+                    let take = chunk.len().min(STEP_CHECK_BYTES - head.len());
+                    head.extend_from_slice(&chunk[..take]);
+                }
+                tail.extend_from_slice(&chunk);
+                tail.drain(..tail.len().saturating_sub(STEP_CHECK_BYTES));
+            }
+            file.flush().await?;
+            check_length("STEP model", total, written)?;
+            check_step_model(&head, &tail)
+        }
+        .await;
+
+        if let Err(e) = result {
+            drop(file);
+            let _ = tokio::fs::remove_file(dest).await;
+            return Err(e);
         }
+        Ok(written)
     }
 }
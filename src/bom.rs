@@ -0,0 +1,119 @@
+// src/bom.rs
+
+use crate::error::{Error, Result};
+use regex::Regex;
+
+/// Header names commonly used for the LCSC part number column, e.g. in JLCPCB
+/// assembly BOMs. Compared ignoring case, spaces and punctuation.
+const LCSC_COLUMN_NAMES: &[&str] = &[
+    "LCSC Part #",
+    "LCSC Part Number",
+    "LCSC Part",
+    "LCSC",
+    "JLCPCB Part #",
+    "Supplier Part",
+    "Supplier Part Number",
+];
+
+/// LCSC IDs extracted from a BOM.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BomIds {
+    /// Unique IDs, in the order they first appear.
+    pub lcsc_ids: Vec<String>,
+    /// 1-based line numbers of data rows without an LCSC ID.
+    pub rows_without_id: Vec<usize>,
+}
+
+/// Separators a BOM may use, tried in this order.
+const SEPARATORS: &[u8] = b",;\t";
+
+/// Extracts LCSC IDs from CSV BOM content.
+///
+/// `lcsc_column` names the column to read; with `None` the first column matching one of
+/// the usual header names ("LCSC Part #", "Supplier Part", ...) is used. Comma,
+/// semicolon and tab separated files are accepted: the separator is the first one
+/// whose header row has that column.
+pub fn parse_bom(content: &str, lcsc_column: Option<&str>) -> Result<BomIds> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    // For the error message: the header as split by the separator that gave the
+    // most columns.
+    let mut widest = csv::StringRecord::new();
+    for &separator in SEPARATORS {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(separator)
+            .flexible(true)
+            .from_reader(content.as_bytes());
+        let header = reader.headers().map_err(csv_error)?.clone();
+        if header.is_empty() {
+            return Err(Error::MissingData("BOM is empty".to_string()));
+        }
+        if let Some(column) = lcsc_column_index(&header, lcsc_column) {
+            return read_ids(reader, column, content);
+        }
+        if header.len() > widest.len() {
+            widest = header;
+        }
+    }
+    Err(Error::MissingData(format!(
+        "No LCSC column ({}) in BOM header: {}",
+        lcsc_column.unwrap_or("e.g. 'LCSC Part #'"),
+        widest.iter().collect::<Vec<_>>().join(", ")
+    )))
+}
+
+/// Finds the LCSC column in a header row, by name or among the usual names.
+fn lcsc_column_index(header: &csv::StringRecord, lcsc_column: Option<&str>) -> Option<usize> {
+    let normalized: Vec<String> = header.iter().map(normalize_header).collect();
+    match lcsc_column {
+        Some(name) => normalized.iter().position(|h| *h == normalize_header(name)),
+        None => LCSC_COLUMN_NAMES
+            .iter()
+            .find_map(|name| normalized.iter().position(|h| *h == normalize_header(name))),
+    }
+}
+
+/// Reads the IDs in `column` from the data rows of a BOM.
+fn read_ids(mut reader: csv::Reader<&[u8]>, column: usize, content: &str) -> Result<BomIds> {
+    let id_re = Regex::new(r"(?i)\bC\d+\b").map_err(|e| Error::ParseError(e.to_string()))?;
+    let mut ids = BomIds::default();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        // Skip blank lines entirely; they aren't parts.
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        match record.get(column).and_then(|f| id_re.find(f)) {
+            Some(m) => {
+                let id = m.as_str().to_uppercase();
+                if !ids.lcsc_ids.contains(&id) {
+                    ids.lcsc_ids.push(id);
+                }
+            }
+            None => {
+                // csv reports positions just after the previous record's `\r`, so
+                // skip what's left of the line break before counting lines.
+                let bytes = content.as_bytes();
+                let mut start = record.position().map_or(0, |p| p.byte() as usize);
+                while matches!(bytes.get(start), Some(b'\r' | b'\n')) {
+                    start += 1;
+                }
+                let line = bytes[..start].iter().filter(|&&b| b == b'\n').count() + 1;
+                ids.rows_without_id.push(line);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::ParseError(format!("BOM: {}", e))
+}
+
+/// Lowercases a header and drops everything but letters and digits.
+fn normalize_header(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
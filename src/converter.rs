@@ -1,12 +1,178 @@
 // src/converter.rs
 
-use crate::{easyeda_models::*, error::Result, kicad_models::*};
-use glam::Vec3;
+use crate::{
+    easyeda_models::*,
+    error::{Error, Result},
+    kicad_models::*,
+    svg_path::{self, ArcGeometry, PathSegment},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-/// Helper to snap coordinates to the standard KiCad schematic grid (50 mil / 1.27mm).
-/// This ensures wires can actually connect to the pins.
-fn snap_to_grid(val: f32) -> f32 {
-    let grid = 1.27; // 50 mils
+/// Options controlling symbol, footprint and 3D model conversion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConvertOptions {
+    /// Fail the conversion instead of warning when two pads (or two pins)
+    /// share the same number.
+    pub duplicate_numbers_are_errors: bool,
+    /// Thermal relief applied to every through-hole pad. `None` leaves the
+    /// zone defaults in charge.
+    pub thermal: Option<PadThermal>,
+    /// Number of `fp_line` segments used when an arc can't be emitted as a native
+    /// `fp_arc` and is approximated instead. `0` picks one segment per 15° of sweep.
+    pub arc_segments: usize,
+    /// Grid (in mm) that symbol pins and graphics are snapped to after centering.
+    /// `None` uses KiCad's standard 1.27mm (50 mil) grid; `Some(0.0)` disables snapping.
+    pub symbol_grid: Option<f32>,
+    /// Pin type used for EasyEDA pin types with no KiCad mapping. `None` means passive.
+    pub default_pin_type: Option<KiPinType>,
+    /// Layer used for EasyEDA layer ids with no KiCad mapping. `None` means `F.Fab`.
+    pub fallback_layer: Option<String>,
+    /// KiCad layers for footprint graphics on the given EasyEDA layer ids, taking
+    /// precedence over [`LAYER_TABLE`].
+    #[serde(with = "layer_id_keys")]
+    pub layer_overrides: HashMap<i32, String>,
+    /// Draw a body rectangle around the pins' inner ends for symbols that have pins
+    /// but no rectangle of their own (typically connectors).
+    pub synthesize_body: bool,
+    /// Emit closed silkscreen/fab tracks (first point == last point) as a single
+    /// `fp_poly` outline instead of one `fp_line` per segment.
+    pub closed_tracks_as_poly: bool,
+    /// Mark footprints as excluded from the BOM. `None` infers it: test points,
+    /// fiducials and mounting holes are excluded.
+    pub exclude_from_bom: Option<bool>,
+    /// Mark footprints as excluded from position (pick and place) files. `None`
+    /// infers it like `exclude_from_bom`.
+    pub exclude_from_pos: Option<bool>,
+    /// Write VRML models indented, one value per line (the default). `false` writes
+    /// compact files, which are several times smaller for high-poly models.
+    pub wrl_pretty: bool,
+    /// Where symbols are anchored: EasyEDA's origin (the default) or the center
+    /// of their pins.
+    pub symbol_origin: SymbolOrigin,
+    /// Prefix for the footprint reference text, giving e.g. `R**`. `None` uses the
+    /// part's own prefix, or `REF**` when it has none.
+    pub reference_prefix: Option<String>,
+    /// Leave out EasyEDA's dimension and angle annotations, which are otherwise drawn
+    /// on `Cmts.User` with the measured length as text.
+    pub skip_dimensions: bool,
+    /// Millimeters per EasyEDA unit for footprints. `None` uses EasyEDA's 10 mil
+    /// (0.254mm) unit; set this for parts whose footprints come out wrongly scaled.
+    pub unit_scale: Option<f32>,
+    /// Line width (mm) for silkscreen graphics EasyEDA gives no width. `None` uses
+    /// KiCad's default of 0.12mm.
+    pub silk_width: Option<f32>,
+    /// Narrowest and widest line (mm) kept on silkscreen and fab layers, e.g.
+    /// `(0.1, 0.5)` to stay within a board's DRC limits. `None` only widens lines
+    /// below 0.01mm.
+    pub silk_width_range: Option<(f32, f32)>,
+    /// Fail with [`Error::Unsupported`] on shapes the importer doesn't handle instead
+    /// of skipping them (see [`crate::importer::import_footprint_with_options`]).
+    pub strict: bool,
+    /// Where footprints' fab outline and courtyard come from.
+    pub fab_style: FabStyle,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        ConvertOptions {
+            duplicate_numbers_are_errors: false,
+            thermal: None,
+            arc_segments: 0,
+            symbol_grid: None,
+            default_pin_type: None,
+            fallback_layer: None,
+            layer_overrides: HashMap::new(),
+            synthesize_body: false,
+            closed_tracks_as_poly: false,
+            exclude_from_bom: None,
+            exclude_from_pos: None,
+            wrl_pretty: true,
+            symbol_origin: SymbolOrigin::default(),
+            reference_prefix: None,
+            skip_dimensions: false,
+            unit_scale: None,
+            silk_width: None,
+            silk_width_range: None,
+            strict: false,
+            fab_style: FabStyle::default(),
+        }
+    }
+}
+
+/// (De)serializes [`ConvertOptions::layer_overrides`] with the layer ids as string
+/// keys, which is all TOML tables allow.
+mod layer_id_keys {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use std::collections::{BTreeMap, HashMap};
+
+    pub fn serialize<S: Serializer>(map: &HashMap<i32, String>, s: S) -> Result<S::Ok, S::Error> {
+        let sorted: BTreeMap<String, &String> = map
+            .iter()
+            .map(|(id, layer)| (id.to_string(), layer))
+            .collect();
+        s.collect_map(sorted)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<HashMap<i32, String>, D::Error> {
+        HashMap::<String, String>::deserialize(d)?
+            .into_iter()
+            .map(|(id, layer)| {
+                let id = id
+                    .trim()
+                    .parse()
+                    .map_err(|_| D::Error::custom(format!("invalid layer id '{}'", id)))?;
+                Ok((id, layer))
+            })
+            .collect()
+    }
+}
+
+/// Which point of an EasyEDA symbol becomes the KiCad symbol's origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolOrigin {
+    /// The origin EasyEDA stores in `dataStr.head`, which is where the part is
+    /// anchored in EasyEDA. Symbols whose origin lies far outside their pins fall
+    /// back to `Centroid`.
+    #[default]
+    Head,
+    /// The center of the pins' extent (or of the rectangles, for symbols without pins).
+    Centroid,
+}
+
+/// Where a footprint's fab outline and courtyard come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FabStyle {
+    /// Whatever EasyEDA drew on its fab and courtyard layers.
+    #[default]
+    #[serde(rename = "easyeda")]
+    EasyEda,
+    /// Generated from the pads, IPC-7351 style: an `F.Fab` outline around the pads
+    /// with a chamfered pin 1 corner, and an `F.CrtYd` rectangle 0.25mm (nominal
+    /// density) beyond it. EasyEDA's own fab and courtyard graphics are dropped; its
+    /// silkscreen is kept.
+    Ipc,
+}
+
+/// Courtyard excess (mm) beyond the pads and body for IPC-7351 nominal density.
+const IPC_COURTYARD_EXCESS: f32 = 0.25;
+
+/// How far (in mm) an EasyEDA symbol origin may lie outside the symbol's extent
+/// before it is considered bogus, e.g. missing from `head`.
+const MAX_ORIGIN_DISTANCE: f32 = 25.4;
+
+/// KiCad's standard schematic grid (50 mil).
+const DEFAULT_SYMBOL_GRID: f32 = 1.27;
+
+/// Helper to snap coordinates to the schematic grid (by default 50 mil / 1.27mm).
+/// This ensures wires can actually connect to the pins. A grid of 0 leaves `val` as is.
+fn snap_to_grid(val: f32, grid: f32) -> f32 {
+    if grid <= 0.0 {
+        return val;
+    }
     (val / grid).round() * grid
 }
 
@@ -16,21 +182,46 @@ fn snap_pin_length(val: f32) -> f32 {
     if val < 2.0 { 1.27 } else { 2.54 }
 }
 
+/// Pin angles further than this (in degrees) from a quarter turn are reported.
+const PIN_ROTATION_TOLERANCE: i32 = 2;
+
+/// Converts an EasyEDA pin rotation to KiCad's, snapped to 0/90/180/270.
+///
+/// KiCad only accepts quarter-turn pin orientations. Returns the KiCad rotation and
+/// how many degrees the original was off-axis.
+fn snap_pin_rotation(rotation: i32) -> (i32, i32) {
+    let rotation = (rotation + 180).rem_euclid(360);
+    let snapped = ((rotation + 45) / 90 * 90) % 360;
+    let deviation = (rotation - snapped).abs();
+    (snapped, deviation.min(360 - deviation))
+}
+
 /// Converts an EasyEDA symbol to a KiCad symbol.
 ///
 /// Handles conversion of pins, rectangles and other symbol elements while maintaining correct positioning.
-/// NOW ALIGNS ALL PINS TO A 50mil (1.27mm) GRID.
+/// NOW ALIGNS ALL PINS TO A 50mil (1.27mm) GRID (see [`ConvertOptions::symbol_grid`]).
 pub fn convert_symbol(ee_symbol: EeSymbol) -> Result<KiSymbol> {
+    convert_symbol_with_options(ee_symbol, &ConvertOptions::default())
+}
+
+/// Same as [`convert_symbol`], with explicit conversion options.
+pub fn convert_symbol_with_options(
+    ee_symbol: EeSymbol,
+    options: &ConvertOptions,
+) -> Result<KiSymbol> {
+    if !ee_symbol.units.is_empty() {
+        return convert_multi_unit_symbol(ee_symbol, options);
+    }
     let (bbox_x, bbox_y) = ee_symbol.bbox;
+    let grid = options.symbol_grid.unwrap_or(DEFAULT_SYMBOL_GRID);
     let mut raw_pins = Vec::new();
     let mut raw_rects = Vec::new();
 
     // 1. Extract raw positions in MM
+    let raw_x = |x: f32| ee_to_mm(x - bbox_x);
+    let raw_y = |y: f32| ee_to_mm(-(y - bbox_y));
     for ee_pin in &ee_symbol.pins {
-        raw_pins.push((
-            ee_to_mm(ee_pin.pos_x - bbox_x),
-            ee_to_mm(-(ee_pin.pos_y - bbox_y)),
-        ));
+        raw_pins.push((raw_x(ee_pin.pos_x), raw_y(ee_pin.pos_y)));
     }
     for ee_rect in &ee_symbol.rectangles {
         let start_x = ee_to_mm(ee_rect.x - bbox_x);
@@ -41,108 +232,515 @@ pub fn convert_symbol(ee_symbol: EeSymbol) -> Result<KiSymbol> {
     }
 
     // 2. Calculate Center
-    let mut min_x = f32::MAX;
-    let mut max_x = f32::MIN;
-    let mut min_y = f32::MAX;
-    let mut max_y = f32::MIN;
-
+    // Positions are already relative to EasyEDA's origin, which is used as is unless
+    // the centroid was asked for or the origin is nowhere near the symbol.
     // We calculate bounds primarily based on PINS to ensure pins are centered well.
     // If we include graphics, a large text label might throw off the pin alignment.
-    for &(x, y) in &raw_pins {
-        if x < min_x {
-            min_x = x;
-        }
-        if x > max_x {
-            max_x = x;
-        }
-        if y < min_y {
-            min_y = y;
-        }
-        if y > max_y {
-            max_y = y;
-        }
-    }
+    // Fallback to rectangles if no pins exist (graphical symbol).
+    let extent_points: Vec<(f32, f32)> = if !raw_pins.is_empty() {
+        raw_pins.clone()
+    } else {
+        let arc_ends = ee_symbol
+            .arcs
+            .iter()
+            .flat_map(|arc| svg_path::path_segments(&arc.path))
+            .flat_map(|segment| [segment.from(), segment.to()])
+            .map(|(x, y)| (raw_x(x), raw_y(y)));
+        raw_rects
+            .iter()
+            .flat_map(|&(s, e)| [s, e])
+            .chain(arc_ends)
+            .collect()
+    };
 
-    // Fallback if no pins exist (graphical symbol)
-    if raw_pins.is_empty() {
-        for &((sx, sy), (ex, ey)) in &raw_rects {
-            for &(x, y) in &[(sx, sy), (ex, ey)] {
-                if x < min_x {
-                    min_x = x;
-                }
-                if x > max_x {
-                    max_x = x;
-                }
-                if y < min_y {
-                    min_y = y;
-                }
-                if y > max_y {
-                    max_y = y;
-                }
+    // Nothing to measure (e.g. a logo made only of unsupported graphics):
+    // keep the origin instead of deriving a center from empty min/max bounds.
+    let (center_x, center_y) = if extent_points.is_empty() {
+        (0.0, 0.0)
+    } else {
+        let mut min_x = f32::MAX;
+        let mut max_x = f32::MIN;
+        let mut min_y = f32::MAX;
+        let mut max_y = f32::MIN;
+        for &(x, y) in &extent_points {
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            min_y = min_y.min(y);
+            max_y = max_y.max(y);
+        }
+        let origin_is_near = min_x - MAX_ORIGIN_DISTANCE <= 0.0
+            && 0.0 <= max_x + MAX_ORIGIN_DISTANCE
+            && min_y - MAX_ORIGIN_DISTANCE <= 0.0
+            && 0.0 <= max_y + MAX_ORIGIN_DISTANCE;
+        if options.symbol_origin == SymbolOrigin::Head && origin_is_near {
+            (0.0, 0.0)
+        } else {
+            if options.symbol_origin == SymbolOrigin::Head {
+                log::warn!(
+                    "Origin of symbol '{}' lies outside the symbol; centering on its pins",
+                    ee_symbol.info.name
+                );
             }
+            // We snap the center calculation itself to the grid to avoid sub-grid offsets
+            (
+                snap_to_grid((min_x + max_x) / 2.0, grid),
+                snap_to_grid((min_y + max_y) / 2.0, grid),
+            )
         }
-    }
-
-    // We snap the center calculation itself to the grid to avoid sub-grid offsets
-    let center_x = snap_to_grid((min_x + max_x) / 2.0);
-    let center_y = snap_to_grid((min_y + max_y) / 2.0);
+    };
 
-    // 3. Create KiCad Pins (Snapped to 50 mil / 1.27mm grid)
+    // 3. Create KiCad Pins (Snapped to the grid, 50 mil / 1.27mm by default)
+    let default_pin_type = options.default_pin_type.unwrap_or(KiPinType::Passive);
+    let mut unmapped_pin_types: Vec<&str> = Vec::new();
+    let mut off_axis_pins: Vec<&str> = Vec::new();
     let mut ki_pins = Vec::new();
     for (ee_pin, &(x, y)) in ee_symbol.pins.iter().zip(raw_pins.iter()) {
         // This math solves the "Half Grid" issue.
         // Even if the pin was at 0.635mm relative to center, this rounds it to 1.27mm.
-        let snapped_x = snap_to_grid(x - center_x);
-        let snapped_y = snap_to_grid(y - center_y);
+        let snapped_x = snap_to_grid(x - center_x, grid);
+        let snapped_y = snap_to_grid(y - center_y, grid);
+        let (rotation, deviation) = snap_pin_rotation(ee_pin.rotation);
+        if deviation > PIN_ROTATION_TOLERANCE {
+            off_axis_pins.push(&ee_pin.number);
+        }
 
         ki_pins.push(KiSymbolPin {
             name: ee_pin.name.clone(),
             number: ee_pin.number.clone(),
-            pin_type: map_pin_type(&ee_pin.pin_type),
+            pin_type: map_pin_type(&ee_pin.pin_type).unwrap_or_else(|| {
+                // "0"/empty is EasyEDA's "undefined"; only unknown codes are worth a warning.
+                let code = ee_pin.pin_type.trim();
+                if !matches!(code, "" | "0") && !unmapped_pin_types.contains(&code) {
+                    unmapped_pin_types.push(code);
+                }
+                default_pin_type
+            }),
             length: snap_pin_length(ee_to_mm(ee_pin.pin_length)), // Also snap length
             pos: (snapped_x, snapped_y),
-            rotation: (ee_pin.rotation + 180) % 360,
+            rotation,
+            hidden: false,
         });
     }
 
+    // Pins sharing a number are one electrical pin drawn several times (e.g. the GND
+    // pins of a power IC). KiCad expects these stacked: keep the first visible and
+    // place the rest, hidden, on top of it.
+    let mut first_by_number: HashMap<String, usize> = HashMap::new();
+    for i in 0..ki_pins.len() {
+        match first_by_number.get(&ki_pins[i].number) {
+            Some(&first) => {
+                let (pos, rotation, length) = (
+                    ki_pins[first].pos,
+                    ki_pins[first].rotation,
+                    ki_pins[first].length,
+                );
+                let pin = &mut ki_pins[i];
+                pin.pos = pos;
+                pin.rotation = rotation;
+                pin.length = length;
+                pin.hidden = true;
+            }
+            None => {
+                first_by_number.insert(ki_pins[i].number.clone(), i);
+            }
+        }
+    }
+
     // 4. Create KiCad Rectangles (Also snapped to prevent detachment)
     let mut ki_rects = Vec::new();
-    for (_, &((sx, sy), (ex, ey))) in ee_symbol.rectangles.iter().zip(raw_rects.iter()) {
+    for (ee_rect, &((sx, sy), (ex, ey))) in ee_symbol.rectangles.iter().zip(raw_rects.iter()) {
         ki_rects.push(KiSymbolRect {
-            start: (snap_to_grid(sx - center_x), snap_to_grid(sy - center_y)),
-            end: (snap_to_grid(ex - center_x), snap_to_grid(ey - center_y)),
+            stroke_width: symbol_stroke_width(ee_rect.stroke_width),
+            fill: symbol_fill(ee_rect),
+            start: (
+                snap_to_grid(sx - center_x, grid),
+                snap_to_grid(sy - center_y, grid),
+            ),
+            end: (
+                snap_to_grid(ex - center_x, grid),
+                snap_to_grid(ey - center_y, grid),
+            ),
         });
     }
 
+    if options.synthesize_body && ki_rects.is_empty() && !ki_pins.is_empty() {
+        ki_rects.push(synthesize_body(&ki_pins, grid));
+    }
+
+    // 5. Create KiCad Arcs. Curves are not snapped; their ends may sit off-grid.
+    let mut arc_notes = Vec::new();
+    let to_local = |(x, y): (f32, f32)| (raw_x(x) - center_x, raw_y(y) - center_y);
+    let mut ki_arcs = Vec::new();
+    let mut ki_polylines: Vec<Vec<(f32, f32)>> = Vec::new();
+    for ee_arc in &ee_symbol.arcs {
+        let mut previous_end = None;
+        for shape in arc_path_shapes(&ee_arc.path, options.arc_segments, &mut arc_notes) {
+            match shape {
+                ArcShape::Arc { start, mid, end } => {
+                    ki_arcs.push(KiSymbolArc {
+                        start: to_local(start),
+                        mid: to_local(mid),
+                        end: to_local(end),
+                    });
+                    previous_end = None;
+                }
+                // Consecutive straight pieces join into one polyline.
+                ArcShape::Line { start, end } => {
+                    match ki_polylines.last_mut() {
+                        Some(points) if previous_end == Some(start) => points.push(to_local(end)),
+                        _ => ki_polylines.push(vec![to_local(start), to_local(end)]),
+                    }
+                    previous_end = Some(end);
+                }
+            }
+        }
+    }
+
+    let mut warnings: Vec<String> = unmapped_pin_types
+        .iter()
+        .map(|code| {
+            format!(
+                "Symbol '{}': EasyEDA pin type '{}' has no KiCad mapping; using {:?}",
+                ee_symbol.info.name, code, default_pin_type
+            )
+        })
+        .collect();
+    warnings.extend(
+        arc_notes
+            .into_iter()
+            .map(|note| format!("Symbol '{}': {}", ee_symbol.info.name, note)),
+    );
+    if !off_axis_pins.is_empty() {
+        warnings.push(format!(
+            "Symbol '{}': pins {} are not at a multiple of 90 degrees; snapped to the nearest",
+            ee_symbol.info.name,
+            off_axis_pins.join(", ")
+        ));
+    }
+    for number in duplicate_numbers(ki_pins.iter().map(|p| p.number.as_str())) {
+        // Same number and same name is a deliberate stack; only differing names conflict.
+        let mut names = ki_pins
+            .iter()
+            .filter(|p| p.number == number)
+            .map(|p| p.name.as_str());
+        let first_name = names.next();
+        if names.all(|n| Some(n) == first_name) {
+            continue;
+        }
+        let msg = format!(
+            "Symbol '{}': pin number '{}' is used by more than one pin",
+            ee_symbol.info.name, number
+        );
+        if options.duplicate_numbers_are_errors {
+            return Err(Error::ParseError(msg));
+        }
+        warnings.push(msg);
+    }
+
     Ok(KiSymbol {
         name: ee_symbol.info.name,
         reference: ee_symbol.info.prefix,
         footprint: ee_symbol.info.package.unwrap_or_default(),
         datasheet: ee_symbol.info.datasheet.unwrap_or_default(),
         lcsc_part: ee_symbol.info.lcsc_id,
-        pins: ki_pins,
         rectangles: ki_rects,
+        arcs: ki_arcs,
+        polylines: ki_polylines,
+        units: Vec::new(),
         is_extended: ee_symbol.info.is_extended,
+        description: ee_symbol.info.description,
+        keywords: ee_symbol.info.keywords,
+        manufacturer: ee_symbol.info.manufacturer,
+        mpn: ee_symbol.info.mpn,
+        // A lone pin's number says nothing (test points, fiducials).
+        hide_pin_numbers: ee_symbol.info.hide_pin_numbers || ki_pins.len() == 1,
+        hide_pin_names: ee_symbol.info.hide_pin_names,
+        pins: ki_pins,
+        warnings,
     })
 }
 
+/// Converts a symbol made of several parts, each into a unit of its own.
+///
+/// Every part is placed about its own origin, as EasyEDA draws it; the first part
+/// becomes unit 1 and carries the symbol's fields.
+fn convert_multi_unit_symbol(ee_symbol: EeSymbol, options: &ConvertOptions) -> Result<KiSymbol> {
+    let EeSymbol { info, units, .. } = ee_symbol;
+    let mut converted = Vec::new();
+    for unit in units {
+        converted.push(convert_symbol_with_options(
+            EeSymbol {
+                info: info.clone(),
+                ..unit
+            },
+            options,
+        )?);
+    }
+    let mut units = converted.into_iter();
+    let mut symbol = units.next().expect("multi-unit symbols have units");
+    for unit in units {
+        for warning in unit.warnings {
+            if !symbol.warnings.contains(&warning) {
+                symbol.warnings.push(warning);
+            }
+        }
+        symbol.units.push(KiSymbolUnit {
+            pins: unit.pins,
+            rectangles: unit.rectangles,
+            arcs: unit.arcs,
+            polylines: unit.polylines,
+        });
+    }
+    symbol.hide_pin_numbers = info.hide_pin_numbers || symbol.all_pins().count() == 1;
+    Ok(symbol)
+}
+
+/// Builds a body rectangle spanning the inner (body-side) ends of the pins.
+///
+/// Bodies that would be thinner than 5.08mm (e.g. a single row of connector pins)
+/// are widened away from the pins, and sides without pins get a one-grid margin so
+/// the outer pins don't sit on the corners.
+fn synthesize_body(pins: &[KiSymbolPin], grid: f32) -> KiSymbolRect {
+    const MIN_SIZE: f32 = 5.08;
+    let direction = |rotation: i32| match rotation.rem_euclid(360) {
+        90 => (0.0, 1.0),
+        180 => (-1.0, 0.0),
+        270 => (0.0, -1.0),
+        _ => (1.0, 0.0),
+    };
+
+    let (mut min_x, mut max_x) = (f32::MAX, f32::MIN);
+    let (mut min_y, mut max_y) = (f32::MAX, f32::MIN);
+    let (mut sum_dx, mut sum_dy) = (0.0, 0.0);
+    let (mut horizontal, mut vertical) = (false, false);
+    for pin in pins {
+        let (dx, dy) = direction(pin.rotation);
+        let (x, y) = (pin.pos.0 + dx * pin.length, pin.pos.1 + dy * pin.length);
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+        sum_dx += dx;
+        sum_dy += dy;
+        horizontal |= dx != 0.0;
+        vertical |= dy != 0.0;
+    }
+
+    // Grow a too-thin axis in the direction the pins point into the body.
+    let widen = |min: &mut f32, max: &mut f32, towards: f32| {
+        let missing = MIN_SIZE - (*max - *min);
+        if missing > 0.0 {
+            if towards > 0.0 {
+                *max += missing;
+            } else if towards < 0.0 {
+                *min -= missing;
+            } else {
+                *min -= missing / 2.0;
+                *max += missing / 2.0;
+            }
+        }
+    };
+    widen(&mut min_x, &mut max_x, sum_dx);
+    widen(&mut min_y, &mut max_y, sum_dy);
+
+    let margin = if grid > 0.0 {
+        grid
+    } else {
+        DEFAULT_SYMBOL_GRID
+    };
+    if !vertical {
+        min_y -= margin;
+        max_y += margin;
+    }
+    if !horizontal {
+        min_x -= margin;
+        max_x += margin;
+    }
+
+    KiSymbolRect {
+        stroke_width: DEFAULT_SYMBOL_STROKE,
+        fill: FillType::Background,
+        start: (snap_to_grid(min_x, grid), snap_to_grid(max_y, grid)),
+        end: (snap_to_grid(max_x, grid), snap_to_grid(min_y, grid)),
+    }
+}
+
+/// Symbol outline width (mm) used when EasyEDA gives none.
+const DEFAULT_SYMBOL_STROKE: f32 = 0.254;
+
+/// Widest symbol outline (mm) kept; anything bolder is almost certainly bad data.
+const MAX_SYMBOL_STROKE: f32 = 1.27;
+
+/// Symbol outline width in mm from EasyEDA's stroke width, clamped to
+/// `0..=MAX_SYMBOL_STROKE`. Missing or unusable widths give the default.
+fn symbol_stroke_width(stroke_width: Option<f32>) -> f32 {
+    stroke_width
+        .filter(|w| w.is_finite() && *w >= 0.0)
+        .map_or(DEFAULT_SYMBOL_STROKE, |w| {
+            ee_to_mm(w).min(MAX_SYMBOL_STROKE)
+        })
+}
+
+/// Fill of a symbol rectangle: none for `none`, solid when filled with its outline
+/// color, and KiCad's body background for any other color. Rectangles that don't say
+/// keep the background fill.
+fn symbol_fill(rect: &EeSymbolRectangle) -> FillType {
+    match rect.fill_color.as_deref() {
+        None | Some("") => FillType::Background,
+        Some(fill) if fill.eq_ignore_ascii_case("none") => FillType::None,
+        Some(fill)
+            if rect
+                .stroke_color
+                .as_deref()
+                .is_some_and(|stroke| stroke.eq_ignore_ascii_case(fill)) =>
+        {
+            FillType::Solid
+        }
+        Some(_) => FillType::Background,
+    }
+}
+
 /// Converts an EasyEDA footprint to a KiCad footprint. (Now implemented)
 /// Converts EasyEDA units to millimeters.
 ///
 /// EasyEDA uses units that are 1/0.254 mm, this converts to standard millimeters.
 fn ee_to_mm(val: f32) -> f32 {
-    val * 0.254
+    val * EE_UNIT_MM
+}
+
+/// Size of an EasyEDA unit (10 mil) in mm.
+const EE_UNIT_MM: f32 = 0.254;
+
+/// Imperial chip size codes and the length (mm) of parts in those packages.
+const CHIP_SIZES: &[(&str, f32)] = &[
+    ("01005", 0.4),
+    ("0201", 0.6),
+    ("0402", 1.0),
+    ("0603", 1.6),
+    ("0805", 2.0),
+    ("1206", 3.2),
+    ("1210", 3.2),
+    ("1812", 4.5),
+    ("2010", 5.0),
+    ("2512", 6.4),
+];
+
+/// The largest body dimension (mm) a footprint's name states, from EasyEDA's
+/// `..._L4.9-W3.9-...` naming or a chip size code such as `C0805`.
+fn nominal_size(name: &str) -> Option<f32> {
+    let number = |s: &str| -> Option<f32> {
+        let end = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        s[..end].parse().ok()
+    };
+    for part in name.split(['_', ' ']) {
+        if let Some((length, width)) = part.strip_prefix('L').and_then(|p| p.split_once("-W"))
+            && let (Some(length), Some(width)) = (number(length), number(width))
+        {
+            return Some(length.max(width));
+        }
+    }
+    // A code on its own or after a one-letter prefix, not inside a longer number.
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter_map(|token| {
+            let digits = token.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+            (token.len() - digits.len() <= 1).then_some(digits)
+        })
+        .find_map(|digits| CHIP_SIZES.iter().find(|(code, _)| *code == digits))
+        .map(|&(_, size)| size)
+}
+
+/// Checks that the pads span a plausible size for the part, to catch footprints in
+/// an unexpected unit. Returns a note if they don't.
+fn check_footprint_size(name: &str, pads: &[FpPad], scale: f32) -> Option<String> {
+    let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+    for pad in pads {
+        let half = (pad.size.0.max(pad.size.1) / 2.0) * scale;
+        let (x, y) = (pad.pos.0 * scale, pad.pos.1 * scale);
+        min = (min.0.min(x - half), min.1.min(y - half));
+        max = (max.0.max(x + half), max.1.max(y + half));
+    }
+    if pads.is_empty() {
+        return None;
+    }
+    let extent = (max.0 - min.0).max(max.1 - min.1);
+    let suggest = |expected: f32| {
+        // Only the powers of ten a unit mix-up would produce.
+        let factor = 10f32.powf((expected / extent).log10().round());
+        format!(
+            "; if the unit is wrong, try ConvertOptions::unit_scale = {}",
+            EE_UNIT_MM * scale * factor
+        )
+    };
+    match nominal_size(name) {
+        Some(nominal) if !(0.25..=4.0).contains(&(extent / nominal)) => Some(format!(
+            "pads span {:.2}mm, implausible for a {}mm part{}",
+            extent,
+            nominal,
+            suggest(nominal)
+        )),
+        None if extent > 100.0 => Some(format!(
+            "pads span {:.2}mm, implausibly large{}",
+            extent,
+            suggest(10.0)
+        )),
+        _ => None,
+    }
+}
+
+/// KiCad layer for each single-layer EasyEDA layer id, as used for footprint
+/// graphics. Override entries with [`ConvertOptions::layer_overrides`].
+///
+/// | id | EasyEDA layer | KiCad layer |
+/// |----|---------------|-------------|
+/// | 3/4 | Top/Bottom Silkscreen | `F.SilkS`/`B.SilkS` |
+/// | 5/6 | Top/Bottom Paste Mask | `F.Paste`/`B.Paste` |
+/// | 7/8 | Top/Bottom Solder Mask | `F.Mask`/`B.Mask` |
+/// | 10 | Board Outline | `Edge.Cuts` |
+/// | 11 | Multi-Layer (slot and cutout outlines) | `Edge.Cuts` |
+/// | 12 | Document | `Cmts.User` |
+/// | 13/14 | Top/Bottom Assembly | `F.Fab`/`B.Fab` |
+/// | 15 | Mechanical | `Dwgs.User` |
+/// | 19 | 3D Model outline | `Eco1.User` |
+/// | 21-50 | Inner 1-30 | `In1.Cu`-`In30.Cu` (not in this table) |
+/// | 99 | Component Shape | `Eco1.User` |
+/// | 100 | Lead Shape | `Eco2.User` |
+/// | 101 | Component Marking | `F.Fab` |
+pub const LAYER_TABLE: &[(i32, &str)] = &[
+    (3, "F.SilkS"),
+    (4, "B.SilkS"),
+    (5, "F.Paste"),
+    (6, "B.Paste"),
+    (7, "F.Mask"),
+    (8, "B.Mask"),
+    (10, "Edge.Cuts"),
+    (11, "Edge.Cuts"),
+    (12, "Cmts.User"),
+    (13, "F.Fab"),
+    (14, "B.Fab"),
+    (15, "Dwgs.User"),
+    (19, "Eco1.User"),
+    (99, "Eco1.User"),
+    (100, "Eco2.User"),
+    (101, "F.Fab"),
+];
+
+/// Whether footprint tracks and arcs on `layer` are kept. Copper, paste and mask
+/// drawings are usually pad outlines or net ties and are skipped.
+fn is_graphic_layer(layer: &str) -> bool {
+    !(layer.ends_with(".Cu") || layer.ends_with(".Paste") || layer.ends_with(".Mask"))
 }
 
 /// Maps EasyEDA layer IDs to KiCad layer names.
-fn map_layer(layer_id: i32, is_smd: bool) -> Vec<String> {
+fn map_layer(layer_id: i32, is_smd: bool) -> Option<Vec<String>> {
     // For through-hole pads, always use *.Cu and *.Mask regardless of layer_id
     if !is_smd {
-        return vec!["*.Cu".to_string(), "*.Mask".to_string()];
+        return Some(vec!["*.Cu".to_string(), "*.Mask".to_string()]);
     }
 
     // For SMD pads, use the appropriate layer mapping
-    match layer_id {
+    Some(match layer_id {
         1 => vec![
             "F.Cu".to_string(),
             "F.Paste".to_string(),
@@ -153,35 +751,189 @@ fn map_layer(layer_id: i32, is_smd: bool) -> Vec<String> {
             "B.Paste".to_string(),
             "B.Mask".to_string(),
         ], // Bottom Layer
-        3 => vec!["F.SilkS".to_string()], // Top Silkscreen
-        4 => vec!["B.SilkS".to_string()],
-        13 => vec!["F.Fab".to_string()],
-        15 => vec!["Dwgs.User".to_string()],
-        _ => vec!["F.Fab".to_string()], // Default fallback
+        21..=50 => vec![format!("In{}.Cu", layer_id - 20)],
+        _ => {
+            let (_, layer) = LAYER_TABLE.iter().find(|(id, _)| *id == layer_id)?;
+            // Unmapped ids: the caller applies `ConvertOptions::fallback_layer`
+            vec![layer.to_string()]
+        }
+    })
+}
+
+/// Narrowest line (in mm) written for footprint graphics.
+const MIN_GRAPHIC_WIDTH: f32 = 0.01;
+
+/// Converts an EasyEDA stroke width to mm for a graphic on `layer`.
+///
+/// A zero or missing width (which KiCad rejects) becomes the default for the layer
+/// (see [`ConvertOptions::silk_width`]); anything else is kept but clamped to
+/// [`MIN_GRAPHIC_WIDTH`]. Silkscreen and fab lines are then clamped to
+/// [`ConvertOptions::silk_width_range`].
+fn graphic_width(stroke_width: f32, layer: &str, options: &ConvertOptions) -> f32 {
+    let is_silk = layer.contains("SilkS");
+    let width = if !stroke_width.is_finite() || stroke_width <= 0.0 {
+        match layer {
+            _ if is_silk => options.silk_width.unwrap_or(0.12),
+            "Edge.Cuts" => 0.05,
+            _ => 0.1,
+        }
+    } else {
+        ee_to_mm(stroke_width).max(MIN_GRAPHIC_WIDTH)
+    };
+    match options.silk_width_range {
+        Some((min, max)) if is_silk || layer.ends_with(".Fab") => width.max(min).min(max),
+        _ => width,
     }
 }
 
 /// Maps EasyEDA pad shapes to KiCad pad shapes.
 ///
 /// Converts string shape names from EasyEDA format to KiCad's FpShape enum.
-fn map_shape(shape: &str) -> FpShape {
+///
+/// An ellipse is only a circle when it is as wide as it is high; elongated ones
+/// become ovals so they keep their aspect.
+fn map_shape(shape: &str, size: (f32, f32)) -> Option<FpShape> {
     match shape {
-        "ELLIPSE" => FpShape::Circle,
-        "RECT" => FpShape::Rect,
-        "OVAL" => FpShape::Oval,
-        _ => FpShape::Rect, // Default fallback
+        "ELLIPSE" if (size.0 - size.1).abs() > 1e-4 => Some(FpShape::Oval),
+        "ELLIPSE" => Some(FpShape::Circle),
+        "RECT" => Some(FpShape::Rect),
+        "OVAL" => Some(FpShape::Oval),
+        _ => None, // Caller falls back to a rectangle
+    }
+}
+
+/// Turns converted shapes by `angle` degrees about the footprint origin,
+/// counterclockwise on screen as KiCad measures it.
+///
+/// Pads keep their own rotation on top of the turn. Rectangles stay rectangles on
+/// quarter turns and become polygons otherwise; text keeps reading left to right.
+fn rotate_shapes(
+    angle: f32,
+    pads: &mut [FpPad],
+    graphics: &mut [FpGraphic],
+    zones: &mut [KiFootprintZone],
+    texts: &mut [FpText],
+) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    // Y points down, so a counterclockwise turn on screen is clockwise in (x, y).
+    let rotate = |p: &mut (f32, f32)| *p = (p.0 * cos + p.1 * sin, p.1 * cos - p.0 * sin);
+    let quarter_turn = (angle / 90.0).fract().abs() < 1e-4;
+
+    for pad in pads {
+        rotate(&mut pad.pos);
+        let (size, rotation, swapped) = normalize_pad_orientation(pad.size, pad.rotation + angle);
+        pad.size = size;
+        pad.rotation = rotation;
+        if swapped {
+            pad.drill_oval = pad.drill_oval.map(|(w, h)| (h, w));
+        }
+    }
+    for graphic in graphics {
+        match &mut graphic.graphic_type {
+            FpGraphicType::Rect { start, end } if !quarter_turn => {
+                let mut points = vec![*start, (end.0, start.1), *end, (start.0, end.1)];
+                points.iter_mut().for_each(rotate);
+                graphic.graphic_type = FpGraphicType::Poly {
+                    points,
+                    filled: false,
+                };
+            }
+            FpGraphicType::Line { start, end }
+            | FpGraphicType::Circle {
+                center: start, end, ..
+            }
+            | FpGraphicType::Rect { start, end } => {
+                rotate(start);
+                rotate(end);
+            }
+            FpGraphicType::Arc { start, mid, end } => {
+                rotate(start);
+                rotate(mid);
+                rotate(end);
+            }
+            FpGraphicType::Poly { points, .. } => points.iter_mut().for_each(rotate),
+        }
+    }
+    for zone in zones {
+        zone.points.iter_mut().for_each(rotate);
+    }
+    for text in texts {
+        rotate(&mut text.pos);
+    }
+}
+
+/// Normalizes a pad's size against its rotation.
+///
+/// Rect, oval and circle pads are symmetric under a 180° turn, so a pad rotated by a
+/// quarter turn is the same copper as the unrotated pad with width/height swapped.
+/// Folding those rotations into the size keeps the long axis of e.g. a rotated SOIC
+/// pad along the board axis KiCad expects, and leaves the pad at 0°.
+///
+/// Returns the new size, the rotation in `[0, 360)`, and whether the axes were swapped.
+fn normalize_pad_orientation(size: (f32, f32), rotation: f32) -> ((f32, f32), f32, bool) {
+    const EPS: f32 = 0.01;
+    let rotation = rotation.rem_euclid(360.0);
+    let quarter_turns = (rotation / 90.0).round();
+
+    if (rotation - quarter_turns * 90.0).abs() > EPS {
+        // Not axis-aligned; KiCad has to rotate it anyway.
+        return (size, rotation, false);
     }
+
+    if quarter_turns as i32 % 2 == 1 {
+        ((size.1, size.0), 0.0, true)
+    } else {
+        (size, 0.0, false)
+    }
+}
+
+/// Width and height (in mm) of the bounding box of a pad outline, if it has one.
+fn outline_extent(points: &[(f32, f32)]) -> Option<(f32, f32)> {
+    if points.len() < 3 {
+        return None;
+    }
+    let (mut min_x, mut max_x, mut min_y, mut max_y) = (f32::MAX, f32::MIN, f32::MAX, f32::MIN);
+    for &(x, y) in points {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    Some((ee_to_mm(max_x - min_x), ee_to_mm(max_y - min_y)))
+}
+
+/// Whether an outline extent matches a pad size, within 5% or 0.02mm.
+fn matches_size(extent: (f32, f32), size: (f32, f32)) -> bool {
+    let close = |a: f32, b: f32| (a - b).abs() <= (0.05 * a.max(b)).max(0.02);
+    close(extent.0, size.0) && close(extent.1, size.1)
+}
+
+/// Returns every number that occurs more than once, in first-seen order.
+fn duplicate_numbers<'a>(numbers: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for number in numbers {
+        match counts.iter_mut().find(|(n, _)| *n == number) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((number, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|(n, _)| n)
+        .collect()
 }
+
 /// Maps EasyEDA pin types to KiCad pin types.
 ///
 /// Converts EasyEDA's numeric pin type codes to KiCad's pin type enum.
-fn map_pin_type(ee_type: &str) -> KiPinType {
+fn map_pin_type(ee_type: &str) -> Option<KiPinType> {
     match ee_type {
-        "1" => KiPinType::Input,
-        "2" => KiPinType::Output,
-        "3" => KiPinType::Bidirectional,
-        "4" => KiPinType::PowerIn,
-        _ => KiPinType::Passive,
+        "1" => Some(KiPinType::Input),
+        "2" => Some(KiPinType::Output),
+        "3" => Some(KiPinType::Bidirectional),
+        "4" => Some(KiPinType::PowerIn),
+        _ => None, // Caller applies `ConvertOptions::default_pin_type`
     }
 }
 /// Converts an EasyEDA footprint to a KiCad footprint.
@@ -192,182 +944,770 @@ pub fn convert_footprint(
     ee_footprint: EeFootprint,
     ki_model: Option<Ki3dModel>,
 ) -> Result<KiFootprint> {
-    let mut ki_pads = Vec::new();
-    let mut ki_graphics = Vec::new();
-    let (bbox_x, bbox_y) = ee_footprint.bbox;
+    convert_footprint_with_options(ee_footprint, ki_model, &ConvertOptions::default())
+}
+
+/// A piece of a converted arc path, still in EasyEDA coordinates.
+#[derive(Clone, Copy)]
+enum ArcShape {
+    Arc {
+        start: (f32, f32),
+        mid: (f32, f32),
+        end: (f32, f32),
+    },
+    Line {
+        start: (f32, f32),
+        end: (f32, f32),
+    },
+}
+
+/// Turns an EasyEDA arc path into native arcs where possible.
+///
+/// Arcs KiCad can't draw natively (elliptical, near-full circles, zero radius) are
+/// approximated with `segments` straight lines (see [`ConvertOptions::arc_segments`]);
+/// straight path commands become lines. The result is empty only if nothing at all
+/// could be read from the path. Fallbacks are described in `warnings`.
+fn arc_path_shapes(path: &str, segments: usize, warnings: &mut Vec<String>) -> Vec<ArcShape> {
+    let mut shapes = Vec::new();
+    for segment in svg_path::path_segments(path) {
+        let PathSegment::Arc {
+            from,
+            to,
+            radii,
+            x_rotation,
+            large_arc,
+            sweep,
+        } = segment
+        else {
+            shapes.push(ArcShape::Line {
+                start: segment.from(),
+                end: segment.to(),
+            });
+            continue;
+        };
+
+        let Some(geometry) =
+            ArcGeometry::from_endpoints(from, to, radii, x_rotation, large_arc, sweep)
+        else {
+            warnings.push(format!(
+                "arc in path '{}' is degenerate; drawn as a straight line",
+                path
+            ));
+            if from != to {
+                shapes.push(ArcShape::Line {
+                    start: from,
+                    end: to,
+                });
+            }
+            continue;
+        };
+
+        let sweep_deg = geometry.sweep_angle.abs().to_degrees();
+        if geometry.is_circular() && (0.5..359.5).contains(&sweep_deg) {
+            shapes.push(ArcShape::Arc {
+                start: from,
+                mid: geometry.point_at(0.5),
+                end: to,
+            });
+            continue;
+        }
 
-    let mut raw_pad_pos = Vec::new();
-    for ee_pad in &ee_footprint.pads {
-        raw_pad_pos.push((
-            ee_to_mm(ee_pad.center_x - bbox_x),
-            ee_to_mm(ee_pad.center_y - bbox_y),
+        warnings.push(format!(
+            "arc in path '{}' can't be drawn natively; approximated with line segments",
+            path
         ));
+        let mut start = from;
+        for end in arc_points(&geometry, to, segments) {
+            shapes.push(ArcShape::Line { start, end });
+            start = end;
+        }
     }
-    let mut raw_text_pos = Vec::new();
-    for ee_text in &ee_footprint.texts {
-        raw_text_pos.push((
-            ee_to_mm(ee_text.center_x - bbox_x),
-            ee_to_mm(ee_text.center_y - bbox_y),
-        ));
+    shapes
+}
+
+/// Points approximating an arc with `segments` straight lines (one per 15° of sweep
+/// if `0`), after its start point and ending exactly at `to`.
+fn arc_points(geometry: &ArcGeometry, to: (f32, f32), segments: usize) -> Vec<(f32, f32)> {
+    let count = if segments > 0 {
+        segments
+    } else {
+        ((geometry.sweep_angle.abs().to_degrees() / 15.0).ceil() as usize).max(2)
+    };
+    (1..=count)
+        .map(|i| {
+            if i == count {
+                to
+            } else {
+                geometry.point_at(i as f32 / count as f32)
+            }
+        })
+        .collect()
+}
+
+/// Reads a path that encloses an area as polygon outlines, starting a new one
+/// wherever the path jumps (a moveto). Arcs are approximated as in
+/// [`arc_path_shapes`], since a polygon can't hold a native arc.
+fn path_outlines(path: &str, segments: usize, warnings: &mut Vec<String>) -> Vec<Vec<(f32, f32)>> {
+    let mut outlines: Vec<Vec<(f32, f32)>> = Vec::new();
+    for segment in svg_path::path_segments(path) {
+        let from = segment.from();
+        let points = match segment {
+            PathSegment::Arc {
+                to,
+                radii,
+                x_rotation,
+                large_arc,
+                sweep,
+                ..
+            } => match ArcGeometry::from_endpoints(from, to, radii, x_rotation, large_arc, sweep) {
+                Some(geometry) => arc_points(&geometry, to, segments),
+                None => {
+                    warnings.push(format!(
+                        "arc in path '{}' is degenerate; drawn as a straight line",
+                        path
+                    ));
+                    vec![to]
+                }
+            },
+            PathSegment::Line { to, .. } => vec![to],
+        };
+        match outlines.last_mut() {
+            Some(outline) if outline.last() == Some(&from) => outline.extend(points),
+            _ => outlines.push([vec![from], points].concat()),
+        }
+    }
+    for outline in &mut outlines {
+        if outline.len() > 1 && outline.first() == outline.last() {
+            outline.pop();
+        }
+    }
+    outlines
+}
+
+/// What converting a single footprint primitive needs to know about the footprint
+/// as a whole, and the fallbacks taken along the way.
+///
+/// Points are moved from EasyEDA's canvas into the footprint's frame: `origin`
+/// (EasyEDA units) is subtracted, the result converted to mm, and `center` (mm,
+/// normally the pads' centroid) subtracted.
+#[derive(Debug)]
+pub struct FootprintContext<'a> {
+    pub origin: (f32, f32),
+    pub center: (f32, f32),
+    pub options: &'a ConvertOptions,
+    /// EasyEDA layer ids with no KiCad mapping, which went to the fallback layer.
+    pub unmapped_layers: Vec<i32>,
+    /// Other fallbacks taken, worded as footprint warnings without the name.
+    pub notes: Vec<String>,
+    /// Number of graphics dropped for being oversized or far off-center.
+    pub out_of_range: usize,
+}
+
+impl<'a> FootprintContext<'a> {
+    pub fn new(origin: (f32, f32), center: (f32, f32), options: &'a ConvertOptions) -> Self {
+        FootprintContext {
+            origin,
+            center,
+            options,
+            unmapped_layers: Vec::new(),
+            notes: Vec::new(),
+            out_of_range: 0,
+        }
     }
 
-    // Calculate centroid (center of the component) based on pads
-    let mut sum_x = 0.0;
-    let mut sum_y = 0.0;
-    let mut count = 0.0;
-    for &(x, y) in &raw_pad_pos {
-        sum_x += x;
-        sum_y += y;
-        count += 1.0;
+    /// Moves an EasyEDA canvas point into the footprint's frame, in mm.
+    ///
+    /// Unlike `convert_symbol`, Y is NOT negated here. EasyEDA's PCB canvas and
+    /// KiCad's footprint editor are both Y-down, whereas KiCad symbol libraries are
+    /// Y-up. Negating here would mirror the footprint and swap pins on polarized parts.
+    pub fn to_local(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            ee_to_mm(x - self.origin.0) - self.center.0,
+            ee_to_mm(y - self.origin.1) - self.center.1,
+        )
+    }
+
+    /// Layer used for EasyEDA layer ids with no KiCad mapping.
+    fn fallback_layer(&self) -> &'a str {
+        self.options.fallback_layer.as_deref().unwrap_or("F.Fab")
+    }
+
+    /// KiCad layers for an EasyEDA layer id. Unknown ids go to the fallback layer,
+    /// remembering which ones did.
+    pub fn layers_for(&mut self, layer_id: i32, is_smd: bool) -> Vec<String> {
+        if let Some(layer) = self
+            .options
+            .layer_overrides
+            .get(&layer_id)
+            .filter(|_| is_smd)
+        {
+            return vec![layer.clone()];
+        }
+        map_layer(layer_id, is_smd).unwrap_or_else(|| {
+            if !self.unmapped_layers.contains(&layer_id) {
+                self.unmapped_layers.push(layer_id);
+            }
+            vec![self.fallback_layer().to_string()]
+        })
     }
-    // Fallback to text position if no pads exist (e.g. logo, fiducial)
-    if count == 0.0 {
-        for &(x, y) in &raw_text_pos {
-            sum_x += x;
-            sum_y += y;
-            count += 1.0;
+
+    /// Whether a point in the footprint's frame is too far out to be part of it; such
+    /// graphics are garbage (e.g. a frame border) and are counted as dropped.
+    fn is_out_of_range(&mut self, (x, y): (f32, f32)) -> bool {
+        let far = x.abs() > 150.0 || y.abs() > 150.0;
+        if far {
+            self.out_of_range += 1;
         }
+        far
     }
+}
 
-    let center_x = if count > 0.0 { sum_x / count } else { 0.0 };
-    let center_y = if count > 0.0 { sum_y / count } else { 0.0 };
+/// Converts an EasyEDA pad to a KiCad pad numbered `number`.
+pub fn convert_pad(
+    ee_pad: &EeFootprintPad,
+    number: String,
+    ctx: &mut FootprintContext<'_>,
+) -> FpPad {
+    let is_smd = ee_pad.hole_radius == 0.0 && ee_pad.hole_length == 0.0;
+    let (drill, drill_oval) = if is_smd {
+        (None, None)
+    } else if ee_pad.hole_length > 0.0 {
+        let drill_width = ee_to_mm(ee_pad.hole_radius * 2.0);
+        let drill_height = ee_to_mm(ee_pad.hole_length);
+        (None, Some((drill_width, drill_height)))
+    } else {
+        let drill_dia = ee_to_mm(ee_pad.hole_radius * 2.0);
+        (Some(drill_dia), None)
+    };
 
-    // --- PADS ---
-    for (idx, (ee_pad, &(x, y))) in ee_footprint.pads.iter().zip(raw_pad_pos.iter()).enumerate() {
-        let is_smd = ee_pad.hole_radius == 0.0 && ee_pad.hole_length == 0.0;
-        let pad_number = if ee_pad.number.trim().is_empty() {
-            (idx + 1).to_string()
-        } else {
-            ee_pad.number.clone()
-        };
+    let (mut size, rotation, mut swapped) = normalize_pad_orientation(
+        (ee_to_mm(ee_pad.width), ee_to_mm(ee_pad.height)),
+        -ee_pad.rotation,
+    );
+    // Some pads are stored with width/height already swapped for their rotation,
+    // so the rotation turns them the wrong way. The outline shows the real copper.
+    if rotation == 0.0
+        && let Some(extent) = outline_extent(&ee_pad.points)
+        && matches_size(extent, (size.1, size.0))
+        && !matches_size(extent, size)
+    {
+        size = (size.1, size.0);
+        swapped = !swapped;
+    }
+    // The slot lives in the pad's local frame, so it follows the size swap.
+    let drill_oval = if swapped {
+        drill_oval.map(|(w, h)| (h, w))
+    } else {
+        drill_oval
+    };
 
-        let (drill, drill_oval) = if is_smd {
-            (None, None)
-        } else if ee_pad.hole_length > 0.0 {
-            let drill_width = ee_to_mm(ee_pad.hole_radius * 2.0);
-            let drill_height = ee_to_mm(ee_pad.hole_length);
-            (None, Some((drill_width, drill_height)))
+    let shape = map_shape(&ee_pad.shape, size).unwrap_or_else(|| {
+        ctx.notes.push(format!(
+            "pad '{}' has unsupported shape '{}'; drawn as a rectangle",
+            number, ee_pad.shape
+        ));
+        FpShape::Rect
+    });
+
+    FpPad {
+        number,
+        pad_type: if is_smd {
+            "smd".to_string()
         } else {
-            let drill_dia = ee_to_mm(ee_pad.hole_radius * 2.0);
-            (Some(drill_dia), None)
-        };
+            "thru_hole".to_string()
+        },
+        shape,
+        pos: ctx.to_local((ee_pad.center_x, ee_pad.center_y)),
+        size,
+        layers: ctx.layers_for(ee_pad.layer_id, is_smd),
+        rotation,
+        drill,
+        drill_oval,
+        thermal: if is_smd { None } else { ctx.options.thermal },
+        clearance: ee_pad.clearance.map(ee_to_mm),
+        net: Some(ee_pad.net.trim())
+            .filter(|net| !net.is_empty())
+            .map(str::to_string),
+    }
+}
 
-        ki_pads.push(FpPad {
-            number: pad_number,
-            pad_type: if is_smd {
-                "smd".to_string()
-            } else {
-                "thru_hole".to_string()
-            },
-            shape: map_shape(&ee_pad.shape),
-            pos: (x - center_x, y - center_y),
-            size: (ee_to_mm(ee_pad.width), ee_to_mm(ee_pad.height)),
-            layers: map_layer(ee_pad.layer_id, is_smd),
-            rotation: -ee_pad.rotation,
-            drill,
-            drill_oval,
-        });
+/// Converts a mechanical hole to an unnumbered NPTH pad with no copper of its own.
+pub fn convert_hole(hole: &EeFootprintHole, ctx: &FootprintContext<'_>) -> FpPad {
+    let diameter = ee_to_mm(hole.radius * 2.0);
+    FpPad {
+        number: String::new(),
+        pad_type: "np_thru_hole".to_string(),
+        shape: FpShape::Circle,
+        pos: ctx.to_local((hole.center_x, hole.center_y)),
+        size: (diameter, diameter),
+        layers: vec!["*.Cu".to_string(), "*.Mask".to_string()],
+        rotation: 0.0,
+        drill: Some(diameter),
+        drill_oval: None,
+        thermal: None,
+        clearance: None,
+        net: None,
     }
+}
 
-    // --- TRACKS (Lines/Polygons) ---
-    // This provides the body outline on silkscreen/fab layers
-    for track in &ee_footprint.tracks {
-        let layers = map_layer(track.layer_id, true);
-        let layer_name = &layers[0];
-
-        // Skip copper tracks (Layer 1/2) unless you specifically want net ties.
-        // Usually footprint graphics are on Silk(3/4), Fab(13), or Doc(15).
-        let is_graphic_layer = layer_name.contains("Silk")
-            || layer_name.contains("Fab")
-            || layer_name.contains("Dwgs");
-
-        if is_graphic_layer && track.points.len() >= 2 {
-            let width = ee_to_mm(track.stroke_width);
-
-            for i in 0..track.points.len() - 1 {
-                let (x1, y1) = track.points[i];
-                let (x2, y2) = track.points[i + 1];
-
-                let start_x = ee_to_mm(x1 - bbox_x) - center_x;
-                let start_y = ee_to_mm(y1 - bbox_y) - center_y;
-                let end_x = ee_to_mm(x2 - bbox_x) - center_x;
-                let end_y = ee_to_mm(y2 - bbox_y) - center_y;
-
-                // SANITY CHECK: Distance
-                // If a line is > 150mm away from the center, it's garbage (e.g. frame border).
-                if start_x.abs() > 150.0 || start_y.abs() > 150.0 {
-                    continue;
-                }
+/// Converts a track to lines, or to one polygon when it is closed and
+/// [`ConvertOptions::closed_tracks_as_poly`] is set. Tracks on copper are skipped.
+pub fn convert_track(track: &EeFootprintTrack, ctx: &mut FootprintContext<'_>) -> Vec<FpGraphic> {
+    let layer_name = ctx.layers_for(track.layer_id, true).swap_remove(0);
+    // Usually footprint graphics are on Silk(3/4), Fab(13), or Doc(15).
+    if !is_graphic_layer(&layer_name) || track.points.len() < 2 {
+        return Vec::new();
+    }
+    let width = graphic_width(track.stroke_width, &layer_name, ctx.options);
 
-                ki_graphics.push(FpGraphic {
-                    layer: layer_name.clone(),
-                    width,
-                    graphic_type: FpGraphicType::Line {
-                        start: (start_x, start_y),
-                        end: (end_x, end_y),
-                    },
-                });
-            }
+    let is_closed = track.points.len() >= 4 && track.points.first() == track.points.last();
+    if is_closed && ctx.options.closed_tracks_as_poly {
+        let points: Vec<(f32, f32)> = track.points[..track.points.len() - 1]
+            .iter()
+            .map(|&p| ctx.to_local(p))
+            .collect();
+        if points.iter().any(|&p| ctx.is_out_of_range(p)) {
+            return Vec::new();
         }
+        return vec![FpGraphic {
+            layer: layer_name,
+            width,
+            graphic_type: FpGraphicType::Poly {
+                points,
+                filled: false,
+            },
+        }];
     }
 
-    // --- CIRCLES ---
-    for circle in &ee_footprint.circles {
-        // FILTER: Ignore circles on Fab/Doc layers (13, 15).
-        // EasyEDA often puts "Pick and Place Origin" or "Collision Radii" here which are massive.
-        // We only want Silkscreen (3, 4) or Copper (1, 2).
-        if circle.layer_id != 1
-            && circle.layer_id != 2
-            && circle.layer_id != 3
-            && circle.layer_id != 4
-        {
+    let mut lines = Vec::new();
+    for pair in track.points.windows(2) {
+        let (start, end) = (ctx.to_local(pair[0]), ctx.to_local(pair[1]));
+        if ctx.is_out_of_range(start) {
             continue;
         }
+        lines.push(FpGraphic {
+            layer: layer_name.clone(),
+            width,
+            graphic_type: FpGraphicType::Line { start, end },
+        });
+    }
+    lines
+}
 
-        let layers = map_layer(circle.layer_id, true);
-        let layer_name = &layers[0];
+/// Converts a rectangle, kept on whatever layer it was drawn on so keepouts on
+/// copper stay there.
+pub fn convert_rect(rect: &EeFootprintRect, ctx: &mut FootprintContext<'_>) -> Option<FpGraphic> {
+    let layer_name = ctx.layers_for(rect.layer_id, true).swap_remove(0);
+    let start = ctx.to_local((rect.x, rect.y));
+    if ctx.is_out_of_range(start) {
+        return None;
+    }
+    Some(FpGraphic {
+        width: graphic_width(rect.stroke_width, &layer_name, ctx.options),
+        layer: layer_name,
+        graphic_type: FpGraphicType::Rect {
+            start,
+            end: (
+                start.0 + ee_to_mm(rect.width),
+                start.1 + ee_to_mm(rect.height),
+            ),
+        },
+    })
+}
 
-        let cx = ee_to_mm(circle.center_x - bbox_x) - center_x;
-        let cy = ee_to_mm(circle.center_y - bbox_y) - center_y;
-        let radius = ee_to_mm(circle.radius);
+/// Converts a circle on a copper or silkscreen layer; others are skipped, as are
+/// filled circles on copper.
+pub fn convert_circle(
+    circle: &EeFootprintCircle,
+    ctx: &mut FootprintContext<'_>,
+) -> Option<FpGraphic> {
+    // FILTER: Ignore circles on Fab/Doc layers (13, 15).
+    // EasyEDA often puts "Pick and Place Origin" or "Collision Radii" here which are massive.
+    // We only want Silkscreen (3, 4) or Copper (1, 2).
+    if !matches!(circle.layer_id, 1..=4) {
+        return None;
+    }
+    // Filled discs on copper become pads; see `convert_copper_disc`.
+    if circle.filled && matches!(circle.layer_id, 1 | 2) {
+        return None;
+    }
+    let layer_name = ctx.layers_for(circle.layer_id, true).swap_remove(0);
+    let (cx, cy) = ctx.to_local((circle.center_x, circle.center_y));
+    let radius = ee_to_mm(circle.radius);
 
-        // SANITY CHECK: Distance
-        // If the circle center is miles away, drop it.
-        if cx.abs() > 150.0 || cy.abs() > 150.0 {
-            continue;
+    // SANITY CHECK: Size
+    // If the circle is massive (>50mm radius), it's likely a collision courtyard, not a graphic.
+    if ctx.is_out_of_range((cx, cy)) {
+        return None;
+    }
+    if radius > 50.0 {
+        ctx.out_of_range += 1;
+        return None;
+    }
+
+    Some(FpGraphic {
+        width: graphic_width(circle.stroke_width, &layer_name, ctx.options),
+        layer: layer_name,
+        graphic_type: FpGraphicType::Circle {
+            center: (cx, cy),
+            end: (cx + radius, cy),
+            filled: circle.filled,
+        },
+    })
+}
+
+/// Converts a filled circle on a copper layer to an unnumbered round SMD pad, so KiCad
+/// treats the disc as copper land. Returns `None` for any other circle, which
+/// [`convert_circle`] handles.
+pub fn convert_copper_disc(
+    circle: &EeFootprintCircle,
+    ctx: &mut FootprintContext<'_>,
+) -> Option<FpPad> {
+    if !circle.filled || !matches!(circle.layer_id, 1 | 2) {
+        return None;
+    }
+    let layer_name = ctx.layers_for(circle.layer_id, true).swap_remove(0);
+    let pos = ctx.to_local((circle.center_x, circle.center_y));
+    if ctx.is_out_of_range(pos) {
+        return None;
+    }
+    // The outline stroke is centered on the radius, so the disc reaches half of it further.
+    let diameter = ee_to_mm(circle.radius * 2.0 + circle.stroke_width);
+    Some(FpPad {
+        number: String::new(),
+        pad_type: "smd".to_string(),
+        shape: FpShape::Circle,
+        pos,
+        size: (diameter, diameter),
+        layers: vec![layer_name],
+        rotation: 0.0,
+        drill: None,
+        drill_oval: None,
+        thermal: None,
+        clearance: None,
+        net: None,
+    })
+}
+
+/// Converts an arc to native arcs, or lines where it has to be approximated. Arcs on
+/// copper are skipped.
+pub fn convert_arc(arc: &EeFootprintArc, ctx: &mut FootprintContext<'_>) -> Vec<FpGraphic> {
+    let layer_name = ctx.layers_for(arc.layer_id, true).swap_remove(0);
+    if !is_graphic_layer(&layer_name) {
+        return Vec::new();
+    }
+    let width = graphic_width(arc.stroke_width, &layer_name, ctx.options);
+
+    let shapes = arc_path_shapes(&arc.path, ctx.options.arc_segments, &mut ctx.notes);
+    if shapes.is_empty() {
+        ctx.notes
+            .push(format!("arc with unusable path '{}' was dropped", arc.path));
+        return Vec::new();
+    }
+    stroked_path_graphics(shapes, &layer_name, width, ctx)
+}
+
+/// Converts a path drawn by an `SVGNODE` (e.g. a silkscreen logo): filled paths
+/// become one filled polygon per outline, others lines and arcs. Paths on copper are
+/// skipped.
+pub fn convert_svg_path(
+    svg: &EeFootprintSvgPath,
+    ctx: &mut FootprintContext<'_>,
+) -> Vec<FpGraphic> {
+    let layer_name = ctx.layers_for(svg.layer_id, true).swap_remove(0);
+    if !is_graphic_layer(&layer_name) {
+        return Vec::new();
+    }
+    let width = graphic_width(svg.stroke_width, &layer_name, ctx.options);
+
+    if !svg.filled {
+        let shapes = arc_path_shapes(&svg.path, ctx.options.arc_segments, &mut ctx.notes);
+        if !shapes.is_empty() {
+            return stroked_path_graphics(shapes, &layer_name, width, ctx);
+        }
+    } else {
+        let outlines = path_outlines(&svg.path, ctx.options.arc_segments, &mut ctx.notes);
+        if !outlines.is_empty() {
+            return filled_path_graphics(outlines, &layer_name, width, ctx);
         }
+    }
+    ctx.notes.push(format!(
+        "SVG path '{}' could not be read and was dropped",
+        svg.path
+    ));
+    Vec::new()
+}
 
-        // SANITY CHECK: Size
-        // If the circle is massive (>50mm radius), it's likely a collision courtyard, not a graphic.
-        if radius > 50.0 {
+/// Fills each outline of a path as a polygon on `layer`, dropping any that reach
+/// far off the footprint.
+fn filled_path_graphics(
+    outlines: Vec<Vec<(f32, f32)>>,
+    layer_name: &str,
+    width: f32,
+    ctx: &mut FootprintContext<'_>,
+) -> Vec<FpGraphic> {
+    let mut graphics = Vec::new();
+    for outline in outlines {
+        if outline.len() < 3 {
+            continue;
+        }
+        let points: Vec<(f32, f32)> = outline.into_iter().map(|p| ctx.to_local(p)).collect();
+        if points.iter().any(|&p| ctx.is_out_of_range(p)) {
             continue;
         }
+        graphics.push(FpGraphic {
+            layer: layer_name.to_string(),
+            width,
+            graphic_type: FpGraphicType::Poly {
+                points,
+                filled: true,
+            },
+        });
+    }
+    graphics
+}
 
-        let end_x = cx + radius;
-        let end_y = cy;
+/// Draws the pieces of a path as lines and arcs on `layer`, dropping any that start
+/// far off the footprint.
+fn stroked_path_graphics(
+    shapes: Vec<ArcShape>,
+    layer_name: &str,
+    width: f32,
+    ctx: &mut FootprintContext<'_>,
+) -> Vec<FpGraphic> {
+    let mut graphics = Vec::new();
+    for shape in shapes {
+        let (ArcShape::Arc { start, .. } | ArcShape::Line { start, .. }) = shape;
+        if ctx.is_out_of_range(ctx.to_local(start)) {
+            continue;
+        }
+        let graphic_type = match shape {
+            ArcShape::Arc { start, mid, end } => FpGraphicType::Arc {
+                start: ctx.to_local(start),
+                mid: ctx.to_local(mid),
+                end: ctx.to_local(end),
+            },
+            ArcShape::Line { start, end } => FpGraphicType::Line {
+                start: ctx.to_local(start),
+                end: ctx.to_local(end),
+            },
+        };
+        graphics.push(FpGraphic {
+            layer: layer_name.to_string(),
+            width,
+            graphic_type,
+        });
+    }
+    graphics
+}
 
-        ki_graphics.push(FpGraphic {
-            layer: layer_name.clone(),
-            width: ee_to_mm(circle.stroke_width),
-            graphic_type: FpGraphicType::Circle {
-                center: (cx, cy),
-                end: (end_x, end_y),
+/// Converts a dimension to plain lines on the comments layer. A length measurement
+/// is labelled with its longest straight line, which is the measured one.
+pub fn convert_dimension(
+    dimension: &EeFootprintDimension,
+    ctx: &mut FootprintContext<'_>,
+) -> (Vec<FpGraphic>, Option<FpText>) {
+    let layer = "Cmts.User".to_string();
+    let width = graphic_width(0.0, &layer, ctx.options);
+    let mut graphics = Vec::new();
+    let mut longest: Option<((f32, f32), (f32, f32))> = None;
+    for shape in arc_path_shapes(&dimension.path, ctx.options.arc_segments, &mut ctx.notes) {
+        let graphic_type = match shape {
+            ArcShape::Arc { start, mid, end } => FpGraphicType::Arc {
+                start: ctx.to_local(start),
+                mid: ctx.to_local(mid),
+                end: ctx.to_local(end),
             },
+            ArcShape::Line { start, end } => {
+                let (start, end) = (ctx.to_local(start), ctx.to_local(end));
+                let length = |(s, e): ((f32, f32), (f32, f32))| (e.0 - s.0).hypot(e.1 - s.1);
+                if longest.is_none_or(|l| length((start, end)) > length(l)) {
+                    longest = Some((start, end));
+                }
+                FpGraphicType::Line { start, end }
+            }
+        };
+        graphics.push(FpGraphic {
+            layer: layer.clone(),
+            width,
+            graphic_type,
         });
     }
+    let label = longest
+        .filter(|_| !dimension.is_angle)
+        .map(|(start, end)| FpText {
+            text_type: "user".to_string(),
+            text: format!("{:.2}mm", (end.0 - start.0).hypot(end.1 - start.1)),
+            pos: ((start.0 + end.0) / 2.0, (start.1 + end.1) / 2.0 - 0.5),
+            layer,
+        });
+    (graphics, label)
+}
+
+/// Converts a cutout region on copper to keepout zones where no copper may go, one
+/// per outline of its path. Other region types, and cutouts on other layers, are
+/// not converted.
+pub fn convert_region(
+    region: &EeFootprintRegion,
+    ctx: &mut FootprintContext<'_>,
+) -> Vec<KiFootprintZone> {
+    if region.region_type != "cutout" {
+        ctx.notes.push(format!(
+            "{} region on layer {} was not converted",
+            region.region_type, region.layer_id
+        ));
+        return Vec::new();
+    }
+    let layers = match region.layer_id {
+        1 => vec!["F.Cu".to_string()],
+        2 => vec!["B.Cu".to_string()],
+        11 => vec!["F.Cu".to_string(), "B.Cu".to_string()],
+        id @ 21..=50 => vec![format!("In{}.Cu", id - 20)],
+        other => {
+            ctx.notes.push(format!(
+                "cutout region on non-copper layer {} was not converted",
+                other
+            ));
+            return Vec::new();
+        }
+    };
+    let outlines: Vec<Vec<(f32, f32)>> =
+        path_outlines(&region.path, ctx.options.arc_segments, &mut ctx.notes)
+            .into_iter()
+            .filter(|outline| outline.len() >= 3)
+            .collect();
+    if outlines.is_empty() {
+        ctx.notes.push(format!(
+            "keepout region with unusable path '{}' was dropped",
+            region.path
+        ));
+        return Vec::new();
+    }
+    outlines
+        .into_iter()
+        .map(|outline| KiFootprintZone {
+            layers: layers.clone(),
+            points: outline.into_iter().map(|p| ctx.to_local(p)).collect(),
+            keepout: KiKeepout {
+                tracks: false,
+                vias: false,
+                pads: true,
+                copperpour: false,
+                footprints: true,
+            },
+        })
+        .collect()
+}
+
+/// Same as [`convert_footprint`], with explicit conversion options.
+pub fn convert_footprint_with_options(
+    ee_footprint: EeFootprint,
+    ki_model: Option<Ki3dModel>,
+    options: &ConvertOptions,
+) -> Result<KiFootprint> {
+    let mut ctx = FootprintContext::new(ee_footprint.bbox, (0.0, 0.0), options);
+
+    // The footprint is centered on its pads, or on its texts if it has no pads
+    // (e.g. logo, fiducial).
+    let pad_pos = ee_footprint.pads.iter().map(|p| (p.center_x, p.center_y));
+    let text_pos = ee_footprint.texts.iter().map(|t| (t.center_x, t.center_y));
+    let anchors: Vec<(f32, f32)> = if ee_footprint.pads.is_empty() {
+        text_pos.map(|p| ctx.to_local(p)).collect()
+    } else {
+        pad_pos.map(|p| ctx.to_local(p)).collect()
+    };
+    if !anchors.is_empty() {
+        let count = anchors.len() as f32;
+        let sum = anchors
+            .iter()
+            .fold((0.0, 0.0), |sum, p| (sum.0 + p.0, sum.1 + p.1));
+        ctx.center = (sum.0 / count, sum.1 / count);
+    }
+
+    // --- PADS ---
+    // Pad numbers are kept exactly as EasyEDA has them (BGA "A1" etc.). Only pads with
+    // no number at all get one from their index, skipping numbers already in use.
+    let mut used_numbers: Vec<String> = ee_footprint
+        .pads
+        .iter()
+        .filter(|p| !p.number.trim().is_empty())
+        .map(|p| p.number.clone())
+        .collect();
+    let mut ki_pads = Vec::new();
+    for (idx, ee_pad) in ee_footprint.pads.iter().enumerate() {
+        let pad_number = if ee_pad.number.trim().is_empty() {
+            let number = (idx + 1..)
+                .map(|n| n.to_string())
+                .find(|n| !used_numbers.contains(n))
+                .unwrap_or_default();
+            used_numbers.push(number.clone());
+            number
+        } else {
+            ee_pad.number.clone()
+        };
+        ki_pads.push(convert_pad(ee_pad, pad_number, &mut ctx));
+    }
+
+    let mut ki_pads = merge_coincident_pads(ki_pads);
+
+    // --- HOLES ---
+    ki_pads.extend(ee_footprint.holes.iter().map(|h| convert_hole(h, &ctx)));
+
+    // --- GRAPHICS ---
+    // Tracks provide the body outline on silkscreen/fab layers.
+    let mut ki_graphics = Vec::new();
+    for track in &ee_footprint.tracks {
+        ki_graphics.extend(convert_track(track, &mut ctx));
+    }
+    for rect in &ee_footprint.rects {
+        ki_graphics.extend(convert_rect(rect, &mut ctx));
+    }
+    for circle in &ee_footprint.circles {
+        ki_pads.extend(convert_copper_disc(circle, &mut ctx));
+        ki_graphics.extend(convert_circle(circle, &mut ctx));
+    }
+    for arc in &ee_footprint.arcs {
+        ki_graphics.extend(convert_arc(arc, &mut ctx));
+    }
+    for svg in &ee_footprint.svg_paths {
+        ki_graphics.extend(convert_svg_path(svg, &mut ctx));
+    }
+
+    // --- DIMENSIONS ---
+    let mut dimension_texts = Vec::new();
+    if !options.skip_dimensions {
+        for dimension in &ee_footprint.dimensions {
+            let (graphics, label) = convert_dimension(dimension, &mut ctx);
+            ki_graphics.extend(graphics);
+            dimension_texts.extend(label);
+        }
+    }
+
+    // --- KEEPOUT ZONES ---
+    let mut ki_zones = Vec::new();
+    for region in &ee_footprint.regions {
+        ki_zones.extend(convert_region(region, &mut ctx));
+    }
 
     // --- TEXTS ---
+    let reference = reference_text(
+        options
+            .reference_prefix
+            .as_deref()
+            .or(ee_footprint.info.prefix.as_deref()),
+    );
     let mut ki_texts = Vec::new();
-    for (ee_text, &(x, y)) in ee_footprint.texts.iter().zip(raw_text_pos.iter()) {
+    for ee_text in &ee_footprint.texts {
         let (text_type, text) = match ee_text.text_type.as_str() {
             "P" => ("value".to_string(), ee_footprint.info.name.clone()),
-            "N" => ("reference".to_string(), "REF**".to_string()),
+            "N" => ("reference".to_string(), reference.clone()),
             _ => ("user".to_string(), ee_text.text.clone()),
         };
 
         // Standardize layers for text
-        let mut layer = map_layer(ee_text.layer_id, true)
-            .get(0)
+        let mut layer = ctx
+            .layers_for(ee_text.layer_id, true)
+            .first()
             .unwrap_or(&"F.Fab".to_string())
             .clone();
 
@@ -382,11 +1722,63 @@ pub fn convert_footprint(
         ki_texts.push(FpText {
             text_type,
             text,
-            pos: (x - center_x, y - center_y),
+            pos: ctx.to_local((ee_text.center_x, ee_text.center_y)),
             layer,
         });
     }
 
+    ki_texts.extend(dimension_texts);
+
+    // A rotation of the whole footprint turns everything EasyEDA drew, before the
+    // fields below are placed around the result. EasyEDA turns clockwise.
+    if ee_footprint.rotation.rem_euclid(360.0) != 0.0 {
+        rotate_shapes(
+            -ee_footprint.rotation,
+            &mut ki_pads,
+            &mut ki_graphics,
+            &mut ki_zones,
+            &mut ki_texts,
+        );
+    }
+
+    if options.fab_style == FabStyle::Ipc {
+        match ipc_outlines(&ki_pads) {
+            Some(outlines) => {
+                ki_graphics.retain(|g| !is_fab_or_courtyard(&g.layer));
+                ki_graphics.extend(outlines);
+            }
+            None => ctx
+                .notes
+                .push("no pads to derive an IPC fab outline from".to_string()),
+        }
+    }
+
+    // KiCad footprints need both fields; add any EasyEDA didn't have, centered above
+    // and below the footprint's copper and graphics.
+    let has_text = |kind: &str| ki_texts.iter().any(|t| t.text_type == kind);
+    let (needs_reference, needs_value) = (!has_text("reference"), !has_text("value"));
+    if needs_reference || needs_value {
+        let ((left, top), (right, bottom)) =
+            footprint_bounds(&ki_pads, &ki_graphics).unwrap_or(((0.0, 0.0), (0.0, 0.0)));
+        let center_x = (left + right) / 2.0;
+        if needs_reference {
+            ki_texts.push(FpText {
+                text_type: "reference".to_string(),
+                text: reference.clone(),
+                pos: (center_x, top - DEFAULT_TEXT_OFFSET),
+                layer: "F.SilkS".to_string(),
+            });
+        }
+        if needs_value {
+            ki_texts.push(FpText {
+                text_type: "value".to_string(),
+                text: ee_footprint.info.name.clone(),
+                pos: (center_x, bottom + DEFAULT_TEXT_OFFSET),
+                layer: "F.Fab".to_string(),
+            });
+        }
+    }
+
     // automatic marker for Pin1
     let pin1 = ki_pads
         .iter()
@@ -439,28 +1831,350 @@ pub fn convert_footprint(
                 // KiCad circle is defined by Center + Point on Edge.
                 // We add the radius to X to define that edge point.
                 end: (dot_x + marker_radius, dot_y),
+                filled: false,
             },
         });
     }
 
-    Ok(KiFootprint {
+    let fallback_layer = ctx.fallback_layer();
+    let mut notes = std::mem::take(&mut ctx.notes);
+    let mut warnings: Vec<String> = ctx
+        .unmapped_layers
+        .iter()
+        .map(|id| {
+            format!(
+                "Footprint '{}': EasyEDA layer {} has no KiCad mapping; placed on {}",
+                ee_footprint.info.name, id, fallback_layer
+            )
+        })
+        .collect();
+
+    // KiCad silently treats same-numbered pads as one net; make that visible.
+    // Unnumbered pads (mechanical holes) aren't connected to anything.
+    let numbers = ki_pads.iter().map(|p| p.number.as_str());
+    for number in duplicate_numbers(numbers.filter(|n| !n.is_empty())) {
+        let msg = format!(
+            "Footprint '{}': pad number '{}' is used by more than one pad",
+            ee_footprint.info.name, number
+        );
+        if options.duplicate_numbers_are_errors {
+            return Err(Error::ParseError(msg));
+        }
+        warnings.push(msg);
+    }
+
+    let mut unsupported: Vec<(&str, usize)> = Vec::new();
+    for shape in &ee_footprint.unsupported_shapes {
+        match unsupported.iter_mut().find(|(s, _)| s == shape) {
+            Some((_, count)) => *count += 1,
+            None => unsupported.push((shape, 1)),
+        }
+    }
+    for (shape, count) in unsupported {
+        notes.push(format!("{} unsupported {} shape(s) skipped", count, shape));
+    }
+    if ctx.out_of_range > 0 {
+        notes.push(format!(
+            "{} oversized or far off-center graphic(s) dropped",
+            ctx.out_of_range
+        ));
+    }
+    let scale = options.unit_scale.map_or(1.0, |unit| unit / EE_UNIT_MM);
+    notes.extend(check_footprint_size(
+        &ee_footprint.info.name,
+        &ki_pads,
+        scale,
+    ));
+    warnings.extend(
+        notes
+            .into_iter()
+            .map(|note| format!("Footprint '{}': {}", ee_footprint.info.name, note)),
+    );
+
+    let non_component =
+        is_non_component(ee_footprint.info.prefix.as_deref(), &ee_footprint.info.name);
+    let mut footprint = KiFootprint {
+        bottom_side: false,
+        exclude_from_bom: options.exclude_from_bom.unwrap_or(non_component),
+        exclude_from_pos: options.exclude_from_pos.unwrap_or(non_component),
         name: ee_footprint.info.name,
         pads: ki_pads,
         texts: ki_texts,
         graphics: ki_graphics,
-        model_3d: ki_model,
-    })
+        zones: ki_zones,
+        model_3d: ki_model.map(|mut model| {
+            // Relative to the footprint origin, with Y up as in KiCad's model space.
+            if let Some(origin) = model.origin {
+                let (x, y) = ctx.to_local(origin);
+                model.offset[0] = x;
+                model.offset[1] = -y;
+            }
+            if ee_footprint.rotation.rem_euclid(360.0) != 0.0 {
+                // Counterclockwise seen from above, as the footprint turned.
+                let angle = (-ee_footprint.rotation).to_radians();
+                let (sin, cos) = angle.sin_cos();
+                let (x, y) = (model.offset[0], model.offset[1]);
+                model.offset[0] = x * cos - y * sin;
+                model.offset[1] = x * sin + y * cos;
+                model.rotate[2] = (model.rotate[2] - ee_footprint.rotation).rem_euclid(360.0);
+            }
+            model
+        }),
+        warnings,
+    };
+    if scale != 1.0 {
+        footprint.scale(scale);
+    }
+    Ok(footprint)
 }
 
+/// Merges pads that are the same physical pad split over several entries (e.g.
+/// copper and paste drawn separately): same number, position and size. The merged
+/// pad keeps the first one's settings and the layers of all of them.
+fn merge_coincident_pads(pads: Vec<FpPad>) -> Vec<FpPad> {
+    const EPS: f32 = 1e-4;
+    let same = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < EPS && (a.1 - b.1).abs() < EPS;
+    let mut merged: Vec<FpPad> = Vec::with_capacity(pads.len());
+    for pad in pads {
+        let existing = merged
+            .iter_mut()
+            .find(|m| m.number == pad.number && same(m.pos, pad.pos) && same(m.size, pad.size));
+        match existing {
+            Some(existing) => {
+                for layer in pad.layers {
+                    if !existing.layers.contains(&layer) {
+                        existing.layers.push(layer);
+                    }
+                }
+                // A drilled entry makes the merged pad through-hole.
+                if existing.drill.is_none() && existing.drill_oval.is_none() {
+                    existing.pad_type = pad.pad_type;
+                    existing.drill = pad.drill;
+                    existing.drill_oval = pad.drill_oval;
+                    existing.thermal = pad.thermal;
+                }
+                if existing.net.is_none() {
+                    existing.net = pad.net;
+                }
+            }
+            None => merged.push(pad),
+        }
+    }
+    merged
+}
+
+/// Footprint reference text for a designator prefix: `R**` for `R` or `R?`, and
+/// KiCad's generic `REF**` when there is no prefix.
+fn reference_text(prefix: Option<&str>) -> String {
+    let prefix = prefix.unwrap_or("").trim().trim_end_matches(['?', '*']);
+    if prefix.is_empty() {
+        "REF**".to_string()
+    } else {
+        format!("{}**", prefix)
+    }
+}
+
+/// Distance (in mm) between the footprint and a reference or value text added for it.
+const DEFAULT_TEXT_OFFSET: f32 = 1.5;
+
+/// Returns the top-left and bottom-right corners of the box covering `pads` and
+/// `graphics`, if there are any.
+fn footprint_bounds(pads: &[FpPad], graphics: &[FpGraphic]) -> Option<((f32, f32), (f32, f32))> {
+    let mut points = Vec::new();
+    for pad in pads {
+        let half = pad.size.0.max(pad.size.1) / 2.0;
+        let (x, y) = pad.pos;
+        points.extend([(x - half, y - half), (x + half, y + half)]);
+    }
+    for graphic in graphics {
+        match &graphic.graphic_type {
+            FpGraphicType::Line { start, end } | FpGraphicType::Rect { start, end } => {
+                points.extend([*start, *end]);
+            }
+            FpGraphicType::Arc { start, mid, end } => points.extend([*start, *mid, *end]),
+            FpGraphicType::Circle { center, end, .. } => {
+                let radius = (end.0 - center.0).hypot(end.1 - center.1);
+                points.extend([
+                    (center.0 - radius, center.1 - radius),
+                    (center.0 + radius, center.1 + radius),
+                ]);
+            }
+            FpGraphicType::Poly { points: poly, .. } => points.extend(poly.iter().copied()),
+        }
+    }
+    let (first, rest) = points.split_first()?;
+    Some(rest.iter().fold((*first, *first), |(min, max), p| {
+        (
+            (min.0.min(p.0), min.1.min(p.1)),
+            (max.0.max(p.0), max.1.max(p.1)),
+        )
+    }))
+}
+
+/// Returns the top-left and bottom-right corners of the box covering the pads' copper,
+/// taking each pad's rotation into account.
+fn pad_extents(pads: &[FpPad]) -> Option<((f32, f32), (f32, f32))> {
+    let mut corners = pads.iter().flat_map(|pad| {
+        let (sin, cos) = pad.rotation.to_radians().sin_cos();
+        let half_w = (pad.size.0 * cos.abs() + pad.size.1 * sin.abs()) / 2.0;
+        let half_h = (pad.size.0 * sin.abs() + pad.size.1 * cos.abs()) / 2.0;
+        let (x, y) = pad.pos;
+        [(x - half_w, y - half_h), (x + half_w, y + half_h)]
+    });
+    let first = corners.next()?;
+    Some(corners.fold((first, first), |(min, max), p| {
+        (
+            (min.0.min(p.0), min.1.min(p.1)),
+            (max.0.max(p.0), max.1.max(p.1)),
+        )
+    }))
+}
+
+/// Whether a layer holds fab or courtyard graphics, which [`FabStyle::Ipc`] replaces.
+fn is_fab_or_courtyard(layer: &str) -> bool {
+    layer.ends_with(".Fab") || layer.ends_with(".CrtYd")
+}
+
+/// The fab outline and courtyard for [`FabStyle::Ipc`], or `None` without pads.
+///
+/// The fab outline follows the pads' extent, with the corner nearest pin 1 chamfered
+/// by 25% of the shorter side, at most 1mm. The courtyard clears it by
+/// [`IPC_COURTYARD_EXCESS`], rounded outward to 0.01mm.
+fn ipc_outlines(pads: &[FpPad]) -> Option<Vec<FpGraphic>> {
+    let ((left, top), (right, bottom)) = pad_extents(pads)?;
+    let corners = [(left, top), (right, top), (right, bottom), (left, bottom)];
+
+    let pin1 = pads
+        .iter()
+        .find(|p| p.number == "1")
+        .or_else(|| pads.iter().find(|p| p.number == "A1"));
+    let fab = match pin1 {
+        Some(pin1) => {
+            let distance = |c: &(f32, f32)| (c.0 - pin1.pos.0).hypot(c.1 - pin1.pos.1);
+            let nearest = (0..4)
+                .min_by(|&a, &b| distance(&corners[a]).total_cmp(&distance(&corners[b])))
+                .unwrap_or(0);
+            let chamfer = ((right - left).min(bottom - top) * 0.25).min(1.0);
+            // Walk the outline from the chamfered corner, cutting it off at both ends.
+            let toward = |from: (f32, f32), to: (f32, f32)| {
+                let length = (to.0 - from.0).hypot(to.1 - from.1);
+                let t = if length > 0.0 { chamfer / length } else { 0.0 };
+                (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+            };
+            let corner = corners[nearest];
+            let next = corners[(nearest + 1) % 4];
+            let previous = corners[(nearest + 3) % 4];
+            let points = vec![
+                toward(corner, next),
+                next,
+                corners[(nearest + 2) % 4],
+                previous,
+                toward(corner, previous),
+            ];
+            FpGraphicType::Poly {
+                points,
+                filled: false,
+            }
+        }
+        None => FpGraphicType::Rect {
+            start: (left, top),
+            end: (right, bottom),
+        },
+    };
+
+    // Rounded outward, ignoring float noise just past a grid line.
+    let down = |v: f32| (v * 100.0 + 1e-3).floor() / 100.0;
+    let up = |v: f32| (v * 100.0 - 1e-3).ceil() / 100.0;
+    let courtyard = FpGraphicType::Rect {
+        start: (
+            down(left - IPC_COURTYARD_EXCESS),
+            down(top - IPC_COURTYARD_EXCESS),
+        ),
+        end: (
+            up(right + IPC_COURTYARD_EXCESS),
+            up(bottom + IPC_COURTYARD_EXCESS),
+        ),
+    };
+    Some(vec![
+        FpGraphic {
+            layer: "F.Fab".to_string(),
+            width: 0.1,
+            graphic_type: fab,
+        },
+        FpGraphic {
+            layer: "F.CrtYd".to_string(),
+            width: 0.05,
+            graphic_type: courtyard,
+        },
+    ])
+}
+
+/// Whether a footprint is a test point, fiducial or mounting hole rather than a part
+/// that is bought and placed, judged by its reference prefix and name.
+fn is_non_component(prefix: Option<&str>, name: &str) -> bool {
+    let prefix = prefix
+        .unwrap_or("")
+        .trim_end_matches(['?', '*'])
+        .to_ascii_uppercase();
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    matches!(prefix.as_str(), "TP" | "FID" | "MH")
+        || ["TESTPOINT", "FIDUCIAL", "MOUNTINGHOLE"]
+            .iter()
+            .any(|k| name.contains(k))
+}
+
+/// Share of unreadable OBJ numbers above which a model is rejected as corrupt.
+#[cfg(feature = "3d")]
+const MAX_BAD_OBJ_FRACTION: f32 = 0.05;
+
 /// Converts an EasyEDA 3D model (with raw OBJ data) to a KiCad 3D model (VRML).
 ///
 /// Converts vertices and faces from OBJ format to VRML format, applying appropriate scaling
 /// and maintaining all geometric information.
-pub fn convert_3d_model(mut ee_model: Ee3dModel) -> Result<Ki3dModel> {
-    let wrl_data = if let Some(obj_data) = &ee_model.raw_obj {
+///
+/// Unreadable coordinates and face indices are replaced (with 0 and vertex 1) and
+/// reported in the model's warnings. If more than 5% of them are unreadable, the
+/// download is treated as garbage and [`Error::ModelConversionError`] is returned.
+#[cfg(feature = "3d")]
+pub fn convert_3d_model(ee_model: Ee3dModel) -> Result<Ki3dModel> {
+    convert_3d_model_with_format(ee_model, Model3dFormat::default())
+}
+
+/// Same as [`convert_3d_model`], producing only the files `format` asks for.
+#[cfg(feature = "3d")]
+pub fn convert_3d_model_with_format(
+    ee_model: Ee3dModel,
+    format: Model3dFormat,
+) -> Result<Ki3dModel> {
+    convert_3d_model_with_options(ee_model, format, &ConvertOptions::default())
+}
+
+/// Same as [`convert_3d_model_with_format`], with explicit conversion options.
+#[cfg(feature = "3d")]
+pub fn convert_3d_model_with_options(
+    mut ee_model: Ee3dModel,
+    format: Model3dFormat,
+    options: &ConvertOptions,
+) -> Result<Ki3dModel> {
+    let obj_data = ee_model.raw_obj.as_ref().filter(|_| format.wants_wrl());
+    let mut warnings = Vec::new();
+    let wrl_data = if let Some(obj_data) = obj_data {
         // --- Functional but simplified OBJ to WRL converter ---
         let mut vertices = Vec::new();
-        let mut faces = Vec::new();
+        let mut normals = Vec::new();
+        // EasyEDA embeds its `newmtl ... endmtl` blocks directly in the OBJ text.
+        let mut materials: HashMap<String, ObjMaterial> = HashMap::new();
+        let mut defining: Option<String> = None;
+        let mut active_material = String::new();
+        // Faces grouped by material, in order of first use.
+        let mut groups: Vec<(String, Vec<ObjFace>)> = Vec::new();
+        // Every coordinate and index read, and how many of them were unusable.
+        let mut values_read = 0usize;
+        let mut bad_values = 0usize;
 
         for line in obj_data.lines() {
             let parts: Vec<&str> = line.split_whitespace().collect();
@@ -468,77 +2182,439 @@ pub fn convert_3d_model(mut ee_model: Ee3dModel) -> Result<Ki3dModel> {
                 continue;
             }
             match parts[0] {
-                "v" => {
-                    // Vertex
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        // EasyEDA OBJ seems to be in inches*10. Convert to mm and scale down by 10
-                        vertices.push(Vec3::new(x, y, z) * 0.254 * 1.55); // 1.55 is a scaler that seems to fix scaling values
+                // Vertex
+                "v" if parts.len() >= 4 => {
+                    values_read += 3;
+                    let x = obj_number(parts[1], &mut bad_values);
+                    let y = obj_number(parts[2], &mut bad_values);
+                    let z = obj_number(parts[3], &mut bad_values);
+                    // EasyEDA OBJ seems to be in inches*10. Convert to mm and scale down by 10
+                    vertices.push(Vec3::new(x, y, z) * 0.254 * 1.55); // 1.55 is a scaler that seems to fix scaling values
+                }
+                // Vertex normal
+                "vn" if parts.len() >= 4 => {
+                    values_read += 3;
+                    let x = obj_number(parts[1], &mut bad_values);
+                    let y = obj_number(parts[2], &mut bad_values);
+                    let z = obj_number(parts[3], &mut bad_values);
+                    // Vertices are scaled uniformly, so normals only need normalizing.
+                    normals.push(Vec3::new(x, y, z).normalize_or_zero());
+                }
+                // Face
+                "f" if parts.len() >= 4 => {
+                    // OBJ faces are 1-indexed. We need 0-indexed.
+                    // Format is f v1//vn1 v2//vn2 v3//vn3 (texture/normal refs optional)
+                    let mut face = ObjFace::default();
+                    for corner in &parts[1..] {
+                        let mut refs = corner.split('/');
+                        let vertex = refs
+                            .next()
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .and_then(|v| v.checked_sub(1));
+                        if vertex.is_none() {
+                            bad_values += 1;
+                        }
+                        face.vertices.push(vertex.unwrap_or(0));
+                        face.normals.push(
+                            refs.nth(1)
+                                .and_then(|n| n.parse::<usize>().ok())
+                                .and_then(|n| n.checked_sub(1)),
+                        );
+                    }
+                    match groups.iter_mut().find(|(m, _)| *m == active_material) {
+                        Some((_, faces)) => faces.push(face),
+                        None => groups.push((active_material.clone(), vec![face])),
                     }
                 }
-                "f" => {
-                    // Face
-                    if parts.len() >= 4 {
-                        // OBJ faces are 1-indexed. We need 0-indexed.
-                        // Format is f v1//vn1 v2//vn2 v3//vn3
-                        let face_indices: Vec<usize> = parts[1..]
-                            .iter()
-                            .map(|p| {
-                                p.split('/')
-                                    .next()
-                                    .unwrap_or("1")
-                                    .parse::<usize>()
-                                    .unwrap_or(1)
-                                    - 1
-                            })
-                            .collect();
-                        faces.push(face_indices);
+                // Materials
+                "newmtl" if parts.len() >= 2 => {
+                    materials.insert(parts[1].to_string(), ObjMaterial::default());
+                    defining = Some(parts[1].to_string());
+                }
+                "endmtl" => defining = None,
+                "usemtl" if parts.len() >= 2 => active_material = parts[1].to_string(),
+                "Kd" | "Ks" | "d" | "Tr" => {
+                    let Some(material) = defining.as_ref().and_then(|m| materials.get_mut(m))
+                    else {
+                        continue;
+                    };
+                    let values: Vec<f32> =
+                        parts[1..].iter().filter_map(|p| p.parse().ok()).collect();
+                    match (parts[0], values.as_slice()) {
+                        ("Kd", &[r, g, b, ..]) => material.diffuse = Vec3::new(r, g, b),
+                        ("Ks", &[r, g, b, ..]) => material.specular = Some(Vec3::new(r, g, b)),
+                        ("d", &[d, ..]) => material.transparency = 1.0 - d,
+                        ("Tr", &[t, ..]) => material.transparency = t,
+                        _ => {}
                     }
                 }
-                _ => {} // Ignore other lines (materials, normals, etc. for now)
+                _ => {} // Ignore other lines (texture coords, etc. for now)
             }
         }
 
+        // Faces pointing past the vertex list are as broken as unreadable numbers.
+        let face_corners = groups.iter().flat_map(|(_, faces)| faces);
+        for face in face_corners {
+            values_read += face.vertices.len();
+            bad_values += face
+                .vertices
+                .iter()
+                .filter(|&&v| v >= vertices.len())
+                .count();
+        }
+        if bad_values > 0 {
+            if bad_values as f32 > values_read as f32 * MAX_BAD_OBJ_FRACTION {
+                return Err(Error::ModelConversionError(format!(
+                    "3D model '{}' looks corrupt: {} of {} OBJ values are unreadable",
+                    ee_model.name, bad_values, values_read
+                )));
+            }
+            let msg = format!(
+                "3D model '{}' has {} unreadable OBJ value(s); parts of the mesh may be misplaced",
+                ee_model.name, bad_values
+            );
+            log::warn!("{}", msg);
+            warnings.push(msg);
+        }
+
         let mut wrl = String::new();
         wrl.push_str("#VRML V2.0 utf8\n");
-        wrl.push_str("Shape {\n");
-        wrl.push_str("  appearance Appearance {\n");
-        wrl.push_str("    material Material { diffuseColor 0.5 0.5 0.5 }\n"); // Default grey
-        wrl.push_str("  }\n");
-        wrl.push_str("  geometry IndexedFaceSet {\n");
-        wrl.push_str("    coord Coordinate {\n");
-        wrl.push_str("      point [\n");
-        for v in &vertices {
-            wrl.push_str(&format!("        {:.4} {:.4} {:.4},\n", v.x, v.y, v.z));
-        }
-        wrl.push_str("      ]\n");
-        wrl.push_str("    }\n");
-        wrl.push_str("    coordIndex [\n");
-        for f in &faces {
-            let face_str = f
-                .iter()
-                .map(|i| i.to_string())
-                .collect::<Vec<String>>()
-                .join(", ");
-            wrl.push_str(&format!("      {}, -1,\n", face_str));
+        for (material_name, faces) in &groups {
+            let material = materials.get(material_name).cloned().unwrap_or_default();
+            write_wrl_shape(
+                &mut wrl,
+                &material,
+                &vertices,
+                &normals,
+                faces,
+                options.wrl_pretty,
+            );
         }
-        wrl.push_str("    ]\n");
-        wrl.push_str("  }\n");
-        wrl.push_str("}\n");
 
         Some(wrl)
     } else {
         None
     };
 
+    let placement = model_placement(&ee_model.transform);
     Ok(Ki3dModel {
         name: ee_model.name,
         wrl_data,
-        step_data: ee_model.step.take(),
-        offset: Vec3::ZERO,
-        scale: Vec3::ONE,
-        rotate: Vec3::ZERO,
+        step_data: ee_model.step.take().filter(|_| format.wants_step()),
+        offset: placement.offset,
+        scale: placement.scale,
+        rotate: placement.rotate,
+        origin: placement.origin,
+        warnings,
     })
 }
+
+/// A 3D model's KiCad placement, decomposed from its EasyEDA transform.
+#[cfg(feature = "3d")]
+struct ModelPlacement {
+    offset: Vec3,
+    scale: Vec3,
+    rotate: Vec3,
+    origin: Option<(f32, f32)>,
+}
+
+/// Decomposes an SVGNODE transform into KiCad's offset, rotation and scale.
+///
+/// `c_origin`, `z` and `c_rotation` are EasyEDA's own placement fields and win when
+/// present; the SVG `transform` matrix fills in the origin and Z rotation when they
+/// are missing, and supplies the scale. X/Y of the offset are left to the footprint
+/// converter (see [`Ki3dModel::origin`]).
+#[cfg(feature = "3d")]
+fn model_placement(transform: &Ee3dTransform) -> ModelPlacement {
+    let [a, b, c, d, e, f] = transform.matrix.unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    let (rx, ry, rz) = transform
+        .rotation
+        .unwrap_or((0.0, 0.0, b.atan2(a).to_degrees()));
+    // EasyEDA rotates clockwise when seen from above, KiCad counter-clockwise.
+    let kicad_angle = |angle: f32| (360.0 - angle).rem_euclid(360.0);
+
+    ModelPlacement {
+        // EasyEDA's Z points the other way from KiCad's, as in easyeda2kicad.py.
+        offset: Vec3::new(0.0, 0.0, -ee_to_mm(transform.z.unwrap_or(0.0))),
+        scale: Vec3::new(a.hypot(b), c.hypot(d), 1.0),
+        rotate: Vec3::new(kicad_angle(rx), kicad_angle(ry), kicad_angle(rz)),
+        origin: transform
+            .origin
+            .or_else(|| (e != 0.0 || f != 0.0).then_some((e, f))),
+    }
+}
+
+/// Parses an OBJ coordinate, counting it in `bad_values` (and using 0) if unreadable.
+#[cfg(feature = "3d")]
+fn obj_number(s: &str, bad_values: &mut usize) -> f32 {
+    match s.parse::<f32>() {
+        Ok(v) if v.is_finite() => v,
+        _ => {
+            *bad_values += 1;
+            0.0
+        }
+    }
+}
+
+/// A material parsed from the `newmtl` blocks of an EasyEDA OBJ.
+#[cfg(feature = "3d")]
+#[derive(Debug, Clone)]
+struct ObjMaterial {
+    diffuse: Vec3,
+    specular: Option<Vec3>,
+    transparency: f32,
+}
+
+#[cfg(feature = "3d")]
+impl Default for ObjMaterial {
+    fn default() -> Self {
+        ObjMaterial {
+            diffuse: Vec3::splat(0.5), // Default grey
+            specular: None,
+            transparency: 0.0,
+        }
+    }
+}
+
+/// A single OBJ face: vertex indices and, per corner, an optional normal index.
+#[cfg(feature = "3d")]
+#[derive(Debug, Clone, Default)]
+struct ObjFace {
+    vertices: Vec<usize>,
+    normals: Vec<Option<usize>>,
+}
+
+/// Writes one VRML `Shape` holding `faces` with the given material.
+///
+/// Each shape only carries the vertices (and normals) its faces reference, re-indexed
+/// from zero, so multi-material models don't repeat the full point list per shape.
+/// Normals are only emitted when every corner in the shape has one, since VRML can't
+/// mix explicit and implicit normals within a single `IndexedFaceSet`.
+#[cfg(feature = "3d")]
+fn write_wrl_shape(
+    wrl: &mut String,
+    material: &ObjMaterial,
+    vertices: &[Vec3],
+    normals: &[Vec3],
+    faces: &[ObjFace],
+    pretty: bool,
+) {
+    let mut vertex_index: HashMap<usize, usize> = HashMap::new();
+    let mut normal_index: HashMap<usize, usize> = HashMap::new();
+    let mut points = Vec::new();
+    let mut vectors = Vec::new();
+    let mut triangles = Vec::new();
+    let mut normal_triangles = Vec::new();
+    let mut has_normals = true;
+    for face in faces {
+        // KiCad's VRML importer mishandles n-gons, so only ever emit triangles.
+        for corners in triangulate_face(&face.vertices, vertices) {
+            triangles.push(corners.map(|c| {
+                let i = face.vertices[c];
+                *vertex_index.entry(i).or_insert_with(|| {
+                    points.push(vertices.get(i).copied().unwrap_or(Vec3::ZERO));
+                    points.len() - 1
+                })
+            }));
+
+            if !has_normals {
+                continue;
+            }
+            let refs = corners.map(|c| face.normals[c].filter(|&n| n < normals.len()));
+            if let [Some(a), Some(b), Some(c)] = refs {
+                normal_triangles.push([a, b, c].map(|n| {
+                    *normal_index.entry(n).or_insert_with(|| {
+                        vectors.push(normals[n]);
+                        vectors.len() - 1
+                    })
+                }));
+            } else {
+                has_normals = false;
+            }
+        }
+    }
+    let has_normals = has_normals && !normal_triangles.is_empty();
+
+    // Pretty output puts every entry on its own indented line; compact output runs
+    // everything together on one line per shape, with trailing zeros dropped.
+    let mut line = |depth: usize, text: &str| {
+        if pretty {
+            wrl.push_str(&"  ".repeat(depth));
+            wrl.push_str(text);
+            wrl.push('\n');
+        } else {
+            wrl.push_str(text);
+            wrl.push(' ');
+        }
+    };
+    let num = |v: f32| {
+        let s = format!("{:.4}", v);
+        if pretty {
+            return s;
+        }
+        let s = s.trim_end_matches('0').trim_end_matches('.');
+        if s == "-0" {
+            "0".to_string()
+        } else {
+            s.to_string()
+        }
+    };
+    let vec3 = |v: &Vec3| format!("{} {} {},", num(v.x), num(v.y), num(v.z));
+
+    let d = material.diffuse;
+    line(0, "Shape {");
+    line(1, "appearance Appearance {");
+    line(2, "material Material {");
+    line(3, &format!("diffuseColor {} {} {}", d.x, d.y, d.z));
+    if let Some(s) = material.specular {
+        line(3, &format!("specularColor {} {} {}", s.x, s.y, s.z));
+    }
+    if material.transparency > 0.0 {
+        line(3, &format!("transparency {}", material.transparency));
+    }
+    line(2, "}");
+    line(1, "}");
+    line(1, "geometry IndexedFaceSet {");
+    line(2, "coord Coordinate {");
+    line(3, "point [");
+    for v in &points {
+        line(4, &vec3(v));
+    }
+    line(3, "]");
+    line(2, "}");
+    line(2, "coordIndex [");
+    for [a, b, c] in &triangles {
+        line(3, &format!("{}, {}, {}, -1,", a, b, c));
+    }
+    line(2, "]");
+    if has_normals {
+        line(2, "normal Normal {");
+        line(3, "vector [");
+        for n in &vectors {
+            line(4, &vec3(n));
+        }
+        line(3, "]");
+        line(2, "}");
+        line(2, "normalIndex [");
+        for [a, b, c] in &normal_triangles {
+            line(3, &format!("{}, {}, {}, -1,", a, b, c));
+        }
+        line(2, "]");
+    }
+    line(1, "}");
+    line(0, "}");
+    if !pretty {
+        wrl.pop();
+        wrl.push('\n');
+    }
+}
+
+/// Splits an OBJ face into triangles, preserving its winding.
+///
+/// Triangles are returned as corner positions within `face` (not vertex indices),
+/// so callers can look up per-corner attributes such as normals.
+///
+/// Triangles pass through untouched. Larger polygons are projected onto their dominant
+/// plane and ear-clipped so concave outlines come out right; degenerate input that
+/// can't be ear-clipped falls back to a simple fan.
+#[cfg(feature = "3d")]
+fn triangulate_face(face: &[usize], vertices: &[Vec3]) -> Vec<[usize; 3]> {
+    if face.len() < 3 {
+        return Vec::new();
+    }
+    if face.len() == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let fan = || {
+        (1..face.len() - 1)
+            .map(|i| [0, i, i + 1])
+            .collect::<Vec<_>>()
+    };
+
+    let Some(points) = face
+        .iter()
+        .map(|&i| vertices.get(i).copied())
+        .collect::<Option<Vec<Vec3>>>()
+    else {
+        return fan();
+    };
+    let n = points.len();
+
+    // Newell's method gives a robust normal even for concave polygons.
+    let mut normal = Vec3::ZERO;
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    if normal.length_squared() <= f32::EPSILON {
+        return fan();
+    }
+
+    // Drop the dominant axis of the normal to get a 2D polygon.
+    let abs = normal.abs();
+    let flat: Vec<(f32, f32)> = points
+        .iter()
+        .map(|v| {
+            if abs.x >= abs.y && abs.x >= abs.z {
+                (v.y, v.z)
+            } else if abs.y >= abs.z {
+                (v.z, v.x)
+            } else {
+                (v.x, v.y)
+            }
+        })
+        .collect();
+
+    let cross = |a: (f32, f32), b: (f32, f32), c: (f32, f32)| {
+        (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+    };
+    let winding = (0..n)
+        .map(|i| {
+            let (a, b) = (flat[i], flat[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f32>()
+        .signum();
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (ia, ib, ic) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            let (a, b, c) = (flat[ia], flat[ib], flat[ic]);
+            // Reflex or degenerate corners can't be ears.
+            if cross(a, b, c) * winding <= 0.0 {
+                return false;
+            }
+            // No other vertex may sit inside the candidate ear.
+            !remaining.iter().any(|&j| {
+                j != ia
+                    && j != ib
+                    && j != ic
+                    && cross(a, b, flat[j]) * winding >= 0.0
+                    && cross(b, c, flat[j]) * winding >= 0.0
+                    && cross(c, a, flat[j]) * winding >= 0.0
+            })
+        });
+
+        let Some(i) = ear else {
+            return fan();
+        };
+        triangles.push([
+            remaining[(i + m - 1) % m],
+            remaining[i],
+            remaining[(i + 1) % m],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
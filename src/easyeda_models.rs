@@ -1,12 +1,43 @@
 // A full implementation would define all structs from the Python `parameters_easyeda.py`
 // This is a simplified example.
 
-#[derive(Debug, Clone)]
+/// The placement settings of a symbol or footprint drawing: its `head`, plus the
+/// `BBox` and `canvas` entries next to it in `dataStr`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EeHead {
+    /// Origin of the drawing (`head.x`, `head.y`); shapes are placed relative to it.
+    pub origin: (f32, f32),
+    /// Rotation of the whole drawing in degrees (`head.rotation`), clockwise.
+    pub rotation: f32,
+    /// Extent of the drawing (`BBox`), if EasyEDA recorded it.
+    pub bbox: Option<EeBBox>,
+    /// Editor grid spacing in EasyEDA units (from `canvas`).
+    pub grid_size: Option<f32>,
+    /// Unit the editor displayed (`mil`, `mm` or `inch`, from `canvas`). Coordinates
+    /// are in EasyEDA's 10 mil units whatever it says.
+    pub unit: Option<String>,
+}
+
+/// An axis-aligned box in EasyEDA units.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EeBBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct EeSymbol {
     pub info: EeSymbolInfo,
     pub bbox: (f32, f32), // Bounding box origin (x, y)
     pub pins: Vec<EeSymbolPin>,
     pub rectangles: Vec<EeSymbolRectangle>,
+    pub arcs: Vec<EeSymbolArc>,
+    /// The parts of a multi-part symbol (e.g. the two amplifiers of a dual op-amp),
+    /// each with its own drawing and no info. Empty for ordinary symbols, whose
+    /// shapes above are their only part.
+    pub units: Vec<EeSymbol>,
     // ... other fields like pins, rectangles, etc.
 }
 
@@ -18,53 +49,143 @@ pub struct EeSymbolInfo {
     pub package: Option<String>,
     pub datasheet: Option<String>,
     pub lcsc_id: Option<String>,
+    /// JLCPCB lists the part as an Extended part (`JLCPCB Part Class` in c_para).
     pub is_extended: bool,
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    /// c_para `Manufacturer`.
+    pub manufacturer: Option<String>,
+    /// Manufacturer part number (c_para `Manufacturer Part`).
+    pub mpn: Option<String>,
+    /// Every pin's number is hidden in EasyEDA.
+    pub hide_pin_numbers: bool,
+    /// Every pin's name is hidden in EasyEDA.
+    pub hide_pin_names: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeFootprint {
     pub info: EeFootprintInfo,
     pub bbox: (f32, f32), // Bounding box origin (x, y)
+    /// Rotation of the whole footprint in degrees (`head.rotation`), clockwise like
+    /// pad rotations and applied on top of them. 0 for nearly every library footprint.
+    pub rotation: f32,
     pub pads: Vec<EeFootprintPad>,
     pub tracks: Vec<EeFootprintTrack>,
     pub texts: Vec<EeFootprintText>,
     // Add other primitives here as needed (circles, arcs, etc.)
     pub circles: Vec<EeFootprintCircle>,
     pub arcs: Vec<EeFootprintArc>,
+    pub holes: Vec<EeFootprintHole>,
+    pub rects: Vec<EeFootprintRect>,
+    pub regions: Vec<EeFootprintRegion>,
+    pub dimensions: Vec<EeFootprintDimension>,
+    pub svg_paths: Vec<EeFootprintSvgPath>,
+    /// Type names of shapes that were skipped because they aren't supported, or were
+    /// too short to read, one entry per shape.
+    pub unsupported_shapes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct EeFootprintInfo {
     pub name: String,
+    /// Reference designator prefix ("TP", "R", ...), if the part has one.
+    pub prefix: Option<String>,
     // ... other info fields
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeFootprintCircle {
     pub center_x: f32,
     pub center_y: f32,
     pub radius: f32,
     pub stroke_width: f32,
     pub layer_id: i32,
+    /// A filled disc (e.g. a silk dot or a round copper land) rather than an outline.
+    pub filled: bool,
+}
+
+/// A filled region (`SOLIDREGION`).
+#[derive(Debug, Clone, Default)]
+pub struct EeFootprintRegion {
+    pub layer_id: i32,
+    /// Outline as an SVG path, in board coordinates.
+    pub path: String,
+    /// `"solid"`, `"cutout"` (no copper may go here) or `"npth"` (a board cutout).
+    pub region_type: String,
+}
+
+/// A measurement annotation: a length (`DIMENSION`) or an angle (`PROTRACTOR`).
+#[derive(Debug, Clone, Default)]
+pub struct EeFootprintDimension {
+    /// The drawn extension lines and arrows, as an SVG path.
+    pub path: String,
+    /// Measures an angle rather than a length.
+    pub is_angle: bool,
+}
+
+/// A path drawn by an `SVGNODE` that isn't a 3D model, e.g. a logo imported into
+/// the silkscreen.
+#[derive(Debug, Clone, Default)]
+pub struct EeFootprintSvgPath {
+    pub path: String, // SVG path data, in board coordinates
+    pub stroke_width: f32,
+    pub layer_id: i32,
+    /// Filled rather than stroked.
+    pub filled: bool,
+}
+
+/// A graphic rectangle (not a pad), e.g. a keepout or courtyard hint.
+#[derive(Debug, Clone, Default)]
+pub struct EeFootprintRect {
+    pub x: f32, // Top-left corner
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub stroke_width: f32,
+    pub layer_id: i32,
+}
+
+/// A non-plated mechanical hole, e.g. for a mounting screw.
+#[derive(Debug, Clone, Default)]
+pub struct EeFootprintHole {
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeFootprintArc {
     pub path: String, // EasyEDA stores arcs as SVG path strings
     pub stroke_width: f32,
     pub layer_id: i32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Ee3dModel {
     pub name: String,
     pub uuid: String,
     pub raw_obj: Option<String>,
     pub step: Option<bytes::Bytes>,
-    // ... other fields like translation, rotation
+    /// Where the model is placed, as far as the footprint says.
+    pub transform: Ee3dTransform,
+}
+
+/// Placement of a 3D model from its SVGNODE. Each part is optional, since older
+/// footprints often carry only some of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ee3dTransform {
+    /// The model's origin on the board (`c_origin`), in EasyEDA units.
+    pub origin: Option<(f32, f32)>,
+    /// Height of the model's origin (`z`), in EasyEDA units.
+    pub z: Option<f32>,
+    /// Rotation about X, Y and Z in degrees (`c_rotation`).
+    pub rotation: Option<(f32, f32, f32)>,
+    /// The node's SVG `transform` as a matrix `[a, b, c, d, e, f]`.
+    pub matrix: Option<[f32; 6]>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeFootprintPad {
     pub shape: String,
     pub center_x: f32,
@@ -76,16 +197,21 @@ pub struct EeFootprintPad {
     pub hole_radius: f32,
     pub hole_length: f32, // Added for oval/slot holes
     pub rotation: f32,
+    pub net: String,
+    pub clearance: Option<f32>, // From the net's design rule, if it has its own
+    /// The pad's copper outline in board coordinates, as EasyEDA draws it. Empty
+    /// for pads saved without one.
+    pub points: Vec<(f32, f32)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeFootprintTrack {
     pub stroke_width: f32,
     pub layer_id: i32,
     pub points: Vec<(f32, f32)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeFootprintText {
     pub text_type: String, // "P" for value, "N" for reference
     pub center_x: f32,
@@ -95,7 +221,7 @@ pub struct EeFootprintText {
     pub text: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeSymbolPin {
     pub number: String,
     pub name: String,
@@ -106,10 +232,23 @@ pub struct EeSymbolPin {
     pub pin_length: f32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct EeSymbolRectangle {
     pub x: f32,
     pub y: f32,
     pub width: f32,
     pub height: f32,
+    /// Outline width, if the shape gives one.
+    pub stroke_width: Option<f32>,
+    /// Outline and fill colors as EasyEDA writes them (`#880000`, `none`), if given.
+    pub stroke_color: Option<String>,
+    pub fill_color: Option<String>,
+}
+
+/// A symbol arc (`A`), e.g. an inductor's winding or a crystal's curved plate.
+#[derive(Debug, Clone, Default)]
+pub struct EeSymbolArc {
+    /// SVG path, with the arc given by its radii and flags (`M x y A rx ry rot large
+    /// sweep x y`) rather than by a center.
+    pub path: String,
 }
@@ -22,6 +22,19 @@ pub enum Error {
 
     #[error("3D model conversion failed: {0}")]
     ModelConversionError(String),
+
+    #[error("EasyEDA rejected the request: {0}")]
+    ApiRejected(String),
+
+    #[error("Download is truncated or corrupt: {0}")]
+    CorruptDownload(String),
+
+    #[error("Component revision mismatch: {0}")]
+    RevisionMismatch(String),
+
+    #[cfg(feature = "zip")]
+    #[error("Zip archive error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,50 +1,332 @@
 use crate::error::{Error, Result};
 use crate::kicad_models::*;
-use regex::Regex;
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::PathBuf; // We need to add `regex` to our dependencies in Cargo.toml
-
-const KICAD_SYM_HEADER: &str = r#"(kicad_symbol_lib (version 20211014) (generator easyeda2kicad_rs)
-"#;
+use std::path::{Path, PathBuf};
 
 const KICAD_SYM_FOOTER: &str = r#")
 "#;
 
+/// Checks that generated S-expression content has balanced parentheses and quotes.
+///
+/// KiCad refuses to load a library with a cryptic error if a single element is malformed,
+/// so this runs on every generated entry before it is written. `element` names the
+/// symbol/footprint in the returned error.
+pub fn validate_sexpr(content: &str, element: &str) -> Result<()> {
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (line_no, line) in content.lines().enumerate() {
+        for c in line.chars() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth < 0 {
+                        return Err(Error::ParseError(format!(
+                            "Unbalanced S-expression in '{}': unexpected ')' on line {}",
+                            element,
+                            line_no + 1
+                        )));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if in_string {
+        return Err(Error::ParseError(format!(
+            "Unbalanced S-expression in '{}': unterminated string",
+            element
+        )));
+    }
+    if depth != 0 {
+        return Err(Error::ParseError(format!(
+            "Unbalanced S-expression in '{}': {} unclosed '('",
+            element, depth
+        )));
+    }
+    Ok(())
+}
+
+/// Splits a `.kicad_sym` library into its top-level `(symbol "name" ...)` blocks.
+///
+/// Uses balanced-paren scanning (string aware), so nested unit symbols such as
+/// `"NAME_1_1"` are kept inside their parent block.
+fn top_level_symbols(content: &str) -> Vec<(String, &str)> {
+    let mut symbols = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in content.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => {
+                depth += 1;
+                if depth == 2 {
+                    start = i;
+                }
+            }
+            ')' => {
+                if depth == 2 {
+                    let block = &content[start..=i];
+                    if let Some(name) = block
+                        .strip_prefix("(symbol")
+                        .and_then(|rest| rest.trim_start().strip_prefix('"'))
+                        .and_then(|rest| rest.split('"').next())
+                    {
+                        symbols.push((name.to_string(), block));
+                    }
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+    symbols
+}
+
 /// Manages the output library structure.
 pub struct KicadLibrary {
     pub path: PathBuf,
+    /// How footprints reference the generated 3D models.
+    pub model_path_mode: Model3dPathMode,
+    /// Name of the symbol library file (`symbols/{lib_name}.kicad_sym`).
+    pub lib_name: String,
+    /// The KiCad release new library files are written for.
+    pub format: KiCadFormat,
+    /// Whether symbols share one library file or get one file each.
+    pub symbol_layout: SymbolLayout,
+    /// Which 3D model files are written and referenced by footprints.
+    pub model_format: Model3dFormat,
+    /// Names of the library's subdirectories.
+    pub layout: LibraryLayout,
 }
 
 impl KicadLibrary {
+    /// Creates a library rooted at `path` with default settings.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        KicadLibrary {
+            path: path.into(),
+            model_path_mode: Model3dPathMode::default(),
+            lib_name: "lib".to_string(),
+            format: KiCadFormat::default(),
+            symbol_layout: SymbolLayout::default(),
+            model_format: Model3dFormat::default(),
+            layout: LibraryLayout::default(),
+        }
+    }
+
+    /// Returns the directory holding the `.kicad_sym` files.
+    pub fn symbols_dir(&self) -> PathBuf {
+        self.path.join(&self.layout.symbols_dir)
+    }
+
+    /// Returns the footprint library (`.pretty`) directory.
+    pub fn footprints_dir(&self) -> PathBuf {
+        self.path.join(&self.layout.footprints_dir)
+    }
+
+    /// Returns the directory the 3D model files are written to.
+    pub fn models_dir(&self) -> PathBuf {
+        self.path.join(&self.layout.models_dir)
+    }
+
+    /// Returns the path of the shared symbol library file.
+    pub fn symbol_lib_path(&self) -> PathBuf {
+        self.symbols_dir()
+            .join(format!("{}.kicad_sym", self.lib_name))
+    }
+
+    /// Returns the file a symbol is written to, according to `symbol_layout`.
+    pub fn symbol_path(&self, name: &str) -> PathBuf {
+        match self.symbol_layout {
+            SymbolLayout::SharedLib => self.symbol_lib_path(),
+            SymbolLayout::PerComponent => self
+                .symbols_dir()
+                .join(format!("{}.kicad_sym", name.replace(['/', '\\'], "_"))),
+        }
+    }
+
+    /// Returns the names of the symbols already in the library, in file order.
+    ///
+    /// With [`SymbolLayout::PerComponent`] every `.kicad_sym` file in `symbols/` is
+    /// read, in file name order. A library that doesn't exist yet has no symbols.
+    pub fn list_symbols(&self) -> Result<Vec<String>> {
+        let files = match self.symbol_layout {
+            SymbolLayout::SharedLib => vec![self.symbol_lib_path()],
+            SymbolLayout::PerComponent => {
+                let dir = self.symbols_dir();
+                if !dir.is_dir() {
+                    return Ok(Vec::new());
+                }
+                let mut files: Vec<PathBuf> = fs::read_dir(&dir)?
+                    .map(|entry| entry.map(|e| e.path()))
+                    .collect::<std::io::Result<_>>()?;
+                files.retain(|p| p.extension().is_some_and(|ext| ext == "kicad_sym"));
+                files.sort();
+                files
+            }
+        };
+        let mut names = Vec::new();
+        for file in files.iter().filter(|f| f.exists()) {
+            let content = fs::read_to_string(file)?;
+            names.extend(
+                top_level_symbols(&content)
+                    .into_iter()
+                    .map(|(name, _)| name),
+            );
+        }
+        Ok(names)
+    }
+
+    /// Removes a symbol from the library. Returns `Ok(false)` if there was no symbol
+    /// of that name.
+    ///
+    /// With [`SymbolLayout::PerComponent`] the symbol's file is deleted; otherwise its
+    /// block is cut out of the shared library, leaving the other symbols as they are.
+    pub fn remove_symbol(&self, name: &str) -> Result<bool> {
+        let path = self.symbol_path(name);
+        if !path.exists() {
+            return Ok(false);
+        }
+        if self.symbol_layout == SymbolLayout::PerComponent {
+            fs::remove_file(path)?;
+            return Ok(true);
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let Some((_, block)) = top_level_symbols(&content)
+            .into_iter()
+            .find(|(existing, _)| existing == name)
+        else {
+            return Ok(false);
+        };
+        // The block borrows from `content`; cut from the start of its line through
+        // the line break after it.
+        let mut start = block.as_ptr() as usize - content.as_ptr() as usize;
+        let mut end = start + block.len();
+        start = content[..start].trim_end_matches([' ', '\t']).len();
+        if content[end..].starts_with("\r\n") {
+            end += 2;
+        } else if content[end..].starts_with('\n') {
+            end += 1;
+        }
+        fs::write(&path, format!("{}{}", &content[..start], &content[end..]))?;
+        Ok(true)
+    }
+
+    /// Deletes a footprint's `.kicad_mod` file. Returns `Ok(false)` if there was none.
+    /// Its 3D model is kept, since other footprints may use it.
+    pub fn remove_footprint(&self, name: &str) -> Result<bool> {
+        let path = self.footprints_dir().join(format!("{}.kicad_mod", name));
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(path)?;
+        Ok(true)
+    }
+
+    /// Returns the directory footprints should use to reference 3D models,
+    /// according to `model_path_mode`.
+    pub fn model_dir(&self) -> Result<String> {
+        let models = self.models_dir();
+        let dir = match self.model_path_mode {
+            Model3dPathMode::Relative => self.layout.model_ref_dir(),
+            Model3dPathMode::Absolute => {
+                // Prefer the canonical path, but don't require the directory to exist yet.
+                let abs = match models.canonicalize() {
+                    Ok(p) => p,
+                    Err(_) => std::env::current_dir()?.join(&models),
+                };
+                abs.to_string_lossy().replace('\\', "/")
+            }
+            Model3dPathMode::KiprojVar => {
+                // A relative output dir is assumed to be relative to the project directory.
+                if self.path.is_relative() {
+                    format!(
+                        "${{KIPRJMOD}}/{}",
+                        models.to_string_lossy().replace('\\', "/")
+                    )
+                } else {
+                    format!("${{KIPRJMOD}}/{}", self.layout.models_dir)
+                }
+            }
+        };
+        Ok(dir)
+    }
+
     /// Creates the necessary directories for symbols, footprints, and 3D models.
     pub fn setup_directories(&self) -> Result<()> {
-        fs::create_dir_all(self.path.join("footprints.pretty"))?;
-        fs::create_dir_all(self.path.join("symbols"))?;
-        fs::create_dir_all(self.path.join("3dmodels.3dshapes"))?;
+        fs::create_dir_all(self.footprints_dir())?;
+        fs::create_dir_all(self.symbols_dir())?;
+        fs::create_dir_all(self.models_dir())?;
         Ok(())
     }
 
     /// Adds a symbol to the symbol library file.
     pub fn add_symbol(&self, symbol: &KiSymbol) -> Result<()> {
-        let lib_path = self.path.join("symbols/lib.kicad_sym");
         let symbol_content = symbol.to_kicad_lib_entry();
+        validate_sexpr(&symbol_content, &symbol.name)?;
+        self.add_symbol_entry(&symbol.name, &symbol_content)
+    }
+
+    /// Appends an already-serialized `(symbol ...)` block to the symbol library,
+    /// skipping it if a symbol with the same name exists.
+    fn add_symbol_entry(&self, name: &str, symbol_content: &str) -> Result<()> {
+        if self.symbol_layout == SymbolLayout::PerComponent {
+            // One self-contained file per symbol, so no parsing is needed to dedup.
+            let path = self.symbol_path(name);
+            if path.exists() {
+                println!("Symbol '{}' already exists in the library. Skipping.", name);
+                return Ok(());
+            }
+            let content = format!(
+                "{}{}{}",
+                self.format.symbol_lib_header(),
+                symbol_content,
+                KICAD_SYM_FOOTER
+            );
+            fs::write(path, content)?;
+            println!("Wrote symbol '{}' to its own library file.", name);
+            return Ok(());
+        }
+
+        let lib_path = self.symbol_lib_path();
 
         // --- Check for Duplicates ---
         if lib_path.exists() {
             let mut file_content = String::new();
             File::open(&lib_path)?.read_to_string(&mut file_content)?;
 
-            // Regex to find (symbol "SYMBOL_NAME" ... )
-            // We escape the name to handle special characters.
-            let pattern = format!(r#"\(\s*symbol\s*"{}"\s*.*\)"#, regex::escape(&symbol.name));
-            let re = Regex::new(&pattern).map_err(|e| Error::ParseError(e.to_string()))?;
-
-            if re.is_match(&file_content) {
-                println!(
-                    "Symbol '{}' already exists in the library. Skipping.",
-                    symbol.name
-                );
+            if top_level_symbols(&file_content)
+                .iter()
+                .any(|(existing, _)| existing == name)
+            {
+                println!("Symbol '{}' already exists in the library. Skipping.", name);
                 // Optionally, here you could implement logic to UPDATE the symbol instead of skipping.
                 return Ok(());
             }
@@ -55,16 +337,17 @@ impl KicadLibrary {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(&lib_path)?;
 
         let metadata = file.metadata()?;
 
         if metadata.len() == 0 {
             // File is new or empty, write header, symbol, and footer
-            file.write_all(KICAD_SYM_HEADER.as_bytes())?;
+            file.write_all(self.format.symbol_lib_header().as_bytes())?;
             file.write_all(symbol_content.as_bytes())?;
             file.write_all(KICAD_SYM_FOOTER.as_bytes())?;
-            println!("Created new symbol library and added '{}'.", symbol.name);
+            println!("Created new symbol library and added '{}'.", name);
         } else {
             // File exists, amend it
             // Seek to the end of the file, but before the last character (')')
@@ -74,7 +357,7 @@ impl KicadLibrary {
             // This effectively inserts the symbol before the final ')'
             file.write_all(symbol_content.as_bytes())?;
             file.write_all(KICAD_SYM_FOOTER.as_bytes())?;
-            println!("Appended symbol '{}' to the existing library.", symbol.name);
+            println!("Appended symbol '{}' to the existing library.", name);
         }
 
         Ok(())
@@ -83,22 +366,168 @@ impl KicadLibrary {
     /// Writes a footprint to its own .kicad_mod file.
     pub fn add_footprint(&self, footprint: &KiFootprint) -> Result<()> {
         let fp_path = self
-            .path
-            .join(format!("footprints.pretty/{}.kicad_mod", footprint.name));
-        let content = footprint.to_kicad_mod_entry();
+            .footprints_dir()
+            .join(format!("{}.kicad_mod", footprint.name));
+        let content = footprint.to_kicad_mod_entry_with_format(
+            &self.model_dir()?,
+            self.model_format,
+            self.format,
+        );
+        validate_sexpr(&content, &footprint.name)?;
         fs::write(fp_path, content)?;
         Ok(())
     }
 
-    /// Writes the 3D model files (.wrl, .step).
+    /// Saves a component's raw EasyEDA data, pretty-printed, as `raw/<lcsc_id>.json`.
+    pub fn add_raw_data(&self, lcsc_id: &str, data: &serde_json::Value) -> Result<PathBuf> {
+        let dir = self.path.join("raw");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.json", lcsc_id));
+        fs::write(&path, serde_json::to_string_pretty(data)?)?;
+        Ok(path)
+    }
+
+    /// Saves a footprint's pad-to-net map ([`KiFootprint::netlist`]) as
+    /// `nets/<footprint>.tsv`, one tab-separated pad number and net name per line.
+    /// Returns `None`, writing nothing, if no pad has a net.
+    pub fn add_netlist(&self, footprint: &KiFootprint) -> Result<Option<PathBuf>> {
+        let netlist = footprint.netlist();
+        if netlist.is_empty() {
+            return Ok(None);
+        }
+        let dir = self.path.join("nets");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.tsv", footprint.name));
+        let mut content = String::from("pad\tnet\n");
+        for (number, net) in netlist {
+            content.push_str(&format!("{}\t{}\n", number, net));
+        }
+        fs::write(&path, content)?;
+        Ok(Some(path))
+    }
+
+    /// Returns the path a model's STEP file is stored at.
+    pub fn step_model_path(&self, name: &str) -> PathBuf {
+        self.model_path(name, "step")
+    }
+
+    /// Path of a model file. The extension is appended rather than set, since
+    /// model names may contain dots ("SOT-23-3_L2.9-W1.3").
+    fn model_path(&self, name: &str, extension: &str) -> PathBuf {
+        self.models_dir().join(format!("{}.{}", name, extension))
+    }
+
+    /// Moves an already-downloaded STEP file (e.g. a temp file written by
+    /// [`crate::api::EasyedaApi::download_step_3d_model`]) into the library.
+    pub fn add_3d_model_step_file(&self, name: &str, file: &Path) -> Result<()> {
+        let dest = self.step_model_path(name);
+        if fs::rename(file, &dest).is_err() {
+            // Renames fail across filesystems; fall back to copy + delete.
+            fs::copy(file, &dest)?;
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+
+    /// Folds another generated library into this one.
+    ///
+    /// Symbols, footprints and 3D models from `other` are added unless an entry with
+    /// the same name already exists here, in which case the existing one is kept.
+    /// `other` is expected to use this library's [`LibraryLayout`].
+    pub fn merge_from(&self, other: &Path) -> Result<()> {
+        self.setup_directories()?;
+
+        // Every `.kicad_sym` is read, so both shared and per-component libraries merge.
+        let other_symbols = other.join(&self.layout.symbols_dir);
+        if other_symbols.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(&other_symbols)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<_>>()?;
+            files.retain(|p| p.extension().is_some_and(|ext| ext == "kicad_sym"));
+            files.sort();
+            for file in files {
+                let content = fs::read_to_string(&file)?;
+                for (name, block) in top_level_symbols(&content) {
+                    validate_sexpr(block, &name)?;
+                    self.add_symbol_entry(&name, &format!("{}\n", block))?;
+                }
+            }
+        }
+
+        for dir in [&self.layout.footprints_dir, &self.layout.models_dir] {
+            let src_dir = other.join(dir);
+            if !src_dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&src_dir)? {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                let dest = self.path.join(dir).join(entry.file_name());
+                if dest.exists() {
+                    println!(
+                        "'{}' already exists in the library. Skipping.",
+                        entry.file_name().to_string_lossy()
+                    );
+                    continue;
+                }
+                fs::copy(entry.path(), dest)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the 3D model files (.wrl, .step) selected by `model_format`.
     pub fn add_3d_model(&self, model: &Ki3dModel) -> Result<()> {
-        let base_path = self.path.join("3dmodels.3dshapes").join(&model.name);
-        if let Some(wrl_data) = &model.wrl_data {
-            fs::write(base_path.with_extension("wrl"), wrl_data)?;
+        if let Some(wrl_data) = model
+            .wrl_data
+            .as_ref()
+            .filter(|_| self.model_format.wants_wrl())
+        {
+            fs::write(self.model_path(&model.name, "wrl"), wrl_data)?;
+        }
+        let step_data = model.step_data.as_ref();
+        if let Some(step_data) = step_data.filter(|_| self.model_format.wants_step()) {
+            fs::write(self.model_path(&model.name, "step"), step_data)?;
         }
-        if let Some(step_data) = &model.step_data {
-            fs::write(base_path.with_extension("step"), step_data)?;
+        Ok(())
+    }
+
+    /// Bundles the library into a single zip file at `path`.
+    ///
+    /// The archive holds the symbol, footprint and 3D model directories of `layout`
+    /// at its root, the same layout as on disk, so footprints' relative model
+    /// references (`../3dmodels.3dshapes/...`) still resolve once it is unpacked.
+    #[cfg(feature = "zip")]
+    pub fn export_zip(&self, path: &Path) -> Result<()> {
+        let mut zip = zip::ZipWriter::new(File::create(path)?);
+        // A fixed timestamp keeps archives of the same library byte-identical.
+        let options =
+            zip::write::SimpleFileOptions::default().last_modified_time(zip::DateTime::default());
+        let layout = &self.layout;
+        for dir in [
+            &layout.symbols_dir,
+            &layout.footprints_dir,
+            &layout.models_dir,
+        ] {
+            let src_dir = self.path.join(dir);
+            if !src_dir.is_dir() {
+                continue;
+            }
+            let mut files: Vec<PathBuf> = fs::read_dir(&src_dir)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<_>>()?;
+            // Skip unfinished downloads (`*.step.part`).
+            files.retain(|p| p.is_file() && p.extension().is_none_or(|ext| ext != "part"));
+            files.sort();
+            for file in files {
+                let name = file.file_name().unwrap_or_default().to_string_lossy();
+                zip.start_file(format!("{}/{}", dir, name), options)?;
+                zip.write_all(&fs::read(&file)?)?;
+            }
         }
+        zip.finish()?;
         Ok(())
     }
 }
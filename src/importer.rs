@@ -1,46 +1,104 @@
 // src/importer.rs
 
+use crate::converter::ConvertOptions;
 use crate::easyeda_models::*;
 use crate::error::{Error, Result};
+use crate::svg_path;
 use serde::Deserialize;
 use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 /// Parses the JSON value from the API into an EeSymbol.
 /// A real implementation would parse the complex `dataStr` field.
 pub fn import_symbol(data: &Value) -> Result<EeSymbol> {
-    let data_str = &data["dataStr"];
+    import_symbol_with_options(data, &ConvertOptions::default())
+}
+
+/// Same as [`import_symbol`], failing on unsupported shapes when
+/// [`ConvertOptions::strict`] is set. Only pins, rectangles and arcs are imported.
+pub fn import_symbol_with_options(data: &Value, options: &ConvertOptions) -> Result<EeSymbol> {
+    let data_str = &*data_str(&data["dataStr"])?;
     let c_para = &data_str["head"]["c_para"];
+    // Privately shared and non-catalog parts have no `lcsc` object at all. Their
+    // c_para can still carry a datasheet link, and the LCSC number when LCSC is the
+    // listed supplier; any other supplier's part number is not an LCSC ID.
+    let lcsc = &data["lcsc"];
+    let supplier_is_lcsc = c_para["Supplier"]
+        .as_str()
+        .is_some_and(|s| s.trim().eq_ignore_ascii_case("LCSC"));
 
-    let info = EeSymbolInfo {
+    let mut info = EeSymbolInfo {
         name: c_para["name"].as_str().unwrap_or("Unknown").to_string(),
         prefix: c_para["pre"].as_str().unwrap_or("U").to_string(),
         package: c_para["package"].as_str().map(String::from),
-        datasheet: data["lcsc"]["url"].as_str().map(String::from),
-        lcsc_id: data["lcsc"]["number"].as_str().map(String::from),
+        datasheet: non_empty_str(&lcsc["url"]).or_else(|| non_empty_str(&c_para["link"])),
+        lcsc_id: non_empty_str(&lcsc["number"])
+            .or_else(|| non_empty_str(&c_para["Supplier Part"]).filter(|_| supplier_is_lcsc)),
         is_extended: c_para["JLCPCB Part Class"]
             .as_str()
             .eq(&Some("Extended Part")),
+        description: non_empty_str(&data["description"])
+            .or_else(|| non_empty_str(&c_para["Description"])),
+        keywords: data["tags"]
+            .as_array()
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|t| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|k| !k.is_empty()),
+        manufacturer: non_empty_str(&c_para["Manufacturer"]),
+        mpn: non_empty_str(&c_para["Manufacturer Part"]),
+        ..Default::default()
     };
 
-    let bbox_x = data_str["head"]["x"]
-        .as_str()
-        .unwrap_or("0")
-        .parse::<f32>()
-        .unwrap_or(0.0);
-    let bbox_y = data_str["head"]["y"]
-        .as_str()
-        .unwrap_or("0")
-        .parse::<f32>()
-        .unwrap_or(0.0);
+    // Multi-part symbols carry one drawing per part in `subparts`; the top-level
+    // `dataStr` then only describes the part as a whole.
+    let subparts = data["subparts"]
+        .as_array()
+        .filter(|parts| !parts.is_empty());
+    let (mut symbol, pin_labels_shown) = match subparts {
+        Some(subparts) => {
+            let mut symbol = EeSymbol::default();
+            let mut pin_labels_shown = Vec::new();
+            for subpart in subparts {
+                let drawing = self::data_str(&subpart["dataStr"])?;
+                let (unit, labels) = parse_symbol_drawing(&drawing, options.strict)?;
+                symbol.units.push(unit);
+                pin_labels_shown.extend(labels);
+            }
+            (symbol, pin_labels_shown)
+        }
+        None => parse_symbol_drawing(data_str, options.strict)?,
+    };
+
+    if !pin_labels_shown.is_empty() {
+        info.hide_pin_names = pin_labels_shown.iter().all(|(name, _)| !name);
+        info.hide_pin_numbers = pin_labels_shown.iter().all(|(_, number)| !number);
+    }
+    symbol.info = info;
+    Ok(symbol)
+}
+
+/// Parses one symbol drawing (a `dataStr` with `head` and `shape`) into a symbol
+/// without info, along with whether each pin's name and number are shown. With
+/// `strict`, shapes that aren't imported are an error.
+fn parse_symbol_drawing(data_str: &Value, strict: bool) -> Result<(EeSymbol, Vec<(bool, bool)>)> {
+    let (bbox_x, bbox_y) = import_head(data_str).origin;
 
     let mut pins = Vec::new();
     let mut rectangles = Vec::new();
+    let mut arcs = Vec::new();
+    // Whether each pin's name and number are shown.
+    let mut pin_labels_shown = Vec::new();
 
     let shapes = data_str["shape"]
         .as_array()
         .ok_or_else(|| Error::MissingData("Symbol shape data is missing".to_string()))?;
 
-    for shape_val in shapes {
+    for shape_val in unique_shapes(shapes) {
         let shape_str = shape_val.as_str().unwrap_or("");
 
         // Symbol pins are more complex, delimited by "^^"
@@ -50,20 +108,34 @@ pub fn import_symbol(data: &Value) -> Result<EeSymbol> {
                 let settings = parse_raw_line(segments[0]);
                 let path = parse_raw_line(segments[2]);
                 let name_data = parse_raw_line(segments[3]);
+                let number_data = segments.get(4).map(|s| parse_raw_line(s));
 
                 if settings.len() > 7 && name_data.len() > 5 && path.len() > 1 {
-                    let path_commands: Vec<&str> = path[1].split_whitespace().collect();
-                    let pin_length = path_commands.last().unwrap_or(&"0").parse().unwrap_or(10.0);
+                    let geometry = pin_path_geometry(path[0]);
+                    let pin_length = geometry.map_or(10.0, |(length, _)| length);
+                    // The explicit rotation wins; the path direction only fills in
+                    // when it is missing.
+                    let rotation = settings[6]
+                        .parse::<f32>()
+                        .ok()
+                        .map(|r| r.round() as i32)
+                        .or(geometry.map(|(_, rotation)| rotation))
+                        .unwrap_or(0);
 
                     pins.push(EeSymbolPin {
                         number: settings[3].to_string(),
                         name: name_data[4].to_string(),
                         pos_x: settings[4].parse().unwrap_or(0.0),
                         pos_y: settings[5].parse().unwrap_or(0.0),
-                        rotation: settings[6].parse().unwrap_or(0),
+                        rotation,
                         pin_type: settings[2].to_string(),
                         pin_length,
                     });
+                    // The name and number segments start with a visibility flag.
+                    pin_labels_shown.push((
+                        name_data[0] != "0",
+                        number_data.is_none_or(|n| n.first() != Some(&"0")),
+                    ));
                 }
             }
         } else {
@@ -73,28 +145,43 @@ pub fn import_symbol(data: &Value) -> Result<EeSymbol> {
                 continue;
             }
             match fields[0] {
-                "R" => {
-                    // Rectangle
-                    if fields.len() > 6 {
-                        rectangles.push(EeSymbolRectangle {
-                            x: fields[1].parse().unwrap_or(0.0),
-                            y: fields[2].parse().unwrap_or(0.0),
-                            width: fields[5].parse().unwrap_or(0.0),
-                            height: fields[6].parse().unwrap_or(0.0),
-                        });
-                    }
+                // Rectangle
+                "R" if fields.len() > 6 => {
+                    rectangles.push(EeSymbolRectangle {
+                        x: fields[1].parse().unwrap_or(0.0),
+                        y: fields[2].parse().unwrap_or(0.0),
+                        width: fields[5].parse().unwrap_or(0.0),
+                        height: fields[6].parse().unwrap_or(0.0),
+                        // R~x~y~rx~ry~width~height~strokeColor~strokeWidth~strokeStyle~fillColor~...
+                        stroke_width: fields.get(8).and_then(|w| w.trim().parse().ok()),
+                        stroke_color: fields.get(7).map(|c| c.trim().to_string()),
+                        fill_color: fields.get(10).map(|c| c.trim().to_string()),
+                    });
+                }
+                // Format: A~pathString~helperDots~strokeColor~strokeWidth~...
+                "A" if fields.len() > 1 => {
+                    arcs.push(EeSymbolArc {
+                        path: fields[1].to_string(),
+                    });
+                }
+                other if strict => {
+                    return Err(Error::Unsupported(format!("symbol shape '{}'", other)));
                 }
                 _ => { /* Ignore polylines, circles etc for now */ }
             }
         }
     }
 
-    Ok(EeSymbol {
-        info,
-        bbox: (bbox_x, bbox_y),
-        pins,
-        rectangles,
-    })
+    Ok((
+        EeSymbol {
+            bbox: (bbox_x, bbox_y),
+            pins,
+            rectangles,
+            arcs,
+            ..Default::default()
+        },
+        pin_labels_shown,
+    ))
 }
 
 // Helper structs for deserializing the nested JSON inside the SVGNODE string.
@@ -107,18 +194,109 @@ struct SvgNode {
 struct SvgNodeAttrs {
     uuid: String,
     title: String,
-    // Add other fields like c_origin, c_rotation if needed for placement
+    #[serde(default)]
+    c_origin: Option<String>,
+    #[serde(default)]
+    z: Option<String>,
+    #[serde(default)]
+    c_rotation: Option<String>,
+    #[serde(default)]
+    transform: Option<String>,
+}
+
+impl SvgNodeAttrs {
+    /// Reads whatever placement data the node has; unreadable parts are left out.
+    fn transform(&self) -> Ee3dTransform {
+        let numbers = |s: &Option<String>| -> Vec<f32> {
+            s.as_deref()
+                .unwrap_or("")
+                .split(',')
+                .filter_map(|v| v.trim().parse().ok())
+                .collect()
+        };
+        Ee3dTransform {
+            origin: match numbers(&self.c_origin)[..] {
+                [x, y] => Some((x, y)),
+                _ => None,
+            },
+            z: match numbers(&self.z)[..] {
+                [z] => Some(z),
+                _ => None,
+            },
+            rotation: match numbers(&self.c_rotation)[..] {
+                [x, y, z] => Some((x, y, z)),
+                _ => None,
+            },
+            matrix: self
+                .transform
+                .as_deref()
+                .and_then(svg_path::parse_transform),
+        }
+    }
 }
 
 /// Extracts 3D model info by correctly parsing the SVGNODE from the footprint shape data.
+///
+/// Footprints with several mounting variants can carry more than one SVGNODE. In that
+/// case the model whose title matches the part's package variant (from `c_para`) is
+/// chosen, falling back to the first one. Use [`import_3d_model_infos`] to get them all.
 pub fn import_3d_model_info(data: &Value) -> Result<Option<Ee3dModel>> {
-    let shapes = data["packageDetail"]["dataStr"]["shape"]
-        .as_array()
-        .ok_or_else(|| {
-            Error::MissingData("Footprint shape data is missing or not an array".to_string())
-        })?;
+    let mut models = import_3d_model_infos(data)?;
+    if models.len() <= 1 {
+        return Ok(models.pop());
+    }
+
+    let footprint_str = footprint_data_str(data)?;
+    let symbol_str = data_str(&data["dataStr"])?;
+    let variant = [
+        &footprint_str["head"]["c_para"]["package"],
+        &symbol_str["head"]["c_para"]["package"],
+        &data["packageDetail"]["title"],
+    ]
+    .into_iter()
+    .find_map(|v| v.as_str().filter(|s| !s.is_empty()));
+
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase()
+    };
+    let chosen = variant.and_then(|variant| {
+        let variant = normalize(variant);
+        models
+            .iter()
+            .position(|m| normalize(&m.name) == variant)
+            .or_else(|| {
+                models.iter().position(|m| {
+                    let title = normalize(&m.name);
+                    !title.is_empty() && (title.contains(&variant) || variant.contains(&title))
+                })
+            })
+    });
 
-    for shape_value in shapes {
+    log::info!(
+        "Found {} 3D models in footprint; using '{}' (package variant: {})",
+        models.len(),
+        models[chosen.unwrap_or(0)].name,
+        variant.unwrap_or("unknown")
+    );
+    Ok(Some(models.swap_remove(chosen.unwrap_or(0))))
+}
+
+/// Returns every 3D model (SVGNODE) referenced by the footprint, in file order. A part
+/// without a footprint has none.
+pub fn import_3d_model_infos(data: &Value) -> Result<Vec<Ee3dModel>> {
+    if data["packageDetail"]["dataStr"].is_null() {
+        return Ok(Vec::new());
+    }
+    let data_str = footprint_data_str(data)?;
+    let shapes = data_str["shape"].as_array().ok_or_else(|| {
+        Error::MissingData("Footprint shape data is missing or not an array".to_string())
+    })?;
+
+    let mut models = Vec::new();
+    for shape_value in unique_shapes(shapes) {
         if let Some(shape_str) = shape_value.as_str() {
             // The line we are looking for starts with "SVGNODE~"
             if shape_str.starts_with("SVGNODE~") {
@@ -126,48 +304,261 @@ pub fn import_3d_model_info(data: &Value) -> Result<Option<Ee3dModel>> {
                 // We use `split_once` to be safe.
                 if let Some((_, json_part)) = shape_str.split_once('~') {
                     // The json_part is a string containing JSON, so we parse it.
-                    let svg_node: SvgNode = serde_json::from_str(json_part)?;
+                    let node: Value = serde_json::from_str(json_part)?;
+                    if !is_3d_svg_node(&node) {
+                        continue;
+                    }
+                    let svg_node: SvgNode = serde_json::from_value(node)?;
 
-                    // We found it! Now we can build our Ee3dModel and return.
-                    return Ok(Some(Ee3dModel {
+                    models.push(Ee3dModel {
+                        transform: svg_node.attrs.transform(),
                         name: svg_node.attrs.title,
                         uuid: svg_node.attrs.uuid,
                         raw_obj: None,
                         step: None,
-                    }));
+                    });
                 }
             }
         }
     }
 
     // If we loop through all shapes and don't find an SVGNODE, there is no model.
-    Ok(None)
+    Ok(models)
+}
+
+/// Whether an SVGNODE holds a 3D model rather than 2D graphics. Models are on
+/// layer 19 (3D model outline); nodes naming no layer at all are taken as models too,
+/// as that is all older footprints used SVGNODE for.
+fn is_3d_svg_node(node: &Value) -> bool {
+    let attrs = &node["attrs"];
+    let layer = json_f32(&node["layerid"]).or_else(|| json_f32(&attrs["layerid"]));
+    attrs["c_etype"] == "outline3D" || layer.is_none_or(|layer| layer == 19.0)
+}
+
+/// Collects the paths drawn by a 2D SVGNODE and its child nodes. Children without a
+/// layer of their own inherit their parent's. Node transforms are not applied; the
+/// paths EasyEDA stores are already in board coordinates.
+fn svg_node_paths(node: &Value, layer_id: i32, paths: &mut Vec<EeFootprintSvgPath>) {
+    let attrs = &node["attrs"];
+    let layer_id = json_f32(&attrs["layerid"])
+        .or_else(|| json_f32(&node["layerid"]))
+        .map_or(layer_id, |layer| layer as i32);
+    if node["nodeName"] == "path"
+        && let Some(path) = attrs["d"].as_str().filter(|d| !d.trim().is_empty())
+    {
+        let fill = attrs["fill"].as_str().unwrap_or("").trim();
+        paths.push(EeFootprintSvgPath {
+            path: path.to_string(),
+            stroke_width: json_f32(&attrs["stroke-width"]).unwrap_or(0.0),
+            layer_id,
+            filled: !fill.is_empty() && fill != "none",
+        });
+    }
+    for child in node["childNodes"].as_array().into_iter().flatten() {
+        svg_node_paths(child, layer_id, paths);
+    }
+}
+
+/// The shapes of a `dataStr` without exact repeats of an earlier shape string. Some
+/// exports list shapes twice, which would otherwise give doubled pads and pins.
+fn unique_shapes(shapes: &[Value]) -> impl Iterator<Item = &Value> {
+    let mut seen = HashSet::new();
+    shapes
+        .iter()
+        .filter(move |shape| shape.as_str().is_none_or(|s| seen.insert(s)))
 }
 
 fn parse_raw_line(line: &str) -> Vec<&str> {
     line.split('~').collect()
 }
 
+/// Derives a pin's length and rotation from its SVG path (e.g. `M 360 250 h -10`).
+///
+/// The path runs from the pin's outer end towards the symbol body. Multi-segment and
+/// curved paths are followed to their final point, so the length is the straight
+/// distance between the two ends. The rotation is EasyEDA's (0 = pointing right,
+/// clockwise), snapped to a quarter turn.
+fn pin_path_geometry(path: &str) -> Option<(f32, i32)> {
+    let (start, end) = svg_path::path_endpoints(path)?;
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        return None;
+    }
+    let angle = dy.atan2(dx).to_degrees() + 180.0;
+    let rotation = ((angle / 90.0).round() as i32 * 90).rem_euclid(360);
+    Some((length, rotation))
+}
+
+/// Parses a point list such as `"10 20 30 40"` into `(x, y)` pairs.
+///
+/// Tolerates repeated whitespace, tabs and commas between values, and scientific
+/// notation. A pair with an unparsable value is skipped rather than read as `0.0`,
+/// which would otherwise drag a line to the origin.
+fn parse_points(points: &str) -> Vec<(f32, f32)> {
+    let values: Vec<Option<f32>> = points
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect();
+    values
+        .chunks_exact(2)
+        .filter_map(|pair| Some((pair[0]?, pair[1]?)))
+        .collect()
+}
+
+/// Returns a `dataStr` value as a JSON object.
+///
+/// Some API responses carry `dataStr` as a JSON-encoded string instead of a nested
+/// object; those are decoded here. Anything else is passed through untouched.
+fn data_str(value: &Value) -> Result<Cow<'_, Value>> {
+    match value {
+        Value::String(s) => Ok(Cow::Owned(serde_json::from_str(s)?)),
+        other => Ok(Cow::Borrowed(other)),
+    }
+}
+
+/// Reads the [`EeHead`] of a decoded `dataStr`.
+///
+/// Numbers may come as JSON numbers or strings. Missing or unreadable values fall
+/// back to 0 (origin and rotation) or `None`.
+pub fn import_head(data_str: &Value) -> EeHead {
+    let head = &data_str["head"];
+    let bbox = &data_str["BBox"];
+    let bbox = match ["x", "y", "width", "height"].map(|key| json_f32(&bbox[key])) {
+        [Some(x), Some(y), Some(width), Some(height)] => Some(EeBBox {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => None,
+    };
+    // CA~width~height~background~grid visible~grid color~grid size~...~unit~...
+    let canvas = data_str["canvas"].as_str().map(parse_raw_line);
+    let canvas_field = |index: usize| {
+        canvas
+            .as_ref()
+            .and_then(|fields| fields.get(index))
+            .map(|field| field.trim())
+            .filter(|field| !field.is_empty())
+    };
+    EeHead {
+        origin: (
+            json_f32(&head["x"]).unwrap_or(0.0),
+            json_f32(&head["y"]).unwrap_or(0.0),
+        ),
+        rotation: json_f32(&head["rotation"]).unwrap_or(0.0),
+        bbox,
+        grid_size: canvas_field(6).and_then(|size| size.parse().ok()),
+        unit: canvas_field(11).map(String::from),
+    }
+}
+
+/// Pad rotation in degrees from EasyEDA's rotation field.
+///
+/// Some footprints store it in radians. A fractional value up to a full turn that
+/// sits on a multiple of π/4 (e.g. `1.5708`) is not a plausible angle in degrees,
+/// so it is converted; everything else is already degrees.
+fn pad_rotation_degrees(rotation: f32) -> f32 {
+    let eighths = rotation / std::f32::consts::FRAC_PI_4;
+    let looks_like_radians = rotation.fract() != 0.0
+        && rotation.abs() <= std::f32::consts::TAU + 0.01
+        && eighths.round() != 0.0
+        && (eighths - eighths.round()).abs() * std::f32::consts::FRAC_PI_4 < 0.01;
+    if looks_like_radians {
+        (eighths.round() * 45.0).rem_euclid(360.0)
+    } else {
+        rotation
+    }
+}
+
+/// Reads a number that the API may send either as a JSON number or as a string.
+fn json_f32(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|n| n as f32),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads the reference prefix from a (possibly string-encoded) symbol dataStr.
+fn data_str_prefix(value: &Value) -> Option<String> {
+    non_empty_str(&data_str(value).ok()?["head"]["c_para"]["pre"])
+}
+
+/// Returns the value as an owned string, treating missing and blank values as `None`.
+fn non_empty_str(value: &Value) -> Option<String> {
+    value
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// The footprint's decoded `dataStr`, or `MissingData("no footprint")` for parts that
+/// have none.
+fn footprint_data_str(data: &Value) -> Result<Cow<'_, Value>> {
+    let value = &data["packageDetail"]["dataStr"];
+    if value.is_null() {
+        return Err(Error::MissingData("no footprint".to_string()));
+    }
+    data_str(value)
+}
+
 /// Parses the detailed footprint data from the `dataStr` field.
+///
+/// Fails with `MissingData("no footprint")` if the part has no footprint.
 pub fn import_footprint(data: &Value) -> Result<EeFootprint> {
-    let data_str = &data["packageDetail"]["dataStr"];
+    import_footprint_with_options(data, &ConvertOptions::default())
+}
+
+/// Same as [`import_footprint`], failing with [`Error::Unsupported`] when
+/// [`ConvertOptions::strict`] is set and the footprint has shapes that would be
+/// skipped ([`EeFootprint::unsupported_shapes`]).
+pub fn import_footprint_with_options(
+    data: &Value,
+    options: &ConvertOptions,
+) -> Result<EeFootprint> {
+    let footprint = parse_footprint(data)?;
+    if options.strict
+        && let Some(shape) = footprint.unsupported_shapes.first()
+    {
+        return Err(Error::Unsupported(format!(
+            "footprint shape '{}' in '{}'",
+            shape, footprint.info.name
+        )));
+    }
+    Ok(footprint)
+}
+
+fn parse_footprint(data: &Value) -> Result<EeFootprint> {
+    let data_str = &*footprint_data_str(data)?;
     let info = EeFootprintInfo {
         name: data["packageDetail"]["title"]
             .as_str()
             .unwrap_or("UnknownFootprint")
             .to_string(),
+        // Footprints rarely carry their own prefix; the symbol's is the same part's.
+        prefix: non_empty_str(&data_str["head"]["c_para"]["pre"])
+            .or_else(|| data_str_prefix(&data["dataStr"])),
     };
 
-    let bbox_x = data_str["head"]["x"]
-        .as_str()
-        .unwrap_or("0")
-        .parse::<f32>()
-        .unwrap_or(0.0);
-    let bbox_y = data_str["head"]["y"]
-        .as_str()
-        .unwrap_or("0")
-        .parse::<f32>()
-        .unwrap_or(0.0);
+    let head = import_head(data_str);
+    let (bbox_x, bbox_y) = head.origin;
+
+    // EasyEDA keeps clearance in named design rules rather than on the PAD line.
+    // A rule named after a pad's net overrides the board default for that pad.
+    let net_clearance: HashMap<String, f32> = data_str["DRCRULE"]
+        .as_object()
+        .map(|rules| {
+            rules
+                .iter()
+                .filter(|(name, _)| name.as_str() != "Default")
+                .filter_map(|(name, rule)| Some((name.clone(), json_f32(&rule["clearance"])?)))
+                .collect()
+        })
+        .unwrap_or_default();
 
     let mut pads = Vec::new();
     let mut tracks = Vec::new();
@@ -175,12 +566,18 @@ pub fn import_footprint(data: &Value) -> Result<EeFootprint> {
 
     let mut circles = Vec::new();
     let mut arcs = Vec::new();
+    let mut holes = Vec::new();
+    let mut rects = Vec::new();
+    let mut regions = Vec::new();
+    let mut dimensions = Vec::new();
+    let mut svg_paths = Vec::new();
+    let mut unsupported_shapes = Vec::new();
 
     let shapes = data_str["shape"]
         .as_array()
         .ok_or_else(|| Error::MissingData("Footprint shape data is missing".to_string()))?;
 
-    for shape_val in shapes {
+    for shape_val in unique_shapes(shapes) {
         let shape_str = shape_val.as_str().unwrap_or("");
         let fields = parse_raw_line(shape_str);
         if fields.is_empty() {
@@ -188,109 +585,147 @@ pub fn import_footprint(data: &Value) -> Result<EeFootprint> {
         }
 
         match fields[0] {
-            "PAD" => {
-                // PAD format from EasyEDA:
-                // [0]PAD [1]shape [2]x [3]y [4]width [5]height [6]layer [7]net [8]number
-                // [9]hole_radius [10]points [11]rotation [12]id [13]hole_length ...
-                if fields.len() > 11 {
-                    let hole_radius = fields[9].parse().unwrap_or(0.0);
-
-                    // FIX: Check field 13 first.
-                    // In modern EasyEDA, field 12 is the ID (UUID), and field 13 is the hole length.
-                    let mut hole_length = if fields.len() > 13 {
-                        fields[13].parse::<f32>().unwrap_or(0.0)
-                    } else {
-                        0.0
-                    };
-
-                    // Fallback for very old formats where field 12 might have been the length.
-                    // (If field 12 is a UUID, parse fails and returns 0.0, so this is safe)
-                    if hole_length == 0.0 && fields.len() > 12 {
-                        let val = fields[12].parse::<f32>().unwrap_or(0.0);
-                        // Only accept it if it looks like a length (not an ID)
-                        if val > 0.0 {
-                            hole_length = val;
-                        }
-                    }
+            // PAD format from EasyEDA:
+            // [0]PAD [1]shape [2]x [3]y [4]width [5]height [6]layer [7]net [8]number
+            // [9]hole_radius [10]points [11]rotation [12]id [13]hole_length ...
+            "PAD" if fields.len() > 11 => {
+                let hole_radius = fields[9].parse().unwrap_or(0.0);
 
-                    pads.push(EeFootprintPad {
-                        shape: fields[1].to_string(),
-                        center_x: fields[2].parse().unwrap_or(0.0),
-                        center_y: fields[3].parse().unwrap_or(0.0),
-                        width: fields[4].parse().unwrap_or(0.0),
-                        height: fields[5].parse().unwrap_or(0.0),
-                        layer_id: fields[6].parse().unwrap_or(0),
-                        number: fields[8].to_string(),
-                        hole_radius,
-                        hole_length, // This will now be populated correctly
-                        rotation: fields[11].parse().unwrap_or(0.0),
-                    });
-                }
-            }
-            "TRACK" => {
-                if fields.len() > 4 {
-                    let points_str: Vec<&str> = fields[4].split(' ').collect();
-                    let mut points = Vec::new();
-                    for i in (0..points_str.len()).step_by(2) {
-                        if i + 1 < points_str.len() {
-                            let x = points_str[i].parse().unwrap_or(0.0);
-                            let y = points_str[i + 1].parse().unwrap_or(0.0);
-                            points.push((x, y));
-                        }
+                // FIX: Check field 13 first.
+                // In modern EasyEDA, field 12 is the ID (UUID), and field 13 is the hole length.
+                let mut hole_length = if fields.len() > 13 {
+                    fields[13].parse::<f32>().unwrap_or(0.0)
+                } else {
+                    0.0
+                };
+
+                // Fallback for very old formats where field 12 might have been the length.
+                // (If field 12 is a UUID, parse fails and returns 0.0, so this is safe)
+                if hole_length == 0.0 && fields.len() > 12 {
+                    let val = fields[12].parse::<f32>().unwrap_or(0.0);
+                    // Only accept it if it looks like a length (not an ID)
+                    if val > 0.0 {
+                        hole_length = val;
                     }
-                    tracks.push(EeFootprintTrack {
-                        stroke_width: fields[1].parse().unwrap_or(0.0),
-                        layer_id: fields[2].parse().unwrap_or(0),
-                        points,
-                    });
                 }
+
+                pads.push(EeFootprintPad {
+                    shape: fields[1].to_string(),
+                    center_x: fields[2].parse().unwrap_or(0.0),
+                    center_y: fields[3].parse().unwrap_or(0.0),
+                    width: fields[4].parse().unwrap_or(0.0),
+                    height: fields[5].parse().unwrap_or(0.0),
+                    layer_id: fields[6].parse().unwrap_or(0),
+                    number: fields[8].to_string(),
+                    hole_radius,
+                    hole_length, // This will now be populated correctly
+                    rotation: pad_rotation_degrees(fields[11].parse().unwrap_or(0.0)),
+                    net: fields[7].to_string(),
+                    clearance: net_clearance.get(fields[7]).copied(),
+                    points: parse_points(fields[10]),
+                });
             }
-            "TEXT" => {
-                if fields.len() > 10 {
-                    texts.push(EeFootprintText {
-                        text_type: fields[1].to_string(),
-                        center_x: fields[2].parse().unwrap_or(0.0),
-                        center_y: fields[3].parse().unwrap_or(0.0),
-                        rotation: fields[5].parse().unwrap_or(0.0),
-                        layer_id: fields[7].parse().unwrap_or(0),
-                        text: fields[10].to_string(),
-                    });
-                }
+            "TRACK" if fields.len() > 4 => {
+                tracks.push(EeFootprintTrack {
+                    stroke_width: fields[1].trim().parse().unwrap_or(0.0),
+                    layer_id: fields[2].trim().parse().unwrap_or(0),
+                    points: parse_points(fields[4]),
+                });
             }
-            "CIRCLE" => {
-                // Format: CIRCLE~layer~width~cx~cy~radius~id
-                if fields.len() > 5 {
-                    circles.push(EeFootprintCircle {
-                        layer_id: fields[1].parse().unwrap_or(0),
-                        stroke_width: fields[2].parse().unwrap_or(0.1),
-                        center_x: fields[3].parse().unwrap_or(0.0),
-                        center_y: fields[4].parse().unwrap_or(0.0),
-                        radius: fields[5].parse().unwrap_or(0.0),
-                    });
-                }
+            "TEXT" if fields.len() > 10 => {
+                texts.push(EeFootprintText {
+                    text_type: fields[1].to_string(),
+                    center_x: fields[2].parse().unwrap_or(0.0),
+                    center_y: fields[3].parse().unwrap_or(0.0),
+                    rotation: fields[5].parse().unwrap_or(0.0),
+                    layer_id: fields[7].parse().unwrap_or(0),
+                    text: fields[10].to_string(),
+                });
             }
-            // Add ARC Parsing
-            "ARC" => {
-                // Format: ARC~layer~width~pathString~id
-                if fields.len() > 3 {
-                    arcs.push(EeFootprintArc {
-                        layer_id: fields[1].parse().unwrap_or(0),
-                        stroke_width: fields[2].parse().unwrap_or(0.1),
-                        path: fields[3].to_string(),
-                    });
+            // Format: CIRCLE~layer~width~cx~cy~radius~id~locked~fill
+            "CIRCLE" if fields.len() > 5 => {
+                circles.push(EeFootprintCircle {
+                    layer_id: fields[1].parse().unwrap_or(0),
+                    stroke_width: fields[2].parse().unwrap_or(0.1),
+                    center_x: fields[3].parse().unwrap_or(0.0),
+                    center_y: fields[4].parse().unwrap_or(0.0),
+                    radius: fields[5].parse().unwrap_or(0.0),
+                    filled: matches!(fields.get(8).map(|f| f.trim()), Some("1" | "true")),
+                });
+            }
+            // Format: ARC~width~layer~net~pathString~helperDots~id~locked
+            "ARC" if fields.len() > 4 => {
+                arcs.push(EeFootprintArc {
+                    stroke_width: fields[1].parse().unwrap_or(0.1),
+                    layer_id: fields[2].parse().unwrap_or(0),
+                    path: fields[4].to_string(),
+                });
+            }
+            // Format: HOLE~cx~cy~radius~id~locked
+            "HOLE" if fields.len() > 3 => {
+                holes.push(EeFootprintHole {
+                    center_x: fields[1].parse().unwrap_or(0.0),
+                    center_y: fields[2].parse().unwrap_or(0.0),
+                    radius: fields[3].parse().unwrap_or(0.0),
+                });
+            }
+            // Format: RECT~x~y~width~height~strokeWidth~id~layer~locked
+            "RECT" if fields.len() > 7 => {
+                rects.push(EeFootprintRect {
+                    x: fields[1].parse().unwrap_or(0.0),
+                    y: fields[2].parse().unwrap_or(0.0),
+                    width: fields[3].parse().unwrap_or(0.0),
+                    height: fields[4].parse().unwrap_or(0.0),
+                    stroke_width: fields[5].parse().unwrap_or(0.0),
+                    layer_id: fields[7].parse().unwrap_or(0),
+                });
+            }
+            // Format: SOLIDREGION~layer~net~pathString~type~id~locked
+            "SOLIDREGION" if fields.len() > 4 => {
+                regions.push(EeFootprintRegion {
+                    layer_id: fields[1].parse().unwrap_or(0),
+                    path: fields[3].to_string(),
+                    region_type: fields[4].to_string(),
+                });
+            }
+            // Format: DIMENSION~layer~pathString~id~locked (PROTRACTOR alike)
+            "DIMENSION" | "PROTRACTOR" if fields.len() > 2 => {
+                dimensions.push(EeFootprintDimension {
+                    path: fields[2].to_string(),
+                    is_angle: fields[0] == "PROTRACTOR",
+                });
+            }
+            // 3D model references are read by `import_3d_model_info`; other nodes
+            // carry 2D graphics such as logos.
+            "SVGNODE" => {
+                let node = shape_str
+                    .split_once('~')
+                    .and_then(|(_, json)| serde_json::from_str::<Value>(json).ok());
+                match node {
+                    Some(node) if is_3d_svg_node(&node) => {}
+                    Some(node) => svg_node_paths(&node, 0, &mut svg_paths),
+                    None => unsupported_shapes.push("SVGNODE".to_string()),
                 }
             }
-            _ => { /* Silently ignore unsupported shapes */ }
+            // Unsupported shapes are reported by the converter.
+            other => unsupported_shapes.push(other.to_string()),
         }
     }
 
     Ok(EeFootprint {
         info,
         bbox: (bbox_x, bbox_y),
+        rotation: head.rotation,
         pads,
         tracks,
         texts,
         circles, // Add to struct
         arcs,    // Add to struct
+        holes,
+        rects,
+        regions,
+        dimensions,
+        svg_paths,
+        unsupported_shapes,
     })
 }
@@ -1,8 +1,15 @@
 // src/kicad_models.rs
 
-use glam::Vec3; // Using glam for 3D vector math
+use serde::{Deserialize, Serialize};
 use std::fmt::Write;
 
+/// Vector type of [`Ki3dModel`]'s placement: glam's `Vec3` with the `3d` feature, a
+/// plain array without it. Index it as `[x, y, z]` to work with either.
+#[cfg(feature = "3d")]
+pub type Vec3 = glam::Vec3;
+#[cfg(not(feature = "3d"))]
+pub type Vec3 = [f32; 3];
+
 // --- 3D Model Structs ---
 
 /// Represents a 3D model in KiCad format.
@@ -17,6 +24,212 @@ pub struct Ki3dModel {
     pub offset: Vec3,
     pub scale: Vec3,
     pub rotate: Vec3,
+    /// The model's origin on the board in EasyEDA units, if known. The footprint
+    /// converter turns it into the X/Y `offset`, which depends on the footprint's origin.
+    pub origin: Option<(f32, f32)>,
+    /// Non-fatal issues found while converting the model.
+    pub warnings: Vec<String>,
+}
+
+/// Controls how a footprint's `(model ...)` entry points at the 3D model files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Model3dPathMode {
+    /// `../3dmodels.3dshapes/{name}.wrl`, relative to the `.pretty` directory.
+    /// Only valid while the generated library layout is kept intact.
+    #[default]
+    Relative,
+    /// Absolute path to the library's `3dmodels.3dshapes` directory.
+    Absolute,
+    /// `${KIPRJMOD}/...`, for libraries that live inside a KiCad project directory.
+    KiprojVar,
+}
+
+/// Which 3D model files are generated and referenced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Model3dFormat {
+    /// Only the VRML model converted from EasyEDA's OBJ.
+    WrlOnly,
+    /// Only the STEP model.
+    StepOnly,
+    /// Both files; footprints reference the `.wrl`.
+    #[default]
+    Both,
+}
+
+impl Model3dFormat {
+    pub fn wants_wrl(self) -> bool {
+        self != Model3dFormat::StepOnly
+    }
+
+    pub fn wants_step(self) -> bool {
+        self != Model3dFormat::WrlOnly
+    }
+
+    /// Extension of the file a footprint's `(model ...)` entry points at.
+    pub fn reference_extension(self) -> &'static str {
+        if self.wants_wrl() { "wrl" } else { "step" }
+    }
+}
+
+/// What generated 3D model files are named after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Model3dNaming {
+    /// The model title from EasyEDA. Titles can be generic ("model") or shared
+    /// between parts, so different models may overwrite each other.
+    #[default]
+    Title,
+    /// The footprint name, so the model sits next to the footprint using it.
+    FootprintName,
+    /// The LCSC part number.
+    LcscId,
+}
+
+impl Model3dNaming {
+    /// Picks the model file name (without extension) for a part.
+    pub fn model_name(self, title: &str, footprint_name: &str, lcsc_id: &str) -> String {
+        match self {
+            Model3dNaming::Title => title,
+            Model3dNaming::FootprintName => footprint_name,
+            Model3dNaming::LcscId => lcsc_id,
+        }
+        .to_string()
+    }
+}
+
+/// How symbols are distributed over `.kicad_sym` files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolLayout {
+    /// Every symbol is appended to one shared library file.
+    #[default]
+    SharedLib,
+    /// Each symbol gets its own self-contained `symbols/{name}.kicad_sym`.
+    PerComponent,
+}
+
+/// Names of the directories a generated library is made of, so the output can be
+/// dropped into an existing library structure.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LibraryLayout {
+    /// Directory holding the `.kicad_sym` files.
+    pub symbols_dir: String,
+    /// Footprint library directory; KiCad expects it to end in `.pretty`.
+    pub footprints_dir: String,
+    /// Directory the 3D model files are written to.
+    pub models_dir: String,
+    /// Directory footprints reference their 3D models in, for
+    /// [`Model3dPathMode::Relative`]. `{models_dir}` is replaced by `models_dir`.
+    /// Anything else, e.g. `${MY_3DMODELS}`, is written as is.
+    pub model_ref: String,
+}
+
+impl Default for LibraryLayout {
+    fn default() -> Self {
+        LibraryLayout {
+            symbols_dir: "symbols".to_string(),
+            footprints_dir: "footprints.pretty".to_string(),
+            models_dir: "3dmodels.3dshapes".to_string(),
+            model_ref: "../{models_dir}".to_string(),
+        }
+    }
+}
+
+impl LibraryLayout {
+    /// A layout named after a library nickname: `{nickname}.pretty` and
+    /// `{nickname}.3dshapes`, with symbols in `symbols/`.
+    pub fn with_nickname(nickname: &str) -> Self {
+        LibraryLayout {
+            footprints_dir: format!("{}.pretty", nickname),
+            models_dir: format!("{}.3dshapes", nickname),
+            ..Default::default()
+        }
+    }
+
+    /// The model directory footprints reference with [`Model3dPathMode::Relative`].
+    pub fn model_ref_dir(&self) -> String {
+        self.model_ref.replace("{models_dir}", &self.models_dir)
+    }
+}
+
+/// The KiCad release generated files are targeted at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum KiCadFormat {
+    /// KiCad 5: footprints use the legacy `.kicad_mod` syntax. KiCad 5 can't read
+    /// `.kicad_sym` at all, so symbol libraries are written as for V6.
+    #[serde(rename = "5")]
+    V5,
+    #[default]
+    #[serde(rename = "6")]
+    V6,
+    #[serde(rename = "7")]
+    V7,
+    #[serde(rename = "8")]
+    V8,
+}
+
+impl KiCadFormat {
+    /// The `(version ...)` stamp KiCad writes into symbol libraries for this release.
+    pub fn symbol_lib_version(self) -> u32 {
+        match self {
+            KiCadFormat::V5 | KiCadFormat::V6 => 20211014,
+            KiCadFormat::V7 => 20220914,
+            KiCadFormat::V8 => 20231120,
+        }
+    }
+
+    /// The `(generator_version ...)` KiCad writes for this release, if it has one.
+    ///
+    /// KiCad 7 and older reject the token, so it is only emitted from V8 on.
+    pub fn generator_version(self) -> Option<&'static str> {
+        match self {
+            KiCadFormat::V5 | KiCadFormat::V6 | KiCadFormat::V7 => None,
+            KiCadFormat::V8 => Some("8.0"),
+        }
+    }
+
+    /// The `(version ...)` of a `(footprint ...)` file for this release, or `None`
+    /// for releases whose footprints are written as legacy `(module ...)`.
+    pub fn footprint_version(self) -> Option<u32> {
+        match self {
+            KiCadFormat::V5 | KiCadFormat::V6 => None,
+            KiCadFormat::V7 => Some(20221018),
+            KiCadFormat::V8 => Some(20240108),
+        }
+    }
+
+    /// Returns the opening line of a `.kicad_sym` file, up to the first symbol.
+    pub fn symbol_lib_header(self) -> String {
+        match self.generator_version() {
+            Some(generator_version) => format!(
+                "(kicad_symbol_lib (version {}) (generator \"easyeda2kicad_rs\") (generator_version \"{}\")\n",
+                self.symbol_lib_version(),
+                generator_version
+            ),
+            // Older releases write the generator as a bare symbol.
+            None => format!(
+                "(kicad_symbol_lib (version {}) (generator easyeda2kicad_rs)\n",
+                self.symbol_lib_version()
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for KiCadFormat {
+    type Err = String;
+
+    /// Parses a major version such as `"7"` or `"v7"`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().trim_start_matches(['v', 'V']) {
+            "5" => Ok(KiCadFormat::V5),
+            "6" => Ok(KiCadFormat::V6),
+            "7" => Ok(KiCadFormat::V7),
+            "8" => Ok(KiCadFormat::V8),
+            other => Err(format!("unsupported KiCad version '{}'", other)),
+        }
+    }
 }
 
 // --- Footprint Structs ---
@@ -28,10 +241,23 @@ pub enum FpShape {
     Oval,
 }
 
+/// Thermal relief settings for a pad's connection to copper zones.
+///
+/// `None` fields inherit the zone's defaults. KiCad always uses four spokes, so
+/// there is no per-pad spoke count to set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PadThermal {
+    /// Spoke (bridge) width in mm.
+    pub width: Option<f32>,
+    /// Gap between the pad and the surrounding zone in mm.
+    pub gap: Option<f32>,
+}
+
 #[derive(Debug)]
 pub struct FpPad {
     pub number: String,
-    pub pad_type: String, // "smd" or "thru_hole"
+    pub pad_type: String, // "smd", "thru_hole" or "np_thru_hole"
     pub shape: FpShape,
     pub pos: (f32, f32),
     pub size: (f32, f32),
@@ -39,6 +265,11 @@ pub struct FpPad {
     pub rotation: f32,                  // in degrees
     pub drill: Option<f32>,             // drill diameter in mm for circular holes
     pub drill_oval: Option<(f32, f32)>, // (width, height) for oval/slot holes
+    pub thermal: Option<PadThermal>,    // None = inherit zone settings
+    pub clearance: Option<f32>,         // None = inherit net class clearance
+    /// The pad's net in EasyEDA. Library footprints have no nets, so it is not
+    /// written to the footprint; see [`KiFootprint::netlist`].
+    pub net: Option<String>,
 }
 
 #[derive(Debug)]
@@ -58,12 +289,24 @@ pub enum FpGraphicType {
     Circle {
         center: (f32, f32),
         end: (f32, f32),
+        /// A solid disc rather than an outline.
+        filled: bool,
     }, // KiCad defines circle by Center + Point on edge
     Arc {
         start: (f32, f32),
         mid: (f32, f32),
         end: (f32, f32),
     },
+    /// A closed outline, filled solid when `filled`.
+    Poly {
+        points: Vec<(f32, f32)>,
+        filled: bool,
+    },
+    /// An axis-aligned, unfilled rectangle between two opposite corners.
+    Rect {
+        start: (f32, f32),
+        end: (f32, f32),
+    },
 }
 
 #[derive(Debug)]
@@ -73,16 +316,46 @@ pub struct FpGraphic {
     pub width: f32,
 }
 
+/// What a keepout zone forbids; `true` means allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KiKeepout {
+    pub tracks: bool,
+    pub vias: bool,
+    pub pads: bool,
+    pub copperpour: bool,
+    pub footprints: bool,
+}
+
+/// A keepout zone (rule area) belonging to a footprint.
+#[derive(Debug)]
+pub struct KiFootprintZone {
+    pub layers: Vec<String>,
+    /// Outline, implicitly closed.
+    pub points: Vec<(f32, f32)>,
+    pub keepout: KiKeepout,
+}
+
 #[derive(Debug)]
 pub struct KiFootprint {
     pub name: String,
     pub pads: Vec<FpPad>,
     pub texts: Vec<FpText>,
     pub graphics: Vec<FpGraphic>,
+    pub zones: Vec<KiFootprintZone>,
     pub model_3d: Option<Ki3dModel>,
+    /// Whether the footprint has been flipped to the bottom side
+    /// (see [`KiFootprint::mirror_to_bottom`]).
+    pub bottom_side: bool,
+    /// Leave the footprint out of the BOM (test points, fiducials, ...).
+    pub exclude_from_bom: bool,
+    /// Leave the footprint out of position (pick and place) files.
+    pub exclude_from_pos: bool,
+    /// Non-fatal issues found during conversion.
+    pub warnings: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KiPinType {
     Input,
     Output,
@@ -100,12 +373,57 @@ pub struct KiSymbolPin {
     pub length: f32,
     pub pos: (f32, f32),
     pub rotation: i32,
+    /// Hidden pins are stacked duplicates of a visible pin with the same number.
+    pub hidden: bool,
+}
+
+/// How a symbol shape's inside is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillType {
+    /// Transparent; whatever lies underneath shows through.
+    None,
+    /// Filled with the outline color.
+    Solid,
+    /// Filled with the body background color (KiCad's usual symbol body).
+    #[default]
+    Background,
+}
+
+impl FillType {
+    /// The `(fill (type ...))` keyword KiCad uses for this fill.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FillType::None => "none",
+            FillType::Solid => "outline",
+            FillType::Background => "background",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct KiSymbolRect {
     pub start: (f32, f32),
     pub end: (f32, f32),
+    /// Outline width in mm.
+    pub stroke_width: f32,
+    pub fill: FillType,
+}
+
+/// A symbol arc through three points.
+#[derive(Debug)]
+pub struct KiSymbolArc {
+    pub start: (f32, f32),
+    pub mid: (f32, f32),
+    pub end: (f32, f32),
+}
+
+/// One unit of a multi-unit symbol, drawn like a whole [`KiSymbol`].
+#[derive(Debug, Default)]
+pub struct KiSymbolUnit {
+    pub pins: Vec<KiSymbolPin>,
+    pub rectangles: Vec<KiSymbolRect>,
+    pub arcs: Vec<KiSymbolArc>,
+    pub polylines: Vec<Vec<(f32, f32)>>,
 }
 
 #[derive(Debug)]
@@ -116,17 +434,104 @@ pub struct KiSymbol {
     pub datasheet: String,
     pub lcsc_part: Option<String>,
     pub is_extended: bool,
+    pub description: Option<String>,
+    pub keywords: Option<String>,
+    pub manufacturer: Option<String>,
+    pub mpn: Option<String>,
     pub pins: Vec<KiSymbolPin>,
     pub rectangles: Vec<KiSymbolRect>,
+    pub arcs: Vec<KiSymbolArc>,
+    /// Open outlines, e.g. arcs KiCad can't draw natively, approximated.
+    pub polylines: Vec<Vec<(f32, f32)>>,
+    /// Further units of a multi-unit symbol, numbered from 2; the pins and graphics
+    /// above are unit 1.
+    pub units: Vec<KiSymbolUnit>,
+    /// Emit `(pin_numbers hide)`, e.g. for test points.
+    pub hide_pin_numbers: bool,
+    /// Emit `(pin_names hide)`.
+    pub hide_pin_names: bool,
+    /// Non-fatal issues found during conversion.
+    pub warnings: Vec<String>,
+}
+
+/// Escapes a value for use inside a quoted S-expression string.
+fn escape_string(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes a value as a bare S-expression atom when it can be one, otherwise as a
+/// quoted string. Line breaks (`\r\n` and `\r` included) become `\n` escapes, which KiCad
+/// shows as multi-line text.
+fn sexpr_atom(value: &str) -> String {
+    let bare = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\\'));
+    if bare {
+        value.to_string()
+    } else {
+        format!(
+            "\"{}\"",
+            escape_string(&value.replace("\r\n", "\n").replace('\r', "\n"))
+        )
+    }
 }
 
 impl KiSymbol {
+    /// Pins of every unit, unit 1 first.
+    pub fn all_pins(&self) -> impl Iterator<Item = &KiSymbolPin> {
+        self.pins
+            .iter()
+            .chain(self.units.iter().flat_map(|unit| &unit.pins))
+    }
+
+    /// Returns the symbol's pinout as `(number, name)` pairs.
+    ///
+    /// Pins are ordered by number (numerically where possible, so "2" comes before
+    /// "10"); stacked pins that repeat the same number and name appear once.
+    pub fn pin_map(&self) -> Vec<(String, String)> {
+        let mut map: Vec<(String, String)> = self
+            .all_pins()
+            .map(|p| (p.number.clone(), p.name.clone()))
+            .collect();
+        map.sort_by(|a, b| {
+            let key = |n: &str| (n.parse::<u64>().map_err(|_| n.to_string()), n.to_string());
+            key(&a.0).cmp(&key(&b.0)).then_with(|| a.1.cmp(&b.1))
+        });
+        map.dedup();
+        map
+    }
+
+    /// Serializes [`pin_map`](Self::pin_map) as a JSON array of
+    /// `{"number": ..., "name": ...}` objects.
+    pub fn pin_map_json(&self) -> String {
+        let pins: Vec<serde_json::Value> = self
+            .pin_map()
+            .into_iter()
+            .map(|(number, name)| serde_json::json!({ "number": number, "name": name }))
+            .collect();
+        serde_json::to_string_pretty(&pins).unwrap_or_default()
+    }
+
     pub fn to_kicad_lib_entry(&self) -> String {
         let mut out = String::new();
         writeln!(
             &mut out,
-            "(symbol \"{}\" (in_bom yes) (on_board yes)",
-            self.name
+            "(symbol \"{}\"{}{} (in_bom yes) (on_board yes)",
+            self.name,
+            if self.hide_pin_numbers {
+                " (pin_numbers hide)"
+            } else {
+                ""
+            },
+            if self.hide_pin_names {
+                " (pin_names hide)"
+            } else {
+                ""
+            }
         )
         .unwrap();
 
@@ -145,58 +550,398 @@ impl KiSymbol {
         }
         writeln!(&mut out, "  (property \"Extended\" \"{}\" (id 5) (at 0 0 0) (effects (font (size 1.27 1.27)) hide))", self.is_extended).unwrap();
 
-        // --- Symbol Graphics ---
-        writeln!(&mut out, "  (symbol \"{}_1_1\"", self.name).unwrap();
+        // Searchable metadata. `ki_description`/`ki_keywords` are what KiCad's symbol
+        // chooser indexes; manufacturer info is kept as plain hidden fields.
+        let extra_properties = [
+            ("ki_description", &self.description),
+            ("ki_keywords", &self.keywords),
+            ("Manufacturer", &self.manufacturer),
+            ("MPN", &self.mpn),
+        ];
+        let mut id = 6;
+        for (key, value) in extra_properties {
+            if let Some(value) = value {
+                writeln!(&mut out, "  (property \"{}\" \"{}\" (id {}) (at 0 0 0) (effects (font (size 1.27 1.27)) hide))", key, escape_string(value), id).unwrap();
+                id += 1;
+            }
+        }
 
-        for rect in &self.rectangles {
-            writeln!(&mut out, "    (rectangle (start {} {}) (end {} {}) (stroke (width 0.254) (type default) (color 0 0 0 0)) (fill (type background)))",
-                rect.start.0, rect.start.1, rect.end.0, rect.end.1).unwrap();
+        // --- Symbol Graphics ---
+        write_symbol_unit(
+            &mut out,
+            &self.name,
+            1,
+            &self.pins,
+            &self.rectangles,
+            &self.arcs,
+            &self.polylines,
+        );
+        for (i, unit) in self.units.iter().enumerate() {
+            write_symbol_unit(
+                &mut out,
+                &self.name,
+                i + 2,
+                &unit.pins,
+                &unit.rectangles,
+                &unit.arcs,
+                &unit.polylines,
+            );
         }
+        writeln!(&mut out, ")").unwrap();
+        out
+    }
+}
 
-        for pin in &self.pins {
-            let pin_type_str = match pin.pin_type {
-                KiPinType::Input => "input",
-                KiPinType::Output => "output",
-                KiPinType::Bidirectional => "bidirectional",
-                KiPinType::PowerIn => "power_in",
-                KiPinType::Passive => "passive",
-                KiPinType::Unspecified => "unspecified",
-            };
+/// Writes the `(symbol "{name}_{unit}_1" ...)` block drawing one unit.
+fn write_symbol_unit(
+    out: &mut String,
+    name: &str,
+    unit: usize,
+    pins: &[KiSymbolPin],
+    rectangles: &[KiSymbolRect],
+    arcs: &[KiSymbolArc],
+    polylines: &[Vec<(f32, f32)>],
+) {
+    writeln!(out, "  (symbol \"{}_{}_1\"", name, unit).unwrap();
 
-            let pin_name = if pin.name.starts_with('~') {
-                format!("\"~{{{}}}\"", &pin.name[1..])
-            } else {
-                format!("\"{}\"", pin.name)
-            };
+    for rect in rectangles {
+        writeln!(out, "    (rectangle (start {} {}) (end {} {}) (stroke (width {}) (type default) (color 0 0 0 0)) (fill (type {})))",
+            rect.start.0, rect.start.1, rect.end.0, rect.end.1, rect.stroke_width, rect.fill.as_str()).unwrap();
+    }
+    for arc in arcs {
+        writeln!(out, "    (arc (start {} {}) (mid {} {}) (end {} {}) (stroke (width 0.254) (type default) (color 0 0 0 0)) (fill (type none)))",
+            arc.start.0, arc.start.1, arc.mid.0, arc.mid.1, arc.end.0, arc.end.1).unwrap();
+    }
+    for points in polylines {
+        let pts: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("(xy {} {})", x, y))
+            .collect();
+        writeln!(out, "    (polyline (pts {}) (stroke (width 0.254) (type default) (color 0 0 0 0)) (fill (type none)))",
+            pts.join(" ")).unwrap();
+    }
 
-            writeln!(
-                &mut out,
-                r#"    (pin {} line (at {} {} {}) (length {})
+    for pin in pins {
+        let pin_type_str = match pin.pin_type {
+            KiPinType::Input => "input",
+            KiPinType::Output => "output",
+            KiPinType::Bidirectional => "bidirectional",
+            KiPinType::PowerIn => "power_in",
+            KiPinType::Passive => "passive",
+            KiPinType::Unspecified => "unspecified",
+        };
+
+        let pin_name = if pin.name.starts_with('~') {
+            format!("\"~{{{}}}\"", &pin.name[1..])
+        } else {
+            format!("\"{}\"", pin.name)
+        };
+
+        writeln!(
+            out,
+            r#"    (pin {} line (at {} {} {}) (length {}){}
       (name {} (effects (font (size 1.27 1.27))))
       (number "{}" (effects (font (size 1.27 1.27))))
     )"#,
-                pin_type_str, pin.pos.0, pin.pos.1, pin.rotation, pin.length, pin_name, pin.number
-            )
-            .unwrap();
-        }
+            pin_type_str,
+            pin.pos.0,
+            pin.pos.1,
+            pin.rotation,
+            pin.length,
+            if pin.hidden { " hide" } else { "" },
+            pin_name,
+            pin.number
+        )
+        .unwrap();
+    }
 
-        writeln!(&mut out, "  )\n)").unwrap(); // Close symbol "{name}_1" and main symbol
-        out
+    writeln!(out, "  )").unwrap();
+}
+
+/// Swaps a front layer name for its back counterpart and vice versa
+/// (`F.SilkS` <-> `B.SilkS`). Other layers (`*.Cu`, `Dwgs.User`, ...) are unchanged.
+fn flip_layer(layer: &str) -> String {
+    if let Some(rest) = layer.strip_prefix("F.") {
+        format!("B.{}", rest)
+    } else if let Some(rest) = layer.strip_prefix("B.") {
+        format!("F.{}", rest)
+    } else {
+        layer.to_string()
     }
 }
 
+/// Converts a three-point arc to KiCad 5's form: the center, and the angle in
+/// degrees swept from `start` (clockwise on screen is positive). `None` if the
+/// points are collinear.
+fn legacy_arc(start: (f32, f32), mid: (f32, f32), end: (f32, f32)) -> Option<((f32, f32), f32)> {
+    let (ax, ay) = (start.0 as f64, start.1 as f64);
+    let (bx, by) = (mid.0 as f64, mid.1 as f64);
+    let (cx, cy) = (end.0 as f64, end.1 as f64);
+    let d = 2.0 * (ax * (by - cy) + bx * (cy - ay) + cx * (ay - by));
+    if d.abs() < 1e-9 {
+        return None;
+    }
+    let (a2, b2, c2) = (ax * ax + ay * ay, bx * bx + by * by, cx * cx + cy * cy);
+    let ux = (a2 * (by - cy) + b2 * (cy - ay) + c2 * (ay - by)) / d;
+    let uy = (a2 * (cx - bx) + b2 * (ax - cx) + c2 * (bx - ax)) / d;
+
+    let angle_of = |x: f64, y: f64| (y - uy).atan2(x - ux).to_degrees();
+    let from_start = |x: f64, y: f64| (angle_of(x, y) - angle_of(ax, ay)).rem_euclid(360.0);
+    let (to_mid, to_end) = (from_start(bx, by), from_start(cx, cy));
+    // Sweep in whichever direction passes through `mid`.
+    let sweep = if to_mid < to_end {
+        to_end
+    } else {
+        to_end - 360.0
+    };
+    Some((
+        (ux as f32, uy as f32),
+        ((sweep * 1000.0).round() / 1000.0) as f32,
+    ))
+}
+
 impl KiFootprint {
+    /// Pad number and EasyEDA net name of every numbered pad that has a net, in pad
+    /// order and once per number. Empty for footprints without internal nets.
+    pub fn netlist(&self) -> Vec<(String, String)> {
+        let mut netlist: Vec<(String, String)> = Vec::new();
+        for pad in &self.pads {
+            if let Some(net) = &pad.net
+                && !pad.number.is_empty()
+                && !netlist.iter().any(|(number, _)| *number == pad.number)
+            {
+                netlist.push((pad.number.clone(), net.clone()));
+            }
+        }
+        netlist
+    }
+
+    /// Turns the footprint into its bottom-side variant, as KiCad's "Flip" does.
+    ///
+    /// Front and back layers are swapped on every pad, text and graphic, the geometry
+    /// is mirrored left to right and rotations are negated. The 3D model is left as
+    /// is: KiCad's 3D viewer turns models of bottom-side footprints over itself, so
+    /// its offset and rotation already match the mirrored footprint. Calling this on
+    /// a footprint that is already on the bottom does nothing.
+    pub fn mirror_to_bottom(&mut self) {
+        if self.bottom_side {
+            return;
+        }
+        self.bottom_side = true;
+        let mirror = |p: &mut (f32, f32)| p.0 = -p.0;
+
+        for pad in &mut self.pads {
+            mirror(&mut pad.pos);
+            pad.rotation = if pad.rotation == 0.0 {
+                0.0
+            } else {
+                -pad.rotation
+            };
+            pad.layers = pad.layers.iter().map(|l| flip_layer(l)).collect();
+        }
+        for text in &mut self.texts {
+            mirror(&mut text.pos);
+            text.layer = flip_layer(&text.layer);
+        }
+        for graphic in &mut self.graphics {
+            graphic.layer = flip_layer(&graphic.layer);
+            match &mut graphic.graphic_type {
+                FpGraphicType::Line { start, end }
+                | FpGraphicType::Circle {
+                    center: start, end, ..
+                }
+                | FpGraphicType::Rect { start, end } => {
+                    mirror(start);
+                    mirror(end);
+                }
+                FpGraphicType::Arc { start, mid, end } => {
+                    mirror(start);
+                    mirror(mid);
+                    mirror(end);
+                }
+                FpGraphicType::Poly { points, .. } => points.iter_mut().for_each(mirror),
+            }
+        }
+        for zone in &mut self.zones {
+            zone.layers = zone.layers.iter().map(|l| flip_layer(l)).collect();
+            zone.points.iter_mut().for_each(mirror);
+        }
+    }
+
+    /// Scales all geometry, including line widths and the 3D model's offset, by
+    /// `factor` about the footprint origin. Text sizes are left alone.
+    pub fn scale(&mut self, factor: f32) {
+        let scale = |p: &mut (f32, f32)| *p = (p.0 * factor, p.1 * factor);
+
+        for pad in &mut self.pads {
+            scale(&mut pad.pos);
+            scale(&mut pad.size);
+            pad.drill = pad.drill.map(|d| d * factor);
+            if let Some(oval) = &mut pad.drill_oval {
+                scale(oval);
+            }
+            pad.clearance = pad.clearance.map(|c| c * factor);
+        }
+        for text in &mut self.texts {
+            scale(&mut text.pos);
+        }
+        for graphic in &mut self.graphics {
+            graphic.width *= factor;
+            match &mut graphic.graphic_type {
+                FpGraphicType::Line { start, end }
+                | FpGraphicType::Circle {
+                    center: start, end, ..
+                }
+                | FpGraphicType::Rect { start, end } => {
+                    scale(start);
+                    scale(end);
+                }
+                FpGraphicType::Arc { start, mid, end } => {
+                    scale(start);
+                    scale(mid);
+                    scale(end);
+                }
+                FpGraphicType::Poly { points, .. } => points.iter_mut().for_each(scale),
+            }
+        }
+        for zone in &mut self.zones {
+            zone.points.iter_mut().for_each(scale);
+        }
+        if let Some(model) = &mut self.model_3d {
+            let offset = &mut model.offset;
+            offset[0] *= factor;
+            offset[1] *= factor;
+            offset[2] *= factor;
+        }
+    }
+
     /// Generates the full S-expression string for a .kicad_mod file.
+    ///
+    /// The 3D model is referenced relative to the `.pretty` directory.
     pub fn to_kicad_mod_entry(&self) -> String {
+        self.to_kicad_mod_entry_with_model_dir("../3dmodels.3dshapes")
+    }
+
+    /// Same as [`KiFootprint::to_kicad_mod_entry`], but references the 3D model
+    /// inside `model_dir` (e.g. an absolute path or a `${KIPRJMOD}` path).
+    pub fn to_kicad_mod_entry_with_model_dir(&self, model_dir: &str) -> String {
+        self.to_kicad_mod_entry_with_model_ref(model_dir, Model3dFormat::default())
+    }
+
+    /// Same as [`KiFootprint::to_kicad_mod_entry_with_model_dir`], referencing the
+    /// model file that `model_format` generates (`.wrl`, or `.step` for STEP only).
+    pub fn to_kicad_mod_entry_with_model_ref(
+        &self,
+        model_dir: &str,
+        model_format: Model3dFormat,
+    ) -> String {
+        self.to_kicad_mod_entry_with_format(model_dir, model_format, KiCadFormat::default())
+    }
+
+    /// Same as [`KiFootprint::to_kicad_mod_entry_with_model_ref`], in the syntax of
+    /// the KiCad release `format`.
+    ///
+    /// [`KiCadFormat::V5`] writes the legacy syntax: a `(tedit ...)` stamp, `(width)`
+    /// instead of `(stroke)`, arcs as center and angle, and rectangles as four lines.
+    /// KiCad 5 has no keepout zones in footprints, so those are left out, and
+    /// excluding a footprint from the BOM or position files marks it `virtual`.
+    ///
+    /// V7 and later write the `(footprint ...)` token with its version, generator,
+    /// a stable `tstamp` (`uuid` from V8 on) derived from the name, and an `(attr)`
+    /// naming the footprint type. V6 keeps writing `(module ...)` as before.
+    pub fn to_kicad_mod_entry_with_format(
+        &self,
+        model_dir: &str,
+        model_format: Model3dFormat,
+        format: KiCadFormat,
+    ) -> String {
+        let legacy = format == KiCadFormat::V5;
+        // Stroke, fill and layer of a graphic, in the order the format expects.
+        let style = |width: f32, layer: &str, fill: Option<&str>| {
+            if legacy {
+                format!("(layer {}) (width {})", layer, width)
+            } else if let Some(fill) = fill {
+                format!(
+                    "(stroke (width {}) (type solid)) (fill {}) (layer {})",
+                    width, fill, layer
+                )
+            } else {
+                format!("(stroke (width {}) (type solid)) (layer {})", width, layer)
+            }
+        };
+
         let mut out = String::new();
-        writeln!(&mut out, "(module {} (layer F.Cu)", self.name).unwrap();
+        let side = if self.bottom_side { "B.Cu" } else { "F.Cu" };
+        let mut attrs = Vec::new();
+        if let Some(version) = format.footprint_version() {
+            let generator = match format.generator_version() {
+                Some(generator_version) => format!(
+                    "(generator \"easyeda2kicad_rs\") (generator_version \"{}\")",
+                    generator_version
+                ),
+                None => "(generator easyeda2kicad_rs)".to_string(),
+            };
+            writeln!(
+                &mut out,
+                "(footprint \"{}\" (version {}) {}\n  (layer \"{}\")",
+                escape_string(&self.name),
+                version,
+                generator,
+                side
+            )
+            .unwrap();
+            // Derived from the name so regenerating a footprint doesn't change it.
+            let hex = crate::sha256_hex(self.name.as_bytes());
+            let id = format!(
+                "{}-{}-{}-{}-{}",
+                &hex[..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..32]
+            );
+            match format {
+                KiCadFormat::V7 => writeln!(&mut out, "  (tstamp {})", id),
+                _ => writeln!(&mut out, "  (uuid \"{}\")", id),
+            }
+            .unwrap();
+            if self.pads.iter().any(|p| p.pad_type == "thru_hole") {
+                attrs.push("through_hole");
+            } else if self.pads.iter().any(|p| p.pad_type == "smd") {
+                attrs.push("smd");
+            }
+        } else if legacy {
+            // KiCad only uses the edit stamp to tell revisions apart; a fixed value
+            // keeps the output reproducible.
+            writeln!(&mut out, "(module {} (layer {}) (tedit 0)", self.name, side).unwrap();
+            if self.exclude_from_bom || self.exclude_from_pos {
+                writeln!(&mut out, "  (attr virtual)").unwrap();
+            }
+        } else {
+            writeln!(&mut out, "(module {} (layer {})", self.name, side).unwrap();
+        }
+        if self.exclude_from_bom {
+            attrs.push("exclude_from_bom");
+        }
+        if self.exclude_from_pos {
+            attrs.push("exclude_from_pos_files");
+        }
+        if !legacy && !attrs.is_empty() {
+            writeln!(&mut out, "  (attr {})", attrs.join(" ")).unwrap();
+        }
 
         // Add texts (reference, value, etc.)
         for text in &self.texts {
+            // Text on the back is mirrored so it reads correctly from below.
+            let justify = if text.layer.starts_with("B.") {
+                " (justify mirror)"
+            } else {
+                ""
+            };
             writeln!(
                 &mut out,
-                "  (fp_text {} {} (at {} {}) (layer {}) (effects (font (size 1 1) (thickness 0.15))))",
-                text.text_type, text.text, text.pos.0, text.pos.1, text.layer
+                "  (fp_text {} {} (at {} {}) (layer {}) (effects (font (size 1 1) (thickness 0.15)){}))",
+                text.text_type, sexpr_atom(&text.text), text.pos.0, text.pos.1, text.layer, justify
             ).unwrap();
         }
 
@@ -205,23 +950,120 @@ impl KiFootprint {
                 FpGraphicType::Line { start, end } => {
                     writeln!(
                         &mut out,
-                        "  (fp_line (start {} {}) (end {} {}) (stroke (width {}) (type solid)) (layer {}))",
-                        start.0, start.1, end.0, end.1, graphic.width, graphic.layer
-                    ).unwrap();
+                        "  (fp_line (start {} {}) (end {} {}) {})",
+                        start.0,
+                        start.1,
+                        end.0,
+                        end.1,
+                        style(graphic.width, &graphic.layer, None)
+                    )
+                    .unwrap();
                 }
-                FpGraphicType::Circle { center, end } => {
+                FpGraphicType::Circle {
+                    center,
+                    end,
+                    filled,
+                } => {
                     writeln!(
                         &mut out,
-                        "  (fp_circle (center {} {}) (end {} {}) (stroke (width {}) (type solid)) (layer {}))",
-                        center.0, center.1, end.0, end.1, graphic.width, graphic.layer
-                    ).unwrap();
+                        "  (fp_circle (center {} {}) (end {} {}) {})",
+                        center.0,
+                        center.1,
+                        end.0,
+                        end.1,
+                        style(graphic.width, &graphic.layer, filled.then_some("solid"))
+                    )
+                    .unwrap();
+                }
+                FpGraphicType::Arc { start, mid, end } if legacy => {
+                    let line = |out: &mut String, a: &(f32, f32), b: &(f32, f32)| {
+                        writeln!(
+                            out,
+                            "  (fp_line (start {} {}) (end {} {}) {})",
+                            a.0,
+                            a.1,
+                            b.0,
+                            b.1,
+                            style(graphic.width, &graphic.layer, None)
+                        )
+                        .unwrap();
+                    };
+                    match legacy_arc(*start, *mid, *end) {
+                        Some((center, angle)) => writeln!(
+                            &mut out,
+                            "  (fp_arc (start {} {}) (end {} {}) (angle {}) {})",
+                            center.0,
+                            center.1,
+                            start.0,
+                            start.1,
+                            angle,
+                            style(graphic.width, &graphic.layer, None)
+                        )
+                        .unwrap(),
+                        // Collinear points: the "arc" is straight.
+                        None => {
+                            line(&mut out, start, mid);
+                            line(&mut out, mid, end);
+                        }
+                    }
                 }
                 FpGraphicType::Arc { start, mid, end } => {
                     writeln!(
                         &mut out,
-                        "  (fp_arc (start {} {}) (mid {} {}) (end {} {}) (stroke (width {}) (type solid)) (layer {}))",
-                        start.0, start.1, mid.0, mid.1, end.0, end.1, graphic.width, graphic.layer
-                    ).unwrap();
+                        "  (fp_arc (start {} {}) (mid {} {}) (end {} {}) {})",
+                        start.0,
+                        start.1,
+                        mid.0,
+                        mid.1,
+                        end.0,
+                        end.1,
+                        style(graphic.width, &graphic.layer, None)
+                    )
+                    .unwrap();
+                }
+                FpGraphicType::Poly { points, filled } => {
+                    let pts: Vec<String> = points
+                        .iter()
+                        .map(|(x, y)| format!("(xy {} {})", x, y))
+                        .collect();
+                    writeln!(
+                        &mut out,
+                        "  (fp_poly (pts {}) {})",
+                        pts.join(" "),
+                        style(
+                            graphic.width,
+                            &graphic.layer,
+                            Some(if *filled { "solid" } else { "none" })
+                        )
+                    )
+                    .unwrap();
+                }
+                FpGraphicType::Rect { start, end } if legacy => {
+                    let corners = [*start, (end.0, start.1), *end, (start.0, end.1), *start];
+                    for edge in corners.windows(2) {
+                        writeln!(
+                            &mut out,
+                            "  (fp_line (start {} {}) (end {} {}) {})",
+                            edge[0].0,
+                            edge[0].1,
+                            edge[1].0,
+                            edge[1].1,
+                            style(graphic.width, &graphic.layer, None)
+                        )
+                        .unwrap();
+                    }
+                }
+                FpGraphicType::Rect { start, end } => {
+                    writeln!(
+                        &mut out,
+                        "  (fp_rect (start {} {}) (end {} {}) {})",
+                        start.0,
+                        start.1,
+                        end.0,
+                        end.1,
+                        style(graphic.width, &graphic.layer, Some("none"))
+                    )
+                    .unwrap();
                 }
             }
         }
@@ -230,21 +1072,23 @@ impl KiFootprint {
         if let Some(model) = &self.model_3d {
             writeln!(
                 &mut out,
-                r#"  (model "../3dmodels.3dshapes/{}.wrl"
+                r#"  (model "{}/{}.{}"
     (offset (xyz {} {} {}))
     (scale (xyz {} {} {}))
     (rotate (xyz {} {} {}))
   )"#,
+                model_dir,
                 model.name,
-                model.offset.x,
-                model.offset.y,
-                model.offset.z,
-                model.scale.x,
-                model.scale.y,
-                model.scale.z,
-                model.rotate.x,
-                model.rotate.y,
-                model.rotate.z
+                model_format.reference_extension(),
+                model.offset[0],
+                model.offset[1],
+                model.offset[2],
+                model.scale[0],
+                model.scale[1],
+                model.scale[2],
+                model.rotate[0],
+                model.rotate[1],
+                model.rotate[2]
             )
             .unwrap();
         }
@@ -257,13 +1101,36 @@ impl KiFootprint {
                 FpShape::Oval => "oval",
             };
             let layers_str = pad.layers.join(" ");
+            // Mechanical holes have no number, which KiCad writes as "".
+            let number = if pad.number.is_empty() {
+                "\"\""
+            } else {
+                pad.number.as_str()
+            };
+
+            // Optional per-pad settings, appended after the drill.
+            let mut extra = String::new();
+            if let Some(clearance) = pad.clearance {
+                write!(&mut extra, " (clearance {})", clearance).unwrap();
+            }
+            if let Some(thermal) = &pad.thermal {
+                // `thermal_width` (rather than KiCad 7's `thermal_bridge_width`) is
+                // understood by every KiCad version that reads this file format.
+                extra.push_str(" (zone_connect 1)");
+                if let Some(width) = thermal.width {
+                    write!(&mut extra, " (thermal_width {})", width).unwrap();
+                }
+                if let Some(gap) = thermal.gap {
+                    write!(&mut extra, " (thermal_gap {})", gap).unwrap();
+                }
+            }
 
             if let Some((width, height)) = pad.drill_oval {
                 // Oval/slot hole
                 writeln!(
                     &mut out,
-                    "  (pad {} {} {} (at {} {} {}) (size {} {}) (layers {}) (drill oval {} {}))",
-                    pad.number,
+                    "  (pad {} {} {} (at {} {} {}) (size {} {}) (layers {}) (drill oval {} {}){})",
+                    number,
                     pad.pad_type,
                     shape_str,
                     pad.pos.0,
@@ -273,15 +1140,16 @@ impl KiFootprint {
                     pad.size.1,
                     layers_str,
                     width,
-                    height
+                    height,
+                    extra
                 )
                 .unwrap();
             } else if let Some(drill_dia) = pad.drill {
                 // Circular hole
                 writeln!(
                     &mut out,
-                    "  (pad {} {} {} (at {} {} {}) (size {} {}) (layers {}) (drill {}))",
-                    pad.number,
+                    "  (pad {} {} {} (at {} {} {}) (size {} {}) (layers {}) (drill {}){})",
+                    number,
                     pad.pad_type,
                     shape_str,
                     pad.pos.0,
@@ -290,15 +1158,16 @@ impl KiFootprint {
                     pad.size.0,
                     pad.size.1,
                     layers_str,
-                    drill_dia
+                    drill_dia,
+                    extra
                 )
                 .unwrap();
             } else {
                 // SMD pad (no drill)
                 writeln!(
                     &mut out,
-                    "  (pad {} {} {} (at {} {} {}) (size {} {}) (layers {}))",
-                    pad.number,
+                    "  (pad {} {} {} (at {} {} {}) (size {} {}) (layers {}){})",
+                    number,
                     pad.pad_type,
                     shape_str,
                     pad.pos.0,
@@ -306,12 +1175,39 @@ impl KiFootprint {
                     pad.rotation,
                     pad.size.0,
                     pad.size.1,
-                    layers_str
+                    layers_str,
+                    extra
                 )
                 .unwrap();
             }
         }
 
+        for zone in self.zones.iter().filter(|_| !legacy) {
+            let rule = |allowed: bool| if allowed { "allowed" } else { "not_allowed" };
+            let keepout = &zone.keepout;
+            let pts: Vec<String> = zone
+                .points
+                .iter()
+                .map(|(x, y)| format!("(xy {} {})", x, y))
+                .collect();
+            writeln!(
+                &mut out,
+                "  (zone (net 0) (net_name \"\") (layers {}) (hatch edge 0.508) \
+                 (connect_pads (clearance 0)) (min_thickness 0.254) \
+                 (keepout (tracks {}) (vias {}) (pads {}) (copperpour {}) (footprints {})) \
+                 (fill (thermal_gap 0.508) (thermal_bridge_width 0.508)) \
+                 (polygon (pts {})))",
+                zone.layers.join(" "),
+                rule(keepout.tracks),
+                rule(keepout.vias),
+                rule(keepout.pads),
+                rule(keepout.copperpour),
+                rule(keepout.footprints),
+                pts.join(" ")
+            )
+            .unwrap();
+        }
+
         writeln!(&mut out, ")").unwrap();
         out
     }
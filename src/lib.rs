@@ -1,19 +1,111 @@
 // src/lib.rs
 
 pub mod api;
+pub mod bom;
 pub mod converter;
 pub mod easyeda_models;
 pub mod error;
 pub mod file_writer;
 pub mod importer;
 pub mod kicad_models;
+pub mod manifest;
+pub mod progress;
+mod svg_path;
 
 use crate::{
     error::Result,
-    kicad_models::{KiFootprint, KiSymbol},
+    kicad_models::{
+        Ki3dModel, KiCadFormat, KiFootprint, KiSymbol, LibraryLayout, Model3dFormat, Model3dNaming,
+        SymbolLayout,
+    },
 };
-use std::path::Path;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Options controlling how a component is imported.
+///
+/// Serialized with the field names as keys (`lib` for `lib_name`), which is how
+/// parts manifests set them (see [`manifest`]).
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImportOptions {
+    /// Options passed on to the symbol and footprint converters.
+    pub convert: converter::ConvertOptions,
+    /// Symbol library file name; `None` uses the default `lib`.
+    #[serde(rename = "lib")]
+    pub lib_name: Option<String>,
+    /// The KiCad release the library files are written for.
+    pub format: KiCadFormat,
+    /// Don't download or generate 3D models.
+    pub skip_3d: bool,
+    /// Whether symbols share one library file or get one file each.
+    pub symbol_layout: SymbolLayout,
+    /// Names of the output directory's subdirectories and the footprints' model path.
+    pub layout: LibraryLayout,
+    /// Which 3D model files are generated and referenced.
+    pub model_format: Model3dFormat,
+    /// What the `.wrl`/`.step` files (and the footprint's reference to them) are named after.
+    pub model_naming: Model3dNaming,
+    /// Flip footprints to the bottom side (see [`KiFootprint::mirror_to_bottom`]).
+    pub mirror_to_bottom: bool,
+    /// Save the component data as fetched from EasyEDA to `raw/<LCSC_ID>.json` in
+    /// the output directory, for debugging conversions.
+    pub save_raw: bool,
+    /// Revision the component must be at (see [`api::component_revision`]). The
+    /// import fails if EasyEDA's data has changed since; `None` takes the latest.
+    pub revision: Option<String>,
+    /// How many 3D model downloads may run at once across a batch; `None` uses
+    /// [`api::DEFAULT_MODEL_DOWNLOADS`]. See [`api::EasyedaApi::with_max_model_downloads`].
+    pub max_model_downloads: Option<usize>,
+    /// Also save each footprint's pad-to-net map as `nets/<footprint>.tsv` (see
+    /// [`KiFootprint::netlist`]), for footprints with named internal nets.
+    pub save_netlist: bool,
+}
+
+/// Describes what an import produced.
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub lcsc_id: String,
+    pub symbol_name: String,
+    /// `None` if the part has no footprint; only the symbol was written.
+    pub footprint_name: Option<String>,
+    pub model_name: Option<String>,
+    /// Why the 3D model couldn't be generated, if that failed. The symbol and
+    /// footprint are still written; the footprint just has no model.
+    pub model_error: Option<String>,
+    /// Whether JLCPCB lists the part as an Extended (rather than Basic) part, which
+    /// carries an extra assembly fee.
+    pub is_extended: bool,
+    /// Non-fatal issues found while converting the component.
+    pub warnings: Vec<String>,
+    /// SHA-256 of the component data EasyEDA returned (as compact JSON), recorded in
+    /// manifest lock files to show when a part changed upstream.
+    pub source_sha256: String,
+    /// Revision of the component data that was imported, if EasyEDA reported one.
+    pub revision: Option<String>,
+}
+
+/// SHA-256 of `data`, as lowercase hex.
+pub fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest;
+    sha2::Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Extracts an LCSC part number (`C12345`) from a bare ID or a product URL.
+///
+/// Understands LCSC product pages (`https://www.lcsc.com/product-detail/..._C12345.html`),
+/// JLCPCB part pages (`https://jlcpcb.com/partdetail/...-.../C12345`) and EasyEDA links
+/// carrying the ID. When several candidates appear, the last one wins, since the ID
+/// follows the product name in these URLs.
+pub fn parse_lcsc_id_from_url(input: &str) -> Option<String> {
+    let re = regex::Regex::new(r"(?i)(?:^|[^a-z0-9])(C\d+)(?:[^0-9]|$)").ok()?;
+    re.captures_iter(input.trim())
+        .last()
+        .map(|caps| caps[1].to_uppercase())
+}
 
 /// Imports a component from EasyEDA's library and converts it to KiCad format.
 ///
@@ -27,12 +119,14 @@ use std::collections::HashMap;
 ///
 /// # Arguments
 ///
-/// * `lcsc_id` - The LCSC part number of the component to import (e.g., "C12345")
+/// * `lcsc_id` - The LCSC part number of the component to import (e.g., "C12345"),
+///   or an LCSC/JLCPCB/EasyEDA URL containing it (see [`parse_lcsc_id_from_url`])
 /// * `output_dir` - Directory where the KiCad library files will be created
 ///
 /// # Returns
 ///
-/// * `Result<()>` - Ok(()) if the import was successful, or an Error if any step failed
+/// * `Result<ImportSummary>` - What was generated, plus any conversion warnings,
+///   or an Error if any step failed
 ///
 /// # Examples
 ///
@@ -47,45 +141,262 @@ use std::collections::HashMap;
 ///     Ok(())
 /// }
 /// ```
-pub async fn import_component(lcsc_id: &str, output_dir: &Path) -> Result<()> {
-    println!("Fetching data for LCSC ID: {}", lcsc_id);
+pub async fn import_component(lcsc_id: &str, output_dir: &Path) -> Result<ImportSummary> {
+    import_component_with_options(lcsc_id, output_dir, &ImportOptions::default()).await
+}
+
+/// Same as [`import_component`], with explicit import options.
+pub async fn import_component_with_options(
+    lcsc_id: &str,
+    output_dir: &Path,
+    options: &ImportOptions,
+) -> Result<ImportSummary> {
+    import_with_api(
+        &api_client(options),
+        lcsc_id,
+        output_dir,
+        options,
+        &|_, _| {},
+    )
+    .await
+}
+
+/// Same as [`import_component`], using an existing API client.
+///
+/// Each [`api::EasyedaApi`] holds its own connection pool, so batch jobs should create
+/// one and pass it to every call; later requests then reuse the open connections
+/// instead of repeating the TLS handshake.
+///
+/// # Example
+///
+/// ```no_run
+/// use easyeda2kicad_rs::{api::EasyedaApi, import_component_with_api};
+/// use std::path::Path;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let api = EasyedaApi::new();
+///     for lcsc_id in ["C2040", "C8952"] {
+///         import_component_with_api(&api, lcsc_id, Path::new("kicad_lib")).await?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn import_component_with_api(
+    api: &api::EasyedaApi,
+    lcsc_id: &str,
+    output_dir: &Path,
+) -> Result<ImportSummary> {
+    import_with_api(
+        api,
+        lcsc_id,
+        output_dir,
+        &ImportOptions::default(),
+        &|_, _| {},
+    )
+    .await
+}
+
+/// Imports a component from component data fetched earlier, e.g. saved with
+/// [`ImportOptions::save_raw`], without touching the network.
+///
+/// `cad_data` is the `result` object of EasyEDA's component API. The symbol and
+/// footprint are generated as by [`import_component_with_options`]; 3D models have to
+/// be downloaded and are skipped.
+///
+/// # Example
+///
+/// ```no_run
+/// use easyeda2kicad_rs::{ImportOptions, import_component_from_data};
+/// use std::path::Path;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let raw = std::fs::read_to_string("kicad_lib/raw/C2040.json")?;
+///     let cad_data = serde_json::from_str(&raw)?;
+///     let options = ImportOptions::default();
+///     import_component_from_data("C2040", &cad_data, Path::new("kicad_lib"), &options)
+///         .await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn import_component_from_data(
+    lcsc_id: &str,
+    cad_data: &serde_json::Value,
+    output_dir: &Path,
+    options: &ImportOptions,
+) -> Result<ImportSummary> {
+    import_cad_data(None, lcsc_id, cad_data, output_dir, options, &|_, _| {}).await
+}
+
+/// A client for the imports of one call, with the limits from `options`.
+fn api_client(options: &ImportOptions) -> api::EasyedaApi {
     let api = api::EasyedaApi::new();
-    let kicad_lib = file_writer::KicadLibrary {
-        path: output_dir.to_path_buf(),
+    match options.max_model_downloads {
+        Some(max) => api.with_max_model_downloads(max),
+        None => api,
+    }
+}
+
+/// The import itself. `on_download` follows the STEP model download (bytes so far,
+/// file size if known).
+async fn import_with_api(
+    api: &api::EasyedaApi,
+    lcsc_id: &str,
+    output_dir: &Path,
+    options: &ImportOptions,
+    on_download: &(dyn Fn(u64, Option<u64>) + Sync),
+) -> Result<ImportSummary> {
+    let lcsc_id = &resolve_lcsc_id(lcsc_id)?;
+    println!("Fetching data for LCSC ID: {}", lcsc_id);
+    let cad_data = match &options.revision {
+        Some(rev) => api.get_cad_data_of_component_rev(lcsc_id, rev).await?,
+        None => api.get_cad_data_of_component(lcsc_id).await?,
     };
+    import_cad_data(
+        Some(api),
+        lcsc_id,
+        &cad_data,
+        output_dir,
+        options,
+        on_download,
+    )
+    .await
+}
+
+/// Writes the library entries for fetched component data. Without an API client no
+/// 3D models are generated.
+async fn import_cad_data(
+    api: Option<&api::EasyedaApi>,
+    lcsc_id: &str,
+    cad_data: &serde_json::Value,
+    output_dir: &Path,
+    options: &ImportOptions,
+    on_download: &(dyn Fn(u64, Option<u64>) + Sync),
+) -> Result<ImportSummary> {
+    let mut kicad_lib = file_writer::KicadLibrary::new(output_dir);
+    if let Some(lib_name) = &options.lib_name {
+        kicad_lib.lib_name = lib_name.clone();
+    }
+    kicad_lib.format = options.format;
+    kicad_lib.symbol_layout = options.symbol_layout;
+    kicad_lib.model_format = options.model_format;
+    kicad_lib.layout = options.layout.clone();
     kicad_lib.setup_directories()?;
 
-    let cad_data = api.get_cad_data_of_component(lcsc_id).await?;
+    let source_sha256 = sha256_hex(serde_json::to_string(cad_data)?.as_bytes());
+    if options.save_raw {
+        kicad_lib.add_raw_data(lcsc_id, cad_data)?;
+    }
 
     // --- SYMBOL ---
-    let ee_symbol = importer::import_symbol(&cad_data)?;
-    let ki_symbol = converter::convert_symbol(ee_symbol)?;
-    let ee_footprint = importer::import_footprint(&cad_data)?;
+    let ee_symbol = importer::import_symbol_with_options(cad_data, &options.convert)?;
+    let ki_symbol = converter::convert_symbol_with_options(ee_symbol, &options.convert)?;
+    // Some parts genuinely have no footprint; the symbol is still worth having.
+    let ee_footprint = match importer::import_footprint_with_options(cad_data, &options.convert) {
+        Ok(ee_footprint) => Some(ee_footprint),
+        Err(error::Error::MissingData(reason)) => {
+            println!("No footprint for this component ({}).", reason);
+            None
+        }
+        Err(e) => return Err(e),
+    };
 
     kicad_lib.add_symbol(&ki_symbol)?;
     println!("Successfully generated symbol: {}", ki_symbol.name);
 
+    let (ki_footprint, model_error) = match ee_footprint {
+        Some(ee_footprint) => {
+            let (ki_footprint, model_error) = import_footprint_with_api(
+                api,
+                &kicad_lib,
+                cad_data,
+                ee_footprint,
+                &ki_symbol,
+                lcsc_id,
+                options,
+                on_download,
+            )
+            .await?;
+            (Some(ki_footprint), model_error)
+        }
+        None => (None, None),
+    };
+
+    let mut warnings = ki_symbol.warnings.clone();
+    if let Some(ki_footprint) = &ki_footprint {
+        warnings.extend(ki_footprint.warnings.iter().cloned());
+        if let Some(model) = &ki_footprint.model_3d {
+            warnings.extend(model.warnings.iter().cloned());
+        }
+    }
+    if ki_symbol.is_extended {
+        warnings.push(format!(
+            "{} is a JLCPCB Extended part; assembly incurs an extra setup fee",
+            lcsc_id
+        ));
+    }
+    for warning in &warnings {
+        println!("Warning: {}", warning);
+    }
+
+    println!("\nImport complete. Files are located in: {:?}", output_dir);
+    Ok(ImportSummary {
+        lcsc_id: lcsc_id.to_string(),
+        symbol_name: ki_symbol.name,
+        model_name: ki_footprint
+            .as_ref()
+            .and_then(|f| f.model_3d.as_ref())
+            .map(|m| m.name.clone()),
+        footprint_name: ki_footprint.map(|f| f.name),
+        model_error,
+        is_extended: ki_symbol.is_extended,
+        warnings,
+        source_sha256,
+        revision: api::component_revision(cad_data),
+    })
+}
+
+/// Writes the footprint and its 3D model for [`import_cad_data`].
+#[allow(clippy::too_many_arguments)]
+async fn import_footprint_with_api(
+    api: Option<&api::EasyedaApi>,
+    kicad_lib: &file_writer::KicadLibrary,
+    cad_data: &serde_json::Value,
+    ee_footprint: easyeda_models::EeFootprint,
+    ki_symbol: &KiSymbol,
+    lcsc_id: &str,
+    options: &ImportOptions,
+    on_download: &(dyn Fn(u64, Option<u64>) + Sync),
+) -> Result<(KiFootprint, Option<String>)> {
     // --- 3D MODEL ---
-    let ki_model = if let Some(mut ee_model_info) = importer::import_3d_model_info(&cad_data)? {
-        println!("Found 3D model: {}", ee_model_info.name);
-        let (raw_obj, step) = tokio::join!(
-            api.get_raw_3d_model_obj(&ee_model_info.uuid),
-            api.get_step_3d_model(&ee_model_info.uuid)
-        );
-        ee_model_info.raw_obj = raw_obj.ok();
-        ee_model_info.step = step.ok();
-        let model = converter::convert_3d_model(ee_model_info)?;
-        kicad_lib.add_3d_model(&model)?;
-        println!("Successfully generated 3D model: {}", model.name);
-        Some(model)
-    } else {
-        println!("No 3D model found for this component.");
-        None
+    // A model that can't be fetched or converted shouldn't cost the symbol and
+    // footprint; the footprint is written without one.
+    let (ki_model, model_error) = match import_3d_model(
+        api,
+        kicad_lib,
+        cad_data,
+        &ee_footprint.info.name,
+        lcsc_id,
+        options,
+        on_download,
+    )
+    .await
+    {
+        Ok(model) => (model, None),
+        Err(e) => (None, Some(e.to_string())),
     };
 
     // --- FOOTPRINT ---
     // Pass the 3D model data to the footprint converter
-    let mut ki_footprint = converter::convert_footprint(ee_footprint, ki_model)?;
+    let mut ki_footprint =
+        converter::convert_footprint_with_options(ee_footprint, ki_model, &options.convert)?;
+    if let Some(model_error) = &model_error {
+        ki_footprint.warnings.push(format!(
+            "Footprint '{}': 3D model skipped: {}",
+            ki_footprint.name, model_error
+        ));
+    }
 
     // Harmonize pad numbers between the symbol and footprint.
     // Some EasyEDA symbols use pin numbers like "P1" while footprints use "1".
@@ -95,14 +406,14 @@ pub async fn import_component(lcsc_id: &str, output_dir: &Path) -> Result<()> {
         pad_index.insert(pad.number.clone(), i);
     }
 
-    for pin in &ki_symbol.pins {
+    for pin in ki_symbol.all_pins() {
         let pin_num = pin.number.trim().to_string();
         if pad_index.contains_key(&pin_num) {
             continue; // already matches
         }
 
-        if pin_num.starts_with('P') {
-            let suffix = pin_num[1..].to_string();
+        if let Some(suffix) = pin_num.strip_prefix('P') {
+            let suffix = suffix.to_string();
             if let Some(&idx) = pad_index.get(&suffix) {
                 // rename pad to match pin (e.g., "1" -> "P1")
                 ki_footprint.pads[idx].number = pin_num.clone();
@@ -120,11 +431,292 @@ pub async fn import_component(lcsc_id: &str, output_dir: &Path) -> Result<()> {
         }
     }
 
+    if options.mirror_to_bottom {
+        ki_footprint.mirror_to_bottom();
+    }
     kicad_lib.add_footprint(&ki_footprint)?;
     println!("Successfully generated footprint: {}", ki_footprint.name);
+    if options.save_netlist
+        && let Some(path) = kicad_lib.add_netlist(&ki_footprint)?
+    {
+        println!("Saved pad nets to {:?}", path);
+    }
+    Ok((ki_footprint, model_error))
+}
 
-    println!("\nImport complete. Files are located in: {:?}", output_dir);
-    Ok(())
+/// Fetches, converts and writes the footprint's 3D model for
+/// [`import_footprint_with_api`]. `None` if there is no model or it was skipped.
+#[cfg(feature = "3d")]
+#[allow(clippy::too_many_arguments)]
+async fn import_3d_model(
+    api: Option<&api::EasyedaApi>,
+    kicad_lib: &file_writer::KicadLibrary,
+    cad_data: &serde_json::Value,
+    footprint_name: &str,
+    lcsc_id: &str,
+    options: &ImportOptions,
+    on_download: &(dyn Fn(u64, Option<u64>) + Sync),
+) -> Result<Option<Ki3dModel>> {
+    let model_info = match api {
+        Some(api) if !options.skip_3d => {
+            importer::import_3d_model_info(cad_data)?.map(|m| (api, m))
+        }
+        _ => None,
+    };
+    let ki_model = if let Some((api, mut ee_model_info)) = model_info {
+        println!("Found 3D model: {}", ee_model_info.name);
+        // Stream the STEP file to disk instead of buffering it; it can be tens of MB.
+        let step_tmp = kicad_lib
+            .step_model_path(&ee_model_info.name)
+            .with_extension("step.part");
+        let format = options.model_format;
+        let (raw_obj, step) = tokio::join!(
+            async {
+                if format.wants_wrl() {
+                    Some(api.get_raw_3d_model_obj(&ee_model_info.uuid).await)
+                } else {
+                    None
+                }
+            },
+            async {
+                if format.wants_step() {
+                    let download = api.download_step_3d_model_with_progress(
+                        &ee_model_info.uuid,
+                        &step_tmp,
+                        on_download,
+                    );
+                    Some(download.await.map(|_| ()))
+                } else {
+                    None
+                }
+            }
+        );
+        // Truncated or garbled downloads are dropped, with a warning; a model that
+        // simply isn't there is no news.
+        let mut rejected = Vec::new();
+        let mut note = |e: error::Error| {
+            if let error::Error::CorruptDownload(_) = e {
+                let msg = format!("3D model '{}' rejected: {}", ee_model_info.name, e);
+                log::warn!("{}", msg);
+                rejected.push(msg);
+            }
+        };
+        let raw_obj = raw_obj.and_then(|r| r.map_err(&mut note).ok());
+        let step = step.and_then(|r| r.map_err(&mut note).ok()).is_some();
+        ee_model_info.raw_obj = raw_obj;
+        let mut model =
+            converter::convert_3d_model_with_options(ee_model_info, format, &options.convert)?;
+        model.warnings.extend(rejected);
+        model.name = options
+            .model_naming
+            .model_name(&model.name, footprint_name, lcsc_id);
+        kicad_lib.add_3d_model(&model)?;
+        if step {
+            kicad_lib.add_3d_model_step_file(&model.name, &step_tmp)?;
+        }
+        println!("Successfully generated 3D model: {}", model.name);
+        Some(model)
+    } else {
+        println!("No 3D model found for this component.");
+        None
+    };
+    Ok(ki_model)
+}
+
+/// Without the `3d` feature no 3D models are generated.
+#[cfg(not(feature = "3d"))]
+#[allow(clippy::too_many_arguments)]
+async fn import_3d_model(
+    _api: Option<&api::EasyedaApi>,
+    _kicad_lib: &file_writer::KicadLibrary,
+    _cad_data: &serde_json::Value,
+    _footprint_name: &str,
+    _lcsc_id: &str,
+    _options: &ImportOptions,
+    _on_download: &(dyn Fn(u64, Option<u64>) + Sync),
+) -> Result<Option<Ki3dModel>> {
+    Ok(None)
+}
+
+/// Accepts a bare LCSC ID or a URL containing one.
+fn resolve_lcsc_id(input: &str) -> Result<String> {
+    match parse_lcsc_id_from_url(input) {
+        Some(id) => Ok(id),
+        None if input.contains("://") => Err(error::Error::ParseError(format!(
+            "No LCSC part number (C12345) found in URL '{}'",
+            input
+        ))),
+        // Not recognisably an ID; let the API decide.
+        None => Ok(input.trim().to_string()),
+    }
+}
+
+/// Imports several components into the same library, one after another.
+///
+/// A failure on one part doesn't stop the rest; each ID is returned alongside its own
+/// result, in input order.
+pub async fn import_components(
+    lcsc_ids: &[&str],
+    output_dir: &Path,
+    options: &ImportOptions,
+) -> Vec<(String, Result<ImportSummary>)> {
+    import_components_with_progress(lcsc_ids, output_dir, options, &progress::NoProgress).await
+}
+
+/// Same as [`import_components`], reporting each step to `progress`.
+///
+/// # Example
+///
+/// ```no_run
+/// use easyeda2kicad_rs::{ImportOptions, import_components_with_progress, progress::ProgressEvent};
+/// use std::path::Path;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let report = |event: ProgressEvent| {
+///         if let ProgressEvent::PartFinished { lcsc_id, success, .. } = event {
+///             println!("{}: {}", lcsc_id, if success { "ok" } else { "failed" });
+///         }
+///     };
+///     let options = ImportOptions::default();
+///     import_components_with_progress(&["C2040"], Path::new("kicad_lib"), &options, &report)
+///         .await;
+/// }
+/// ```
+pub async fn import_components_with_progress(
+    lcsc_ids: &[&str],
+    output_dir: &Path,
+    options: &ImportOptions,
+    progress: &dyn progress::ProgressSink,
+) -> Vec<(String, Result<ImportSummary>)> {
+    use progress::ProgressEvent;
+
+    progress.event(ProgressEvent::BatchStarted {
+        total: lcsc_ids.len(),
+    });
+    let api = api_client(options);
+    let mut results = Vec::with_capacity(lcsc_ids.len());
+    for (index, &lcsc_id) in lcsc_ids.iter().enumerate() {
+        progress.event(ProgressEvent::PartStarted { index, lcsc_id });
+        let on_download = |downloaded, total| {
+            progress.event(ProgressEvent::ModelDownload {
+                index,
+                lcsc_id,
+                downloaded,
+                total,
+            })
+        };
+        let result = import_with_api(&api, lcsc_id, output_dir, options, &on_download).await;
+        progress.event(ProgressEvent::PartFinished {
+            index,
+            lcsc_id,
+            success: result.is_ok(),
+        });
+        results.push((lcsc_id.to_string(), result));
+    }
+    let succeeded = results.iter().filter(|(_, r)| r.is_ok()).count();
+    progress.event(ProgressEvent::BatchFinished {
+        succeeded,
+        failed: results.len() - succeeded,
+    });
+    results
+}
+
+/// The outcome of [`import_from_bom`].
+#[derive(Debug)]
+pub struct BomImport {
+    /// Each unique LCSC ID from the BOM with its import result, in BOM order.
+    pub results: Vec<(String, Result<ImportSummary>)>,
+    /// 1-based line numbers of BOM rows that had no LCSC ID.
+    pub rows_without_id: Vec<usize>,
+}
+
+/// Imports every part listed in a CSV BOM (e.g. a JLCPCB assembly BOM).
+///
+/// `lcsc_column` names the column holding the LCSC IDs; with `None` the usual
+/// headers such as "LCSC Part #" or "Supplier Part" are tried. IDs are deduplicated
+/// before importing. Fails only if the BOM can't be read or has no LCSC column.
+pub async fn import_from_bom(
+    path: &Path,
+    lcsc_column: Option<&str>,
+    output_dir: &Path,
+) -> Result<BomImport> {
+    import_from_bom_with_options(path, lcsc_column, output_dir, &ImportOptions::default()).await
+}
+
+/// Same as [`import_from_bom`], with explicit import options.
+pub async fn import_from_bom_with_options(
+    path: &Path,
+    lcsc_column: Option<&str>,
+    output_dir: &Path,
+    options: &ImportOptions,
+) -> Result<BomImport> {
+    let content = std::fs::read_to_string(path)?;
+    let bom = bom::parse_bom(&content, lcsc_column)?;
+    for line in &bom.rows_without_id {
+        println!("Warning: BOM line {} has no LCSC ID", line);
+    }
+
+    let ids: Vec<&str> = bom.lcsc_ids.iter().map(String::as_str).collect();
+    Ok(BomImport {
+        results: import_components(&ids, output_dir, options).await,
+        rows_without_id: bom.rows_without_id,
+    })
+}
+
+/// The outcome of [`import_from_manifest`].
+#[derive(Debug)]
+pub struct ManifestImport {
+    /// Each part of the manifest with its import result, in manifest order.
+    pub results: Vec<(String, Result<ImportSummary>)>,
+    /// The lock file that was written.
+    pub lock_file: PathBuf,
+}
+
+/// Imports every part listed in a parts manifest (see [`manifest`] for the format).
+///
+/// Each part is imported with the manifest's defaults and its own overrides applied.
+/// Afterwards a lock file ([`manifest::LOCK_FILE_NAME`]) is written to `output_dir`,
+/// recording the EasyEDA API version and a hash of each successfully fetched part,
+/// so CI can tell when regenerating the library would change it. Fails only if the
+/// manifest can't be read or parsed, or the lock file can't be written.
+pub async fn import_from_manifest(path: &Path, output_dir: &Path) -> Result<ManifestImport> {
+    import_from_manifest_with_options(path, output_dir, &ImportOptions::default()).await
+}
+
+/// Same as [`import_from_manifest`], with import options the manifest builds on.
+pub async fn import_from_manifest_with_options(
+    path: &Path,
+    output_dir: &Path,
+    options: &ImportOptions,
+) -> Result<ManifestImport> {
+    let manifest = manifest::parse_manifest(&std::fs::read_to_string(path)?)?;
+
+    let api = api_client(options);
+    let mut results = Vec::with_capacity(manifest.parts.len());
+    for part in &manifest.parts {
+        let mut part_options = options.clone();
+        manifest.defaults.apply(&mut part_options)?;
+        part.overrides.apply(&mut part_options)?;
+        let result =
+            import_with_api(&api, &part.lcsc_id, output_dir, &part_options, &|_, _| {}).await;
+        results.push((part.lcsc_id.clone(), result));
+    }
+
+    let locked: Vec<(String, String)> = results
+        .iter()
+        .filter_map(|(id, result)| {
+            let summary = result.as_ref().ok()?;
+            Some((id.clone(), summary.source_sha256.clone()))
+        })
+        .collect();
+    std::fs::create_dir_all(output_dir)?;
+    let lock_file = output_dir.join(manifest::LOCK_FILE_NAME);
+    std::fs::write(
+        &lock_file,
+        manifest::lock_file_contents(api::API_VERSION, &locked),
+    )?;
+    Ok(ManifestImport { results, lock_file })
 }
 
 /// Fetches and converts a component from EasyEDA to KiCad data structures.
@@ -140,10 +732,13 @@ pub async fn import_component(lcsc_id: &str, output_dir: &Path) -> Result<()> {
 ///
 /// # Returns
 ///
-/// * `Result<(KiSymbol, KiFootprint, Option<Ki3dModel>)>` -
+/// * `Result<(KiSymbol, Option<KiFootprint>, Option<Ki3dModel>)>` -
 ///   - `KiSymbol`: The converted KiCad symbol
-///   - `KiFootprint`: The converted KiCad footprint
-///   - `Option<Ki3dModel>`: The converted 3D model, if available
+///   - `Option<KiFootprint>`: The converted KiCad footprint, `None` for parts that
+///     have none
+///   - `Option<Ki3dModel>`: The converted 3D model, if available. `None` (with a
+///     logged warning) if it couldn't be converted, and always `None` without the
+///     `3d` feature
 ///
 /// # Example
 ///
@@ -157,7 +752,12 @@ pub async fn import_component(lcsc_id: &str, output_dir: &Path) -> Result<()> {
 /// ```
 pub async fn import_component_data(
     lcsc_id: &str,
-) -> Result<(KiSymbol, KiFootprint, Option<kicad_models::Ki3dModel>)> {
+) -> Result<(
+    KiSymbol,
+    Option<KiFootprint>,
+    Option<kicad_models::Ki3dModel>,
+)> {
+    let lcsc_id = &resolve_lcsc_id(lcsc_id)?;
     println!("Fetching data for LCSC ID: {}", lcsc_id);
     let api = api::EasyedaApi::new();
 
@@ -166,29 +766,58 @@ pub async fn import_component_data(
     // --- SYMBOL ---
     let ee_symbol = importer::import_symbol(&cad_data)?;
     let ki_symbol = converter::convert_symbol(ee_symbol)?;
-    let ee_footprint = importer::import_footprint(&cad_data)?;
+    // Some parts genuinely have no footprint; the symbol is still worth having.
+    let ee_footprint = match importer::import_footprint(&cad_data) {
+        Ok(ee_footprint) => Some(ee_footprint),
+        Err(error::Error::MissingData(reason)) => {
+            println!("No footprint for this component ({}).", reason);
+            None
+        }
+        Err(e) => return Err(e),
+    };
 
     println!("Successfully generated symbol: {}", ki_symbol.name);
 
     // --- 3D MODEL ---
-    let ki_model = if let Some(mut ee_model_info) = importer::import_3d_model_info(&cad_data)? {
-        println!("Found 3D model: {}", ee_model_info.name);
-        let (raw_obj, step) = tokio::join!(
-            api.get_raw_3d_model_obj(&ee_model_info.uuid),
-            api.get_step_3d_model(&ee_model_info.uuid)
-        );
-        ee_model_info.raw_obj = raw_obj.ok();
-        ee_model_info.step = step.ok();
-        let model = converter::convert_3d_model(ee_model_info)?;
-        println!("Successfully generated 3D model: {}", model.name);
-        Some(model)
-    } else {
-        println!("No 3D model found for this component.");
-        None
+    // As in `import_footprint_with_api`, a model that can't be read or converted
+    // is skipped rather than failing the import.
+    #[cfg(not(feature = "3d"))]
+    let ki_model = None;
+    #[cfg(feature = "3d")]
+    let ki_model = match importer::import_3d_model_info(&cad_data) {
+        Ok(Some(mut ee_model_info)) => {
+            println!("Found 3D model: {}", ee_model_info.name);
+            let (raw_obj, step) = tokio::join!(
+                api.get_raw_3d_model_obj(&ee_model_info.uuid),
+                api.get_step_3d_model(&ee_model_info.uuid)
+            );
+            ee_model_info.raw_obj = raw_obj.ok();
+            ee_model_info.step = step.ok();
+            match converter::convert_3d_model(ee_model_info) {
+                Ok(model) => {
+                    println!("Successfully generated 3D model: {}", model.name);
+                    Some(model)
+                }
+                Err(e) => {
+                    log::warn!("3D model skipped: {}", e);
+                    None
+                }
+            }
+        }
+        Ok(None) => {
+            println!("No 3D model found for this component.");
+            None
+        }
+        Err(e) => {
+            log::warn!("3D model skipped: {}", e);
+            None
+        }
     };
 
     // --- FOOTPRINT ---
     // Pass the 3D model data to the footprint converter
-    let ki_footprint = converter::convert_footprint(ee_footprint, ki_model.clone())?;
+    let ki_footprint = ee_footprint
+        .map(|ee_footprint| converter::convert_footprint(ee_footprint, ki_model.clone()))
+        .transpose()?;
     Ok((ki_symbol, ki_footprint, ki_model))
 }
@@ -0,0 +1,198 @@
+// src/main.rs
+
+use easyeda2kicad_rs::{
+    ImportOptions, import_components, import_from_bom_with_options, kicad_models::KiCadFormat,
+};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: easyeda2kicad <LCSC_ID>... [options]
+       easyeda2kicad --bom <file.csv> [options]
+       easyeda2kicad --check
+
+Options:
+  -o, --output <dir>          Library output directory (default: easyeda2kicad)
+      --lib-name <name>       Symbol library name (default: lib)
+      --kicad-version <5-8>   KiCad release to target (default: 6)
+      --no-3d                 Skip 3D models
+      --save-raw              Also save EasyEDA's data as raw/<LCSC_ID>.json
+      --strict                Fail on shapes that can't be converted
+      --save-nets             Also save footprint pad nets as nets/<footprint>.tsv
+      --max-downloads <n>     3D model downloads to run at once (default: 4)
+      --bom <file>            Import every LCSC ID listed in a CSV BOM
+      --bom-column <name>     BOM column holding the LCSC IDs
+                              (default: auto-detect, e.g. \"LCSC Part #\")
+      --check                 Check that the EasyEDA API is reachable and exit
+  -h, --help                  Show this help";
+
+/// Parsed command line arguments.
+#[derive(Debug)]
+struct Args {
+    lcsc_ids: Vec<String>,
+    bom: Option<PathBuf>,
+    bom_column: Option<String>,
+    output: PathBuf,
+    options: ImportOptions,
+}
+
+/// What the command line asked for.
+enum Command {
+    Import(Box<Args>),
+    Check,
+    Help,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut lcsc_ids = Vec::new();
+    let mut bom = None;
+    let mut bom_column = None;
+    let mut output = PathBuf::from("easyeda2kicad");
+    let mut options = ImportOptions::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Accept both `--flag value` and `--flag=value`.
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| -> Result<String, String> {
+            match inline_value {
+                Some(v) => Ok(v.to_string()),
+                None => args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {}", name)),
+            }
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--check" => return Ok(Command::Check),
+            "-o" | "--output" => output = PathBuf::from(value(&flag)?),
+            "--lib-name" => options.lib_name = Some(value(&flag)?),
+            "--kicad-version" => options.format = value(&flag)?.parse::<KiCadFormat>()?,
+            "--no-3d" => options.skip_3d = true,
+            "--save-raw" => options.save_raw = true,
+            "--strict" => options.convert.strict = true,
+            "--save-nets" => options.save_netlist = true,
+            "--max-downloads" => {
+                let value = value(&flag)?;
+                let max = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&max| max > 0)
+                    .ok_or_else(|| format!("invalid value '{}' for --max-downloads", value))?;
+                options.max_model_downloads = Some(max);
+            }
+            "--bom" => bom = Some(PathBuf::from(value(&flag)?)),
+            "--bom-column" => bom_column = Some(value(&flag)?),
+            _ if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+            _ => lcsc_ids.push(arg.trim().to_uppercase()),
+        }
+    }
+
+    if lcsc_ids.is_empty() && bom.is_none() {
+        return Err("no LCSC IDs or BOM given".to_string());
+    }
+    Ok(Command::Import(Box::new(Args {
+        lcsc_ids,
+        bom,
+        bom_column,
+        output,
+        options,
+    })))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Command::Import(args)) => args,
+        Ok(Command::Check) => {
+            let report = easyeda2kicad_rs::api::EasyedaApi::new()
+                .health_check()
+                .await;
+            println!("{}", report);
+            return if report.is_healthy() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            };
+        }
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let ids: Vec<&str> = args.lcsc_ids.iter().map(String::as_str).collect();
+    let mut results = import_components(&ids, &args.output, &args.options).await;
+
+    let mut rows_without_id = Vec::new();
+    if let Some(bom) = &args.bom {
+        match import_from_bom_with_options(
+            bom,
+            args.bom_column.as_deref(),
+            &args.output,
+            &args.options,
+        )
+        .await
+        {
+            Ok(import) => {
+                results.extend(import.results);
+                rows_without_id = import.rows_without_id;
+            }
+            Err(e) => {
+                eprintln!("error: could not read BOM {:?}: {}", bom, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    println!("\nSummary:");
+    let mut failed = 0;
+    for (lcsc_id, result) in &results {
+        match result {
+            Ok(summary) => println!(
+                "  {}{}: symbol '{}', footprint '{}', 3D model {} ({} warning(s))",
+                lcsc_id,
+                if summary.is_extended {
+                    " [Extended]"
+                } else {
+                    ""
+                },
+                summary.symbol_name,
+                summary.footprint_name.as_deref().unwrap_or("none"),
+                summary
+                    .model_name
+                    .as_deref()
+                    .map_or("none".to_string(), |m| format!("'{}'", m)),
+                summary.warnings.len()
+            ),
+            Err(e) => {
+                failed += 1;
+                println!("  {}: FAILED: {}", lcsc_id, e);
+            }
+        }
+    }
+    println!(
+        "{} of {} component(s) imported into {:?}",
+        results.len() - failed,
+        results.len(),
+        args.output
+    );
+    if !rows_without_id.is_empty() {
+        let lines: Vec<String> = rows_without_id.iter().map(|l| l.to_string()).collect();
+        println!("BOM lines without an LCSC ID: {}", lines.join(", "));
+    }
+
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
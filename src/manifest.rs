@@ -0,0 +1,157 @@
+// src/manifest.rs
+
+//! Parts manifests (`parts.toml`) listing the components of a library, and the lock
+//! files recording what was fetched for them.
+//!
+//! A manifest is a small TOML file: optional top-level defaults, then one `[[part]]`
+//! table per component. Settings are [`ImportOptions`] fields.
+//!
+//! ```toml
+//! lib = "my_parts"        # symbol library for every part
+//! skip_3d = false
+//! format = "7"
+//!
+//! [[part]]
+//! lcsc = "C2040"
+//!
+//! [[part]]
+//! lcsc = "C8952"
+//! lib = "connectors"      # per-part overrides
+//! mirror_to_bottom = true
+//! convert = { fab_style = "ipc" }
+//! ```
+
+use crate::ImportOptions;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// File name of the lock file written next to the generated library.
+pub const LOCK_FILE_NAME: &str = "easyeda2kicad.lock";
+
+/// Import settings a manifest sets, for all parts or for a single one: any
+/// [`ImportOptions`] field, by name (`lib` for `lib_name`), with nested tables for
+/// nested options such as `convert`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct PartOverrides(pub toml::Table);
+
+impl PartOverrides {
+    /// Applies the settings that are present to `options`, leaving the others as
+    /// they are. Unknown settings and values of the wrong type are errors.
+    pub fn apply(&self, options: &mut ImportOptions) -> Result<()> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let mut merged = serde_json::to_value(&*options)?;
+        merge(&mut merged, serde_json::to_value(&self.0)?);
+        *options = serde_json::from_value(merged).map_err(|e| Error::ParseError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Overlays `overlay` onto `base`, table by table.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// One `[[part]]` entry of a manifest.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ManifestPart {
+    #[serde(rename = "lcsc")]
+    pub lcsc_id: String,
+    #[serde(flatten)]
+    pub overrides: PartOverrides,
+}
+
+/// A parsed parts manifest.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Manifest {
+    /// Settings from the top of the file, applied to every part.
+    #[serde(flatten)]
+    pub defaults: PartOverrides,
+    /// The parts, in file order.
+    #[serde(default, rename = "part")]
+    pub parts: Vec<ManifestPart>,
+}
+
+/// Parses manifest content.
+///
+/// Every setting is checked against [`ImportOptions`], so a typo can't silently
+/// change the generated library.
+pub fn parse_manifest(content: &str) -> Result<Manifest> {
+    let mut manifest: Manifest =
+        toml::from_str(content).map_err(|e| Error::ParseError(format!("Manifest: {}", e)))?;
+    if manifest.defaults.0.contains_key("lcsc") {
+        return Err(Error::ParseError(
+            "Manifest: 'lcsc' belongs in a [[part]] table".to_string(),
+        ));
+    }
+    manifest
+        .defaults
+        .apply(&mut ImportOptions::default())
+        .map_err(|e| in_context(e, "defaults"))?;
+
+    for part in &mut manifest.parts {
+        part.lcsc_id = part.lcsc_id.trim().to_uppercase();
+    }
+    for (i, part) in manifest.parts.iter().enumerate() {
+        if manifest.parts[..i]
+            .iter()
+            .any(|p| p.lcsc_id == part.lcsc_id)
+        {
+            return Err(Error::ParseError(format!(
+                "Manifest: {} is listed twice",
+                part.lcsc_id
+            )));
+        }
+        part.overrides
+            .apply(&mut ImportOptions::default())
+            .map_err(|e| in_context(e, &format!("part {}", part.lcsc_id)))?;
+    }
+    Ok(manifest)
+}
+
+/// Prefixes a settings error with the manifest section it came from.
+fn in_context(e: Error, section: &str) -> Error {
+    match e {
+        Error::ParseError(msg) => Error::ParseError(format!("Manifest {}: {}", section, msg)),
+        other => other,
+    }
+}
+
+/// A lock file: the API version and the hash of each part's component data.
+#[derive(Serialize)]
+struct LockFile<'a> {
+    api_version: &'a str,
+    part: Vec<LockedPart<'a>>,
+}
+
+#[derive(Serialize)]
+struct LockedPart<'a> {
+    lcsc: &'a str,
+    sha256: &'a str,
+}
+
+/// Renders a lock file recording the API version and the SHA-256 of each part's
+/// component data (see [`crate::ImportSummary::source_sha256`]).
+pub fn lock_file_contents(api_version: &str, parts: &[(String, String)]) -> String {
+    let lock = LockFile {
+        api_version,
+        part: parts
+            .iter()
+            .map(|(lcsc, sha256)| LockedPart { lcsc, sha256 })
+            .collect(),
+    };
+    format!(
+        "# Written by easyeda2kicad-rs when importing a parts manifest. Do not edit.\n{}",
+        toml::to_string(&lock).expect("lock files only hold strings")
+    )
+}
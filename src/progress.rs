@@ -0,0 +1,54 @@
+// src/progress.rs
+
+//! Progress reporting for batch imports.
+//!
+//! Pass a [`ProgressSink`] to [`crate::import_components_with_progress`] to follow a
+//! batch as it runs, e.g. to drive a progress bar. Any `Fn(ProgressEvent)` closure is
+//! a sink.
+
+/// Something that happened during a batch import.
+///
+/// `index` is the part's 0-based position in the batch, so events can be matched to
+/// rows of a display.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent<'a> {
+    /// A batch of `total` parts is starting.
+    BatchStarted { total: usize },
+    /// A part is being fetched and converted.
+    PartStarted { index: usize, lcsc_id: &'a str },
+    /// More of a part's STEP model has been downloaded. `total` is the file size, if
+    /// the server sent one.
+    ModelDownload {
+        index: usize,
+        lcsc_id: &'a str,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    /// A part is done; `success` is false if it failed to import.
+    PartFinished {
+        index: usize,
+        lcsc_id: &'a str,
+        success: bool,
+    },
+    /// Every part has been attempted.
+    BatchFinished { succeeded: usize, failed: usize },
+}
+
+/// Receives [`ProgressEvent`]s. Events arrive in order, from the importing task.
+pub trait ProgressSink: Send + Sync {
+    fn event(&self, event: ProgressEvent<'_>);
+}
+
+impl<F: Fn(ProgressEvent<'_>) + Send + Sync> ProgressSink for F {
+    fn event(&self, event: ProgressEvent<'_>) {
+        self(event)
+    }
+}
+
+/// A sink that ignores every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn event(&self, _event: ProgressEvent<'_>) {}
+}
@@ -0,0 +1,327 @@
+// src/svg_path.rs
+
+//! Minimal SVG path parsing for the path strings EasyEDA embeds in pins and arcs.
+
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+/// One drawing command of a path, in absolute coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PathSegment {
+    /// A straight line. Bézier curves are reduced to the chord between their ends.
+    Line { from: (f32, f32), to: (f32, f32) },
+    /// An elliptical arc (`A`), with the rotation in degrees.
+    Arc {
+        from: (f32, f32),
+        to: (f32, f32),
+        radii: (f32, f32),
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+    },
+}
+
+impl PathSegment {
+    pub(crate) fn from(&self) -> (f32, f32) {
+        match *self {
+            PathSegment::Line { from, .. } | PathSegment::Arc { from, .. } => from,
+        }
+    }
+
+    pub(crate) fn to(&self) -> (f32, f32) {
+        match *self {
+            PathSegment::Line { to, .. } | PathSegment::Arc { to, .. } => to,
+        }
+    }
+}
+
+/// Parses a path into absolute segments.
+///
+/// Parsing is forgiving: it stops at the first unknown command or incomplete
+/// argument list and returns whatever was read up to that point.
+pub(crate) fn path_segments(path: &str) -> Vec<PathSegment> {
+    let mut segments = Vec::new();
+    let mut current = (0.0f32, 0.0f32);
+    let mut subpath_start = current;
+    let mut command = ' ';
+    let mut args: Vec<f32> = Vec::new();
+
+    let mut tokens: VecDeque<PathToken> = path_tokens(path).into();
+    while let Some(token) = tokens.pop_front() {
+        match token {
+            PathToken::Command(c) => {
+                if !args.is_empty() {
+                    break;
+                }
+                if matches!(c, 'Z' | 'z') {
+                    if current != subpath_start {
+                        segments.push(PathSegment::Line {
+                            from: current,
+                            to: subpath_start,
+                        });
+                    }
+                    current = subpath_start;
+                }
+                command = c;
+            }
+            PathToken::Number(value, raw) => {
+                // Arc flags are single characters and may be packed without
+                // separators ("A 5 5 0 015 5"); split them off before reading the rest.
+                let is_flag = command.eq_ignore_ascii_case(&'A') && matches!(args.len(), 3 | 4);
+                if is_flag && raw.len() > 1 && raw.starts_with(['0', '1']) && !raw.contains('.') {
+                    let (flag, rest) = raw.split_at(1);
+                    if let (Ok(flag), Ok(rest_value)) = (flag.parse(), rest.parse()) {
+                        tokens.push_front(PathToken::Number(rest_value, rest.to_string()));
+                        tokens.push_front(PathToken::Number(flag, flag.to_string()));
+                        continue;
+                    }
+                }
+                args.push(value);
+                let needed = match command.to_ascii_uppercase() {
+                    'H' | 'V' => 1,
+                    'M' | 'L' | 'T' => 2,
+                    'S' | 'Q' => 4,
+                    'C' => 6,
+                    'A' => 7,
+                    _ => break,
+                };
+                if args.len() < needed {
+                    continue;
+                }
+
+                let base = if command.is_ascii_lowercase() {
+                    current
+                } else {
+                    (0.0, 0.0)
+                };
+                // Every command but H/V ends at its last coordinate pair.
+                let n = args.len();
+                let to = match command.to_ascii_uppercase() {
+                    'H' => (base.0 + args[0], current.1),
+                    'V' => (current.0, base.1 + args[0]),
+                    _ => (base.0 + args[n - 2], base.1 + args[n - 1]),
+                };
+                match command.to_ascii_uppercase() {
+                    'M' => subpath_start = to,
+                    'A' => segments.push(PathSegment::Arc {
+                        from: current,
+                        to,
+                        radii: (args[0].abs(), args[1].abs()),
+                        x_rotation: args[2],
+                        large_arc: args[3] != 0.0,
+                        sweep: args[4] != 0.0,
+                    }),
+                    _ => segments.push(PathSegment::Line { from: current, to }),
+                }
+                current = to;
+                args.clear();
+                // Extra pairs after a moveto are implicit linetos.
+                command = match command {
+                    'M' => 'L',
+                    'm' => 'l',
+                    c => c,
+                };
+            }
+        }
+    }
+    segments
+}
+
+/// Returns the first and last points drawn by a path.
+pub(crate) fn path_endpoints(path: &str) -> Option<((f32, f32), (f32, f32))> {
+    let segments = path_segments(path);
+    Some((segments.first()?.from(), segments.last()?.to()))
+}
+
+/// Parses an SVG `transform` attribute (e.g. `scale(1) translate(10, 0) rotate(90)`)
+/// into one matrix `[a, b, c, d, e, f]`, mapping `(x, y)` to
+/// `(a*x + c*y + e, b*x + d*y + f)`.
+///
+/// Unknown or malformed parts make the whole transform unreadable (`None`).
+pub(crate) fn parse_transform(transform: &str) -> Option<[f32; 6]> {
+    let mut matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+    let mut rest = transform.trim();
+    while !rest.is_empty() {
+        let (name, after) = rest.split_once('(')?;
+        let (args, after) = after.split_once(')')?;
+        let args: Vec<f32> = args
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().ok())
+            .collect::<Option<_>>()?;
+        let next = match (name.trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => [a, b, c, d, e, f],
+            ("translate", &[tx]) => [1.0, 0.0, 0.0, 1.0, tx, 0.0],
+            ("translate", &[tx, ty]) => [1.0, 0.0, 0.0, 1.0, tx, ty],
+            ("scale", &[s]) => [s, 0.0, 0.0, s, 0.0, 0.0],
+            ("scale", &[sx, sy]) => [sx, 0.0, 0.0, sy, 0.0, 0.0],
+            ("rotate", &[angle, ref center @ ..]) if matches!(center.len(), 0 | 2) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let (cx, cy) = center
+                    .first()
+                    .zip(center.get(1))
+                    .map_or((0.0, 0.0), |(x, y)| (*x, *y));
+                // Rotation about (cx, cy): translate, rotate, translate back.
+                [
+                    cos,
+                    sin,
+                    -sin,
+                    cos,
+                    cx - cos * cx + sin * cy,
+                    cy - sin * cx - cos * cy,
+                ]
+            }
+            _ => return None,
+        };
+        let [a, b, c, d, e, f] = matrix;
+        let [na, nb, nc, nd, ne, nf] = next;
+        matrix = [
+            a * na + c * nb,
+            b * na + d * nb,
+            a * nc + c * nd,
+            b * nc + d * nd,
+            a * ne + c * nf + e,
+            b * ne + d * nf + f,
+        ];
+        rest = after.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Some(matrix)
+}
+
+/// The center parameterization of an elliptical arc.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ArcGeometry {
+    pub center: (f32, f32),
+    pub radii: (f32, f32),
+    /// Ellipse rotation, in radians.
+    pub x_rotation: f32,
+    /// Angle of the start point, in radians.
+    pub start_angle: f32,
+    /// Signed sweep in radians; positive runs towards +Y from +X.
+    pub sweep_angle: f32,
+}
+
+impl ArcGeometry {
+    /// Converts an SVG endpoint arc to center form (SVG 1.1, appendix F.6.5).
+    ///
+    /// Radii too small to span the endpoints are scaled up, as SVG renderers do.
+    /// Returns `None` for arcs that are really straight lines or points (zero
+    /// radius, coincident endpoints) and for non-finite input.
+    pub(crate) fn from_endpoints(
+        from: (f32, f32),
+        to: (f32, f32),
+        radii: (f32, f32),
+        x_rotation_deg: f32,
+        large_arc: bool,
+        sweep: bool,
+    ) -> Option<Self> {
+        let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+        if rx == 0.0 || ry == 0.0 || from == to {
+            return None;
+        }
+        let phi = x_rotation_deg.to_radians();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+
+        let dx = (from.0 - to.0) / 2.0;
+        let dy = (from.1 - to.1) / 2.0;
+        let x1 = cos_phi * dx + sin_phi * dy;
+        let y1 = -sin_phi * dx + cos_phi * dy;
+
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = (num / den).max(0.0).sqrt();
+        if large_arc == sweep {
+            coef = -coef;
+        }
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+
+        let center = (
+            cos_phi * cx1 - sin_phi * cy1 + (from.0 + to.0) / 2.0,
+            sin_phi * cx1 + cos_phi * cy1 + (from.1 + to.1) / 2.0,
+        );
+
+        let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let end_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+        let mut sweep_angle = (end_angle - start_angle).rem_euclid(TAU);
+        if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= TAU;
+        }
+
+        let geometry = ArcGeometry {
+            center,
+            radii: (rx, ry),
+            x_rotation: phi,
+            start_angle,
+            sweep_angle,
+        };
+        let finite = [center.0, center.1, rx, ry, start_angle, sweep_angle]
+            .iter()
+            .all(|v| v.is_finite());
+        finite.then_some(geometry)
+    }
+
+    /// Returns the point at fraction `t` (0 = start, 1 = end) along the arc.
+    pub(crate) fn point_at(&self, t: f32) -> (f32, f32) {
+        let angle = self.start_angle + self.sweep_angle * t;
+        let (sin_a, cos_a) = angle.sin_cos();
+        let (sin_phi, cos_phi) = self.x_rotation.sin_cos();
+        let (rx, ry) = self.radii;
+        (
+            self.center.0 + rx * cos_phi * cos_a - ry * sin_phi * sin_a,
+            self.center.1 + rx * sin_phi * cos_a + ry * cos_phi * sin_a,
+        )
+    }
+
+    /// Whether the arc is (close enough to) circular for KiCad's arc primitives.
+    pub(crate) fn is_circular(&self) -> bool {
+        (self.radii.0 - self.radii.1).abs() <= self.radii.0.max(self.radii.1) * 1e-3
+    }
+}
+
+enum PathToken {
+    Command(char),
+    /// The parsed value and its source text.
+    Number(f32, String),
+}
+
+/// Splits an SVG path into commands and numbers, accepting both `M 1 2 h 3` and the
+/// compact `M1,2h-3` forms.
+fn path_tokens(path: &str) -> Vec<PathToken> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    let flush = |number: &mut String, tokens: &mut Vec<PathToken>| {
+        if let Ok(value) = number.parse() {
+            tokens.push(PathToken::Number(value, std::mem::take(number)));
+        }
+        number.clear();
+    };
+
+    for c in path.chars() {
+        match c {
+            'e' | 'E' if !number.is_empty() => number.push(c),
+            c if c.is_ascii_alphabetic() => {
+                flush(&mut number, &mut tokens);
+                tokens.push(PathToken::Command(c));
+            }
+            '-' | '+' if !number.is_empty() && !number.ends_with(['e', 'E']) => {
+                flush(&mut number, &mut tokens);
+                number.push(c);
+            }
+            '.' if number.contains('.') => {
+                flush(&mut number, &mut tokens);
+                number.push(c);
+            }
+            c if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => number.push(c),
+            _ => flush(&mut number, &mut tokens),
+        }
+    }
+    flush(&mut number, &mut tokens);
+    tokens
+}
@@ -0,0 +1,355 @@
+use easyeda2kicad_rs::{
+    ImportOptions,
+    api::{
+        EasyedaApi, check_component_revision, check_obj_model, check_step_model,
+        component_revision, parse_component_response,
+    },
+    error::Error,
+    import_components_with_progress, parse_lcsc_id_from_url,
+    progress::ProgressEvent,
+};
+use serde_json::json;
+use std::{fs, path::Path, sync::Mutex};
+
+#[test]
+fn test_html_response_is_reported_as_rejected() {
+    let html = "<!DOCTYPE html>\n<html><head><title>Verify you are human</title></head></html>";
+    assert!(matches!(
+        parse_component_response(html),
+        Err(Error::ApiRejected(_))
+    ));
+    assert!(matches!(
+        parse_component_response("Too Many Requests"),
+        Err(Error::ApiRejected(_))
+    ));
+}
+
+#[test]
+fn test_json_response_is_parsed() {
+    let body = r#"{"success":true,"code":0,"result":{"title":"R1"}}"#;
+    assert_eq!(parse_component_response(body).unwrap()["title"], "R1");
+
+    let body = r#"{"success":false,"code":404,"message":"not found"}"#;
+    assert!(matches!(
+        parse_component_response(body),
+        Err(Error::MissingData(_))
+    ));
+}
+
+#[test]
+fn test_parse_lcsc_id_from_url() {
+    let cases = [
+        ("C12345", Some("C12345")),
+        (" c2040 ", Some("C2040")),
+        (
+            "https://www.lcsc.com/product-detail/Chip-Resistor-Surface-Mount_UNI-ROYAL-0402WGF1002TCE_C25744.html",
+            Some("C25744"),
+        ),
+        (
+            "https://jlcpcb.com/partdetail/Yageo-RC0402FR_0710KL/C25744",
+            Some("C25744"),
+        ),
+        (
+            "https://easyeda.com/components/8c6f5f3e2b1d4e6a?lcsc=C8952",
+            Some("C8952"),
+        ),
+        ("https://www.lcsc.com/products/Resistors_439.html", None),
+        ("ABC123", None),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            parse_lcsc_id_from_url(input).as_deref(),
+            expected,
+            "{}",
+            input
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_compressed_response_bodies() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let fixture =
+        |ext: &str| fs::read(format!("tests/fixtures/compressed_response.{}", ext)).unwrap();
+    let plain = String::from_utf8(fixture("json")).unwrap();
+    assert_eq!(
+        parse_component_response(&plain).unwrap()["title"],
+        "GZIP_TEST"
+    );
+
+    // Serves the fixture once per encoding, recording the Accept-Encoding asked for.
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = server.local_addr().unwrap().port();
+    let encodings = [("gzip", "json.gz"), ("deflate", "json.zlib")];
+    let bodies: Vec<_> = encodings
+        .iter()
+        .map(|&(encoding, ext)| (encoding, fixture(ext)))
+        .collect();
+    let server = std::thread::spawn(move || {
+        let mut accepted = Vec::new();
+        for ((encoding, body), stream) in bodies.into_iter().zip(server.incoming()) {
+            let mut stream = stream.unwrap();
+            let mut request = [0u8; 4096];
+            let n = stream.read(&mut request).unwrap();
+            let request = String::from_utf8_lossy(&request[..n]).to_ascii_lowercase();
+            accepted.extend(
+                request
+                    .lines()
+                    .find_map(|l| l.strip_prefix("accept-encoding:"))
+                    .map(|v| v.trim().to_string()),
+            );
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: {}\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n",
+                encoding,
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+        accepted
+    });
+
+    let api = EasyedaApi::new();
+    let url = format!("http://127.0.0.1:{}/components", port);
+    for (encoding, _) in encodings {
+        let report = api.health_check_url(&url).await;
+        assert!(report.is_healthy(), "{}: {}", encoding, report);
+    }
+    let accepted = server.join().unwrap();
+    assert_eq!(accepted.len(), encodings.len());
+    for accepted in accepted {
+        for encoding in ["gzip", "deflate", "br"] {
+            assert!(accepted.contains(encoding), "{}", accepted);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_batch_progress_events() {
+    // A URL without a part number fails before any request is made.
+    let events = Mutex::new(Vec::new());
+    let sink = |event: ProgressEvent| events.lock().unwrap().push(format!("{:?}", event));
+    let results = import_components_with_progress(
+        &["https://www.lcsc.com/no-part-here"],
+        &std::env::temp_dir().join("easyeda2kicad_rs_progress"),
+        &ImportOptions::default(),
+        &sink,
+    )
+    .await;
+    assert!(results[0].1.is_err());
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "BatchStarted { total: 1 }",
+            "PartStarted { index: 0, lcsc_id: \"https://www.lcsc.com/no-part-here\" }",
+            "PartFinished { index: 0, lcsc_id: \"https://www.lcsc.com/no-part-here\", success: false }",
+            "BatchFinished { succeeded: 0, failed: 1 }",
+        ]
+    );
+
+    let results = import_components_with_progress(
+        &[],
+        Path::new("unused"),
+        &ImportOptions::default(),
+        &|event: ProgressEvent| {
+            assert!(matches!(
+                event,
+                ProgressEvent::BatchStarted { total: 0 }
+                    | ProgressEvent::BatchFinished {
+                        succeeded: 0,
+                        failed: 0
+                    }
+            ))
+        },
+    )
+    .await;
+    assert!(results.is_empty());
+}
+
+#[test]
+fn test_session_cookie() {
+    assert!(EasyedaApi::with_cookie("session=abc123; theme=dark").is_ok());
+    // Header values can't span lines; a pasted newline must not reach the request.
+    assert!(matches!(
+        EasyedaApi::with_cookie("session=abc\r\nX-Injected: 1"),
+        Err(Error::ParseError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_step_endpoints_are_tried_in_order() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    // The first endpoint has nothing listening; the second serves the model.
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_port = closed.local_addr().unwrap().port();
+    drop(closed);
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = server.local_addr().unwrap().port();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = server.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let n = stream.read(&mut request).unwrap();
+        let request = String::from_utf8_lossy(&request[..n]).to_string();
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Length: 32\r\nConnection: close\r\n\r\n\
+                  ISO-10303-21;\nEND-ISO-10303-21;\n",
+            )
+            .unwrap();
+        request
+    });
+
+    let api = EasyedaApi::new().with_step_endpoints([
+        format!("http://127.0.0.1:{}/{{uuid}}", closed_port),
+        format!("http://127.0.0.1:{}/models/{{uuid}}", port),
+    ]);
+    let model = api.get_step_3d_model("abc123").await.unwrap();
+    assert_eq!(&model[..], b"ISO-10303-21;\nEND-ISO-10303-21;\n");
+    assert!(handle.join().unwrap().starts_with("GET /models/abc123 "));
+
+    let api = EasyedaApi::new()
+        .with_step_endpoints([format!("http://127.0.0.1:{}/{{uuid}}", closed_port)]);
+    match api.get_step_3d_model("abc123").await {
+        Err(Error::MissingData(msg)) => assert!(msg.contains("abc123"), "{}", msg),
+        other => panic!("expected MissingData, got {:?}", other.map(|b| b.len())),
+    }
+}
+
+#[test]
+fn test_downloaded_models_are_checked() {
+    assert!(check_obj_model("# comment\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").is_ok());
+    assert!(matches!(
+        check_obj_model("v 0 0 0\nv 1 0 0\nv 0 1"),
+        Err(Error::CorruptDownload(_))
+    ));
+    assert!(check_obj_model("<html>Not Found</html>").is_err());
+
+    let step = b"ISO-10303-21;\nHEADER;\nENDSEC;\nEND-ISO-10303-21;\n";
+    assert!(check_step_model(step, step).is_ok());
+    // Cut off mid-file: the header is fine but the end marker is missing.
+    assert!(matches!(
+        check_step_model(step, b"#12=CARTESIAN_POINT('',(0.,"),
+        Err(Error::CorruptDownload(_))
+    ));
+    assert!(check_step_model(b"{\"error\":\"not found\"}", step).is_err());
+}
+
+#[test]
+fn test_component_revision() {
+    let data = json!({ "uuid": "abc", "updateTime": 1700000000 });
+    assert_eq!(component_revision(&data).as_deref(), Some("1700000000"));
+    assert_eq!(
+        component_revision(&json!({ "updated_at": " 2023-11-14 " })).as_deref(),
+        Some("2023-11-14")
+    );
+    assert_eq!(component_revision(&json!({ "updateTime": "" })), None);
+
+    assert!(check_component_revision("C2040", &data, "1700000000").is_ok());
+    assert!(matches!(
+        check_component_revision("C2040", &data, "1600000000"),
+        Err(Error::RevisionMismatch(_))
+    ));
+    // Data without a stamp can't be confirmed to be the pinned revision.
+    assert!(matches!(
+        check_component_revision("C2040", &json!({}), "1700000000"),
+        Err(Error::RevisionMismatch(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_model_downloads_are_limited() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    // Serves three STEP downloads, recording how many were open at once.
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = server.local_addr().unwrap().port();
+    let active = Arc::new(AtomicUsize::new(0));
+    let most_active = Arc::new(AtomicUsize::new(0));
+    let (active_in_server, most_in_server) = (active.clone(), most_active.clone());
+    std::thread::spawn(move || {
+        for stream in server.incoming().take(3) {
+            let mut stream = stream.unwrap();
+            let (active, most_active) = (active_in_server.clone(), most_in_server.clone());
+            std::thread::spawn(move || {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                most_active.fetch_max(now, Ordering::SeqCst);
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                // Counted out before answering, so the client can't start the next
+                // download first.
+                active.fetch_sub(1, Ordering::SeqCst);
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 32\r\nConnection: close\r\n\r\n\
+                          ISO-10303-21;\nEND-ISO-10303-21;\n",
+                    )
+                    .unwrap();
+            });
+        }
+    });
+
+    let api = EasyedaApi::new()
+        .with_step_endpoints([format!("http://127.0.0.1:{}/{{uuid}}", port)])
+        .with_max_model_downloads(1);
+    let (a, b, c) = tokio::join!(
+        api.get_step_3d_model("a"),
+        api.get_step_3d_model("b"),
+        api.get_step_3d_model("c"),
+    );
+    assert!(a.is_ok() && b.is_ok() && c.is_ok());
+    assert_eq!(most_active.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = server.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let replies: [&[u8]; 2] = [
+            b"HTTP/1.1 200 OK\r\nContent-Length: 28\r\nConnection: close\r\n\r\n\
+              {\"success\":true,\"result\":{}}",
+            b"HTTP/1.1 429 Too Many Requests\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ];
+        for (stream, reply) in server.incoming().zip(replies) {
+            let mut stream = stream.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            stream.write_all(reply).unwrap();
+        }
+    });
+
+    let api = EasyedaApi::new();
+    let url = format!("http://127.0.0.1:{}/components?version={{version}}", port);
+    let report = api.health_check_url(&url).await;
+    assert!(report.is_healthy(), "{}", report);
+    assert_eq!(report.status, Some(200));
+    assert!(report.latency.is_some());
+    assert!(!report.url.contains("{version}"));
+
+    let report = api.health_check_url(&url).await;
+    assert!(report.reachable && !report.json_ok);
+    assert_eq!(report.status, Some(429));
+    assert!(report.error.is_some());
+
+    // A port that was just freed, so nothing is listening on it.
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_url = format!("http://{}/", closed.local_addr().unwrap());
+    drop(closed);
+    let report = api.health_check_url(&closed_url).await;
+    assert!(!report.reachable && !report.is_healthy());
+    assert_eq!(report.status, None);
+}
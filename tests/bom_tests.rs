@@ -0,0 +1,37 @@
+use easyeda2kicad_rs::{bom::parse_bom, error::Error};
+
+#[test]
+fn test_parse_jlcpcb_bom() {
+    let csv = "\u{feff}Comment,Designator,Footprint,LCSC Part #\r\n\
+               100nF,\"C1,C2,C3\",0402,C1525\r\n\
+               10k,R1,0402,c25744\r\n\
+               \"Header \"\"2x5\"\"\",J1,THT,\r\n\
+               \r\n\
+               100nF,C4,0402,C1525\r\n";
+    let bom = parse_bom(csv, None).unwrap();
+    assert_eq!(bom.lcsc_ids, ["C1525", "C25744"]);
+    assert_eq!(bom.rows_without_id, [4]);
+}
+
+#[test]
+fn test_parse_bom_columns() {
+    // Semicolon separated, alternative header name, quoted newline.
+    let csv = "Value;Supplier Part\n\"multi\nline\";C2040\nX;none\n";
+    let bom = parse_bom(csv, None).unwrap();
+    assert_eq!(bom.lcsc_ids, ["C2040"]);
+    assert_eq!(bom.rows_without_id, [4]);
+
+    // An explicit column wins over the auto-detected one.
+    let csv = "LCSC,Alt LCSC\nC1,C2\n";
+    assert_eq!(parse_bom(csv, Some("alt lcsc")).unwrap().lcsc_ids, ["C2"]);
+
+    // A separator inside a quoted header name doesn't decide the format.
+    let csv = "\"Value, Tol\";\"LCSC Part #\"\n\"10k, 1%\";C25744\n";
+    let bom = parse_bom(csv, None).unwrap();
+    assert_eq!(bom.lcsc_ids, ["C25744"]);
+
+    assert!(matches!(
+        parse_bom("Value,Footprint\n10k,0402\n", None),
+        Err(Error::MissingData(_))
+    ));
+}
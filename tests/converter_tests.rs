@@ -1,32 +1,178 @@
 use easyeda2kicad_rs::{
-    converter::convert_symbol,
-    easyeda_models::{EeSymbol, EeSymbolInfo, EeSymbolPin, EeSymbolRectangle},
+    ImportOptions,
+    converter::{
+        ConvertOptions, FabStyle, SymbolOrigin, convert_3d_model, convert_3d_model_with_options,
+        convert_footprint, convert_footprint_with_options, convert_symbol,
+        convert_symbol_with_options,
+    },
+    easyeda_models::{Ee3dModel, EeSymbol, EeSymbolInfo, EeSymbolPin, EeSymbolRectangle},
+    error::Error,
+    import_component_from_data,
+    importer::{import_3d_model_info, import_3d_model_infos, import_footprint, import_symbol},
+    kicad_models::{FpGraphic, FpGraphicType, KiPinType, Model3dFormat, PadThermal},
 };
-use std::{path::Path, time::Instant};
-use tokio;
+use glam::Vec3;
+use serde_json::{Value, json};
+use std::{fs, path::Path, time::Instant};
+
+/// Wraps raw EasyEDA footprint shape strings in the API response layout.
+fn footprint_data(shapes: &[&str]) -> Value {
+    json!({
+        "packageDetail": {
+            "title": "TEST_FP",
+            "dataStr": {
+                "head": { "x": "4000", "y": "3000" },
+                "shape": shapes,
+            }
+        }
+    })
+}
+
+/// Wraps raw EasyEDA symbol shape strings in the API response layout.
+fn symbol_data(shapes: &[&str]) -> Value {
+    json!({
+        "dataStr": {
+            "head": { "x": "400", "y": "300", "c_para": { "name": "TEST_SYM", "pre": "U?" } },
+            "shape": shapes,
+        }
+    })
+}
+
+/// Wraps raw OBJ text in an `Ee3dModel` ready for conversion.
+fn model_from_obj(obj: &str) -> Ee3dModel {
+    Ee3dModel {
+        name: "TEST_MODEL".to_string(),
+        uuid: "test".to_string(),
+        raw_obj: Some(obj.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Extracts the `point` and `coordIndex` arrays from generated VRML.
+fn parse_wrl(wrl: &str) -> (Vec<[f32; 3]>, Vec<Vec<usize>>) {
+    let section = |start: &str| {
+        let body = &wrl[wrl.find(start).unwrap() + start.len()..];
+        body[..body.find(']').unwrap()].to_string()
+    };
+    let numbers: Vec<f32> = section("point [")
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|t| t.parse().ok())
+        .collect();
+    let points = numbers.chunks(3).map(|c| [c[0], c[1], c[2]]).collect();
+    let faces = section("coordIndex [")
+        .split("-1")
+        .map(|f| {
+            f.split(',')
+                .filter_map(|t| t.trim().parse().ok())
+                .collect::<Vec<_>>()
+        })
+        .filter(|f| !f.is_empty())
+        .collect();
+    (points, faces)
+}
+
+/// Imports a component fixture from `tests/fixtures/components` into `output_dir`.
+async fn import_fixture(name: &str, output_dir: &Path) -> easyeda2kicad_rs::ImportSummary {
+    let raw = fs::read_to_string(format!("tests/fixtures/components/{}.json", name)).unwrap();
+    let cad_data: Value = serde_json::from_str(&raw).unwrap();
+    import_component_from_data(name, &cad_data, output_dir, &ImportOptions::default())
+        .await
+        .unwrap()
+}
+
+/// Returns the position and size of pad `number` in a generated footprint.
+fn pad_geometry(footprint: &str, number: &str) -> ((f32, f32), (f32, f32)) {
+    let line = footprint
+        .lines()
+        .find(|l| l.trim_start().starts_with(&format!("(pad {} ", number)))
+        .unwrap_or_else(|| panic!("no pad {} in\n{}", number, footprint));
+    let pair = |key: &str| -> (f32, f32) {
+        let rest = &line[line.find(key).unwrap() + key.len()..];
+        let mut values = rest.split([' ', ')']).filter_map(|v| v.parse().ok());
+        (values.next().unwrap(), values.next().unwrap())
+    };
+    (pair("(at "), pair("(size "))
+}
+
+/// Asserts two points are within 1 µm of each other.
+fn assert_near(actual: (f32, f32), expected: (f32, f32)) {
+    assert!(
+        (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
 
 #[tokio::test]
 async fn test_basic_component_import() {
-    // Test importing a simple component (C2040 - 0805 capacitor)
-    let test_lcsc_id = "C2040";
-    let result = easyeda2kicad_rs::import_component(test_lcsc_id, Path::new("test_output")).await;
-    assert!(
-        result.is_ok(),
-        "Failed to import basic component: {:?}",
-        result.err()
+    // A two-pad 0805 capacitor.
+    let dir = std::env::temp_dir().join("easyeda2kicad_rs_fixture_basic");
+    let _ = fs::remove_dir_all(&dir);
+    let summary = import_fixture("capacitor_0805", &dir).await;
+    assert_eq!(summary.symbol_name, "CAP_100nF_0805");
+    assert_eq!(summary.footprint_name.as_deref(), Some("C0805"));
+    assert!(!summary.is_extended);
+    assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+    // Offline imports skip the 3D model rather than failing it.
+    assert!(summary.model_name.is_none() && summary.model_error.is_none());
+
+    let footprint = fs::read_to_string(dir.join("footprints.pretty/C0805.kicad_mod")).unwrap();
+    assert_eq!(footprint.matches("(pad ").count(), 2);
+    let (at, size) = pad_geometry(&footprint, "1");
+    assert_near(at, (-0.95, 0.0));
+    assert_near(size, (1.0008, 1.2497));
+    assert_near(pad_geometry(&footprint, "2").0, (0.95, 0.0));
+
+    let symbols = fs::read_to_string(dir.join("symbols/lib.kicad_sym")).unwrap();
+    assert_eq!(
+        symbols.matches("(pin passive line").count(),
+        2,
+        "{}",
+        symbols
     );
+    assert!(symbols.contains("\"C100001\""), "{}", symbols);
 }
 
 #[tokio::test]
 async fn test_complex_component_import() {
-    // Test importing a more complex component (C324124 - STM32 MCU)
-    let test_lcsc_id = "C324124";
-    let result = easyeda2kicad_rs::import_component(test_lcsc_id, Path::new("test_output")).await;
-    assert!(
-        result.is_ok(),
-        "Failed to import complex component: {:?}",
-        result.err()
-    );
+    // An NE555 timer in SOIC-8, with power, input and output pins.
+    let dir = std::env::temp_dir().join("easyeda2kicad_rs_fixture_complex");
+    let _ = fs::remove_dir_all(&dir);
+    let summary = import_fixture("ne555_soic8", &dir).await;
+    assert_eq!(summary.symbol_name, "NE555");
+    assert!(summary.is_extended);
+
+    let name = summary.footprint_name.unwrap();
+    let footprint =
+        fs::read_to_string(dir.join(format!("footprints.pretty/{}.kicad_mod", name))).unwrap();
+    assert_eq!(footprint.matches("(pad ").count(), 8);
+    // Pin 1 top left, counting down the left side and back up the right.
+    assert_near(pad_geometry(&footprint, "1").0, (-2.7, -1.905));
+    assert_near(pad_geometry(&footprint, "4").0, (-2.7, 1.905));
+    assert_near(pad_geometry(&footprint, "5").0, (2.7, 1.905));
+    assert_near(pad_geometry(&footprint, "8").0, (2.7, -1.905));
+
+    let symbols = fs::read_to_string(dir.join("symbols/lib.kicad_sym")).unwrap();
+    let pin_type = |number: &str| {
+        let needle = format!("(number \"{}\"", number);
+        let line = symbols
+            .lines()
+            .position(|l| l.contains(&needle))
+            .unwrap_or_else(|| panic!("no pin {} in\n{}", number, symbols));
+        // The pin's header line comes before its name and number.
+        symbols
+            .lines()
+            .take(line)
+            .filter(|l| l.contains("(pin "))
+            .last()
+            .unwrap()
+            .to_string()
+    };
+    assert!(pin_type("1").contains("power_in"), "{}", pin_type("1"));
+    assert!(pin_type("2").contains("input"), "{}", pin_type("2"));
+    assert!(pin_type("3").contains("output"), "{}", pin_type("3"));
+    assert!(pin_type("8").contains("power_in"), "{}", pin_type("8"));
 }
 #[tokio::test]
 async fn test_symbol_conversion() {
@@ -39,6 +185,7 @@ async fn test_symbol_conversion() {
             datasheet: None,
             lcsc_id: Some("C1234".to_string()),
             is_extended: false,
+            ..Default::default()
         },
         bbox: (0.0, 0.0),
         pins: vec![
@@ -66,7 +213,9 @@ async fn test_symbol_conversion() {
             y: -1.0,
             width: 5.0,
             height: 2.0,
+            ..Default::default()
         }],
+        ..Default::default()
     };
 
     let result = convert_symbol(ee_symbol);
@@ -87,33 +236,2293 @@ async fn test_symbol_conversion() {
 
 #[tokio::test]
 async fn test_multiple_component_import() {
-    let lcsc_ids = vec!["C8952", "C2040", "C5659"];
+    let dir = std::env::temp_dir().join("easyeda2kicad_rs_fixture_multiple");
+    let _ = fs::remove_dir_all(&dir);
+    let fixtures = ["capacitor_0805", "ne555_soic8", "npn_sot23"];
     let start_time = Instant::now();
 
-    for lcsc_id in &lcsc_ids {
-        let result = easyeda2kicad_rs::import_component(lcsc_id, Path::new("test_output")).await;
+    let mut footprints = Vec::new();
+    for fixture in fixtures {
+        footprints.push(import_fixture(fixture, &dir).await.footprint_name.unwrap());
+    }
+
+    // All three symbols share one library; each footprint has its own file.
+    let symbols = fs::read_to_string(dir.join("symbols/lib.kicad_sym")).unwrap();
+    for name in ["CAP_100nF_0805", "NE555", "NPN_SOT23"] {
+        assert!(
+            symbols.contains(&format!("(symbol \"{}\"", name)),
+            "{}",
+            symbols
+        );
+    }
+    for name in &footprints {
         assert!(
-            result.is_ok(),
-            "Failed to import component {}: {:?}",
-            lcsc_id,
-            result.err()
+            dir.join(format!("footprints.pretty/{}.kicad_mod", name))
+                .exists()
         );
     }
+    let sot23 =
+        fs::read_to_string(dir.join(format!("footprints.pretty/{}.kicad_mod", footprints[2])))
+            .unwrap();
+    assert_eq!(sot23.matches("(pad ").count(), 3);
 
     println!(
         "Imported {} components in {:?}",
-        lcsc_ids.len(),
+        fixtures.len(),
         start_time.elapsed()
     );
 }
 
+#[tokio::test]
+async fn test_multi_unit_component_import() {
+    // A dual op-amp delivered as one drawing per amplifier in `subparts`.
+    let dir = std::env::temp_dir().join("easyeda2kicad_rs_fixture_multi_unit");
+    let _ = fs::remove_dir_all(&dir);
+    let summary = import_fixture("lm358_dual_opamp", &dir).await;
+    assert_eq!(summary.symbol_name, "LM358");
+    assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+
+    let symbols = fs::read_to_string(dir.join("symbols/lib.kicad_sym")).unwrap();
+    let unit = |n: usize| {
+        let start = symbols
+            .find(&format!("(symbol \"LM358_{}_1\"", n))
+            .unwrap_or_else(|| panic!("no unit {} in\n{}", n, symbols));
+        let end = symbols[start + 1..]
+            .find("(symbol \"")
+            .map_or(symbols.len(), |i| start + 1 + i);
+        &symbols[start..end]
+    };
+    for (n, numbers) in [(1, ["1", "2", "3"]), (2, ["5", "6", "7"])] {
+        let unit = unit(n);
+        assert_eq!(unit.matches("(pin ").count(), 5, "{}", unit);
+        assert_eq!(unit.matches("(rectangle ").count(), 1, "{}", unit);
+        for number in numbers.iter().chain(&["4", "8"]) {
+            assert!(
+                unit.contains(&format!("(number \"{}\"", number)),
+                "{}",
+                unit
+            );
+        }
+    }
+    assert!(!symbols.contains("LM358_3_1"));
+
+    let ee_symbol = import_symbol(
+        &serde_json::from_str::<Value>(
+            &fs::read_to_string("tests/fixtures/components/lm358_dual_opamp.json").unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let symbol = convert_symbol(ee_symbol).unwrap();
+    assert_eq!(symbol.units.len(), 1);
+    // Supply pins repeat in each unit but are one pin of the part.
+    assert_eq!(symbol.pin_map().len(), 8);
+}
+
 #[tokio::test]
 async fn test_invalid_component_import() {
-    // Test importing a non-existent component
-    let test_lcsc_id = "INVALID_ID";
-    let result = easyeda2kicad_rs::import_component(test_lcsc_id, Path::new("test_output")).await;
+    // Component data without a symbol can't be imported.
+    let dir = std::env::temp_dir().join("easyeda2kicad_rs_fixture_invalid");
+    let result =
+        import_component_from_data("INVALID_ID", &json!({}), &dir, &ImportOptions::default()).await;
     assert!(
         result.is_err(),
         "Expected error when importing invalid component"
     );
 }
+
+#[test]
+fn test_footprint_not_mirrored_sot23() {
+    // SOT-23: pins 1 and 2 along the bottom edge, pin 3 alone at the top.
+    // EasyEDA's PCB canvas is Y-down, so pin 3 has the smaller Y value.
+    let data = footprint_data(&[
+        "PAD~RECT~3996.26~3004.33~3.5~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3996.26,3004.33",
+        "PAD~RECT~4003.74~3004.33~3.5~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4003.74,3004.33",
+        "PAD~RECT~4000~2995.67~3.5~4~1~~3~0~~0~gge3~0~~Y~0~0~0.4~4000,2995.67",
+    ]);
+    let ee_footprint = import_footprint(&data).unwrap();
+    let footprint = convert_footprint(ee_footprint, None).unwrap();
+
+    let pad = |n: &str| footprint.pads.iter().find(|p| p.number == n).unwrap();
+    // KiCad footprints are also Y-down: pin 3 must stay above pins 1 and 2.
+    assert!(pad("3").pos.1 < pad("1").pos.1);
+    assert!(pad("3").pos.1 < pad("2").pos.1);
+    // ...and pin 1 must stay on the left.
+    assert!(pad("1").pos.0 < pad("2").pos.0);
+}
+
+#[test]
+fn test_pre_swapped_pad_size_follows_outline() {
+    // Vertical SOT-223: the 3.3 x 2.0 mm tab stands upright, 2.0 mm wide in X and
+    // 3.3 mm tall in Y, as its outline shows. Pad 4 stores its size already swapped
+    // for the 90° rotation; pad 5 stores it the usual way.
+    let outline = "3996.063 2993.505 4003.937 2993.505 4003.937 3006.495 3996.063 3006.495";
+    let data = footprint_data(&[
+        &format!(
+            "PAD~RECT~4000~3000~7.874~12.99~1~~4~0~{}~90~gge1~0~~Y~0~0~0.4~4000,3000",
+            outline
+        ),
+        &format!(
+            "PAD~RECT~4000~3000~12.99~7.874~1~~5~0~{}~90~gge2~0~~Y~0~0~0.4~4000,3000",
+            outline
+        ),
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    for pad in &footprint.pads {
+        assert_eq!(pad.rotation, 0.0);
+        assert!(
+            (pad.size.0 - 2.0).abs() < 0.01 && (pad.size.1 - 3.3).abs() < 0.01,
+            "pad {} should be 2.0 x 3.3 mm, got {:?}",
+            pad.number,
+            pad.size
+        );
+    }
+}
+
+#[test]
+fn test_rotated_soic_pads_keep_long_axis() {
+    // SOIC-8 placed vertically: pads are 6.3 x 2.4 (long along local X) rotated 90°,
+    // so on the board their long axis runs along Y.
+    let data = footprint_data(&[
+        "PAD~RECT~3992.5~2985~6.3~2.4~1~~1~0~~90~gge1~0~~Y~0~0~0.4~3992.5,2985",
+        "PAD~RECT~3997.5~2985~6.3~2.4~1~~2~0~~90~gge2~0~~Y~0~0~0.4~3997.5,2985",
+        "PAD~RECT~4002.5~2985~6.3~2.4~1~~3~0~~90~gge3~0~~Y~0~0~0.4~4002.5,2985",
+        "PAD~RECT~4007.5~2985~6.3~2.4~1~~4~0~~90~gge4~0~~Y~0~0~0.4~4007.5,2985",
+        "PAD~OVAL~4007.5~3015~6.3~2.4~1~~5~0~~270~gge5~0~~Y~0~0~0.4~4007.5,3015",
+        "PAD~OVAL~4002.5~3015~6.3~2.4~1~~6~0~~270~gge6~0~~Y~0~0~0.4~4002.5,3015",
+        "PAD~OVAL~3997.5~3015~6.3~2.4~1~~7~0~~270~gge7~0~~Y~0~0~0.4~3997.5,3015",
+        "PAD~OVAL~3992.5~3015~6.3~2.4~1~~8~0~~270~gge8~0~~Y~0~0~0.4~3992.5,3015",
+    ]);
+    let ee_footprint = import_footprint(&data).unwrap();
+    let footprint = convert_footprint(ee_footprint, None).unwrap();
+
+    assert_eq!(footprint.pads.len(), 8);
+    for pad in &footprint.pads {
+        assert_eq!(pad.rotation, 0.0, "pad {} not normalized", pad.number);
+        assert!(
+            pad.size.1 > pad.size.0,
+            "pad {} long axis should run along Y, got {:?}",
+            pad.number,
+            pad.size
+        );
+    }
+}
+
+#[test]
+fn test_obj_faces_are_triangulated() {
+    // An L-shaped hexagon (concave) plus a quad. The hexagon starts at the corner where
+    // a naive fan would spill outside the outline.
+    let obj = "\
+v 0 0 0
+v 2 0 0
+v 2 1 0
+v 1 1 0
+v 1 2 0
+v 0 2 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+f 3 4 5 6 1 2
+f 7 8 9 10
+";
+    let model = convert_3d_model(model_from_obj(obj)).unwrap();
+    let (points, faces) = parse_wrl(model.wrl_data.as_deref().unwrap());
+
+    assert_eq!(faces.len(), 4 + 2);
+    for face in &faces {
+        assert_eq!(face.len(), 3, "face {:?} is not a triangle", face);
+        // Every triangle must keep the counter-clockwise winding of its source polygon;
+        // a clockwise one would lie outside the concave outline.
+        let [a, b, c] = [points[face[0]], points[face[1]], points[face[2]]];
+        let z = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        assert!(z > 0.0, "triangle {:?} flipped or degenerate", face);
+    }
+}
+
+#[test]
+fn test_obj_parse_failures_are_reported() {
+    // 30 good vertices, one unreadable coordinate: a warning, not an error.
+    let mut obj: String = (0..30).map(|i| format!("v {} 0 0\n", i)).collect();
+    obj.push_str("v 1 x 0\nf 1 2 3\n");
+    let model = convert_3d_model(model_from_obj(&obj)).unwrap();
+    assert!(model.wrl_data.is_some());
+    assert_eq!(model.warnings.len(), 1);
+    assert!(
+        model.warnings[0].contains("1 unreadable"),
+        "{:?}",
+        model.warnings
+    );
+
+    let clean = convert_3d_model(model_from_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")).unwrap();
+    assert!(clean.warnings.is_empty());
+
+    // Mostly garbage, including faces pointing past the vertex list.
+    let garbage = "v 0 0 0\nv nan 1 ?\nv 1 ?? 0\nf 1 2 9\nf x 2 3\n";
+    assert!(matches!(
+        convert_3d_model(model_from_obj(garbage)),
+        Err(Error::ModelConversionError(_))
+    ));
+}
+
+#[test]
+fn test_compact_wrl_output() {
+    let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0.5\nvn 0 0 1\nf 1//1 2//1 3//1 4//1\n";
+    let wrl = |wrl_pretty: bool| {
+        let options = ConvertOptions {
+            wrl_pretty,
+            ..Default::default()
+        };
+        convert_3d_model_with_options(model_from_obj(obj), Model3dFormat::Both, &options)
+            .unwrap()
+            .wrl_data
+            .unwrap()
+    };
+    let (pretty, compact) = (wrl(true), wrl(false));
+    assert_eq!(
+        pretty,
+        convert_3d_model(model_from_obj(obj))
+            .unwrap()
+            .wrl_data
+            .unwrap()
+    );
+
+    assert!(compact.len() * 3 < pretty.len() * 2, "{}", compact);
+    assert_eq!(compact.lines().count(), 2, "{}", compact);
+    assert!(compact.starts_with("#VRML V2.0 utf8\nShape { "));
+    assert!(!compact.contains("0000") && !compact.contains("  "));
+    assert_eq!(parse_wrl(&compact), parse_wrl(&pretty));
+}
+
+#[test]
+fn test_obj_materials_become_separate_shapes() {
+    let obj = "\
+newmtl body
+Ka 0.2 0.2 0.2
+Kd 0.1 0.1 0.1
+Ks 0.3 0.3 0.3
+d 1
+endmtl
+newmtl pins
+Kd 0.9 0.7 0.2
+d 0.5
+endmtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 5 5 5
+v 6 5 5
+v 6 6 5
+usemtl body
+f 1 2 3
+usemtl pins
+f 4 5 6
+";
+    let model = convert_3d_model(model_from_obj(obj)).unwrap();
+    let wrl = model.wrl_data.unwrap();
+
+    assert_eq!(wrl.matches("Shape {").count(), 2);
+    assert!(!wrl.contains("diffuseColor 0.5 0.5 0.5"));
+    assert!(wrl.contains("diffuseColor 0.1 0.1 0.1"));
+    assert!(wrl.contains("specularColor 0.3 0.3 0.3"));
+    assert!(wrl.contains("diffuseColor 0.9 0.7 0.2"));
+    assert!(wrl.contains("transparency 0.5"));
+
+    // Each shape is self-contained: three points, indices starting at zero.
+    let (_, second) = wrl.split_at(wrl.rfind("Shape {").unwrap());
+    let (points, faces) = parse_wrl(second);
+    assert_eq!(points.len(), 3);
+    assert_eq!(faces, vec![vec![0, 1, 2]]);
+}
+
+#[test]
+fn test_obj_normals_are_emitted() {
+    let obj = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 2
+vn 0 1 1
+f 1//1 2//1 3//2 4//2
+";
+    let model = convert_3d_model(model_from_obj(obj)).unwrap();
+    let wrl = model.wrl_data.unwrap();
+    assert!(wrl.contains("normal Normal {"));
+    // Normals are normalized on the way through.
+    assert!(wrl.contains("0.0000 0.0000 1.0000"));
+    assert!(wrl.contains("0.0000 0.7071 0.7071"));
+
+    let normal_index = &wrl[wrl.find("normalIndex [").unwrap()..];
+    let rows: Vec<&str> = normal_index
+        .lines()
+        .skip(1)
+        .take(2)
+        .map(str::trim)
+        .collect();
+    // Corners 4,1,2 then 2,3,4: vn2 is seen first, so it becomes local normal 0.
+    assert_eq!(rows, vec!["0, 1, 1, -1,", "1, 0, 0, -1,"]);
+
+    // Without `vn` data there is nothing to emit.
+    let model = convert_3d_model(model_from_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 3\n")).unwrap();
+    assert!(!model.wrl_data.unwrap().contains("normal"));
+}
+
+#[test]
+fn test_graphic_only_symbol_stays_at_origin() {
+    // A logo "symbol": only polylines and text, no pins and no rectangles.
+    let data = symbol_data(&[
+        "PL~380 290 420 290 420 310~#880000~1~0~none~gge1~0",
+        "T~L~395~305~0~#000000~~8pt~~~~comment~LOGO~1~start~gge2~0",
+    ]);
+    let ee_symbol = import_symbol(&data).unwrap();
+    assert!(ee_symbol.pins.is_empty());
+    assert!(ee_symbol.rectangles.is_empty());
+
+    let symbol = convert_symbol(ee_symbol).unwrap();
+    let entry = symbol.to_kicad_lib_entry();
+    assert!(!entry.contains("NaN") && !entry.contains("inf"));
+
+    // A rectangle-only symbol is centered on its body.
+    let data = symbol_data(&["R~380~290~~~40~20~#880000~1~0~none~gge1~0~"]);
+    let symbol = convert_symbol(import_symbol(&data).unwrap()).unwrap();
+    let rect = &symbol.rectangles[0];
+    assert_eq!(rect.start.0, -rect.end.0);
+    assert_eq!(rect.start.1, -rect.end.1);
+}
+
+#[test]
+fn test_duplicate_pad_and_pin_numbers_warn() {
+    let data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4000~3000~4~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4000,3000",
+        "PAD~RECT~4010~3000~4~4~1~~1~0~~0~gge3~0~~Y~0~0~0.4~4010,3000",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.warnings.len(), 1);
+    assert!(footprint.warnings[0].contains("pad number '1'"));
+
+    let strict = ConvertOptions {
+        duplicate_numbers_are_errors: true,
+        ..Default::default()
+    };
+    assert!(
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &strict).is_err()
+    );
+
+    let data = symbol_data(&[
+        "P~show~0~1~380~300~180~gge1~0^^380~300^^M 380 300 h 10~#880000^^1~393~304~0~VCC~start~~~#0000FF^^1~388~299~0~1~end~~~#0000FF^^0~387~300^^0~M 390 303 L 393 300 L 390 297",
+        "P~show~0~1~420~300~0~gge2~0^^420~300^^M 420 300 h -10~#880000^^1~407~304~0~GND~end~~~#0000FF^^1~412~299~0~1~start~~~#0000FF^^0~413~300^^0~M 410 297 L 407 300 L 410 303",
+    ]);
+    let symbol = convert_symbol(import_symbol(&data).unwrap()).unwrap();
+    assert_eq!(symbol.warnings.len(), 1);
+    assert!(symbol.warnings[0].contains("pin number '1'"));
+}
+
+#[test]
+fn test_symbol_metadata_properties() {
+    let mut data = symbol_data(&[]);
+    data["description"] = json!("Dual \"rail-to-rail\" op-amp");
+    data["tags"] = json!(["Amplifiers", "Op-Amps"]);
+    data["dataStr"]["head"]["c_para"]["Manufacturer"] = json!("Texas Instruments");
+    data["dataStr"]["head"]["c_para"]["Manufacturer Part"] = json!("LM358DR");
+
+    let ee_symbol = import_symbol(&data).unwrap();
+    assert_eq!(ee_symbol.info.mpn.as_deref(), Some("LM358DR"));
+
+    let entry = convert_symbol(ee_symbol).unwrap().to_kicad_lib_entry();
+    assert!(entry.contains(r#"(property "ki_description" "Dual \"rail-to-rail\" op-amp""#));
+    assert!(entry.contains(r#"(property "ki_keywords" "Amplifiers Op-Amps""#));
+    assert!(entry.contains(r#"(property "Manufacturer" "Texas Instruments""#));
+    assert!(entry.contains(r#"(property "MPN" "LM358DR""#));
+}
+
+#[test]
+fn test_symbol_without_lcsc_object() {
+    // A privately shared part: no `lcsc` object, metadata only in c_para.
+    let mut data = symbol_data(&[]);
+    let c_para = &mut data["dataStr"]["head"]["c_para"];
+    c_para["Manufacturer Part"] = json!("MY-PART-01");
+    c_para["link"] = json!("https://example.com/my-part.pdf");
+    c_para["Supplier"] = json!("Mouser");
+    c_para["Supplier Part"] = json!("595-MYPART01");
+
+    let ee_symbol = import_symbol(&data).unwrap();
+    assert_eq!(ee_symbol.info.lcsc_id, None);
+    assert_eq!(ee_symbol.info.mpn.as_deref(), Some("MY-PART-01"));
+    assert_eq!(
+        ee_symbol.info.datasheet.as_deref(),
+        Some("https://example.com/my-part.pdf")
+    );
+    let symbol = convert_symbol(ee_symbol).unwrap();
+    assert_eq!(symbol.lcsc_part, None);
+    assert!(!symbol.to_kicad_lib_entry().contains("595-MYPART01"));
+
+    // With LCSC as the supplier, its part number is the LCSC ID.
+    data["dataStr"]["head"]["c_para"]["Supplier"] = json!("LCSC");
+    data["dataStr"]["head"]["c_para"]["Supplier Part"] = json!("C12345");
+    let ee_symbol = import_symbol(&data).unwrap();
+    assert_eq!(ee_symbol.info.lcsc_id.as_deref(), Some("C12345"));
+
+    // The `lcsc` object wins when present.
+    data["lcsc"] =
+        json!({ "number": "C2040", "url": "https://www.lcsc.com/product-detail/C2040.html" });
+    let ee_symbol = import_symbol(&data).unwrap();
+    assert_eq!(ee_symbol.info.lcsc_id.as_deref(), Some("C2040"));
+    assert_eq!(
+        ee_symbol.info.datasheet.as_deref(),
+        Some("https://www.lcsc.com/product-detail/C2040.html")
+    );
+}
+
+#[test]
+fn test_same_numbered_pins_are_stacked() {
+    // Four GND pins that all carry number 5, spread around the body.
+    let gnd = |x: i32, y: i32, rot: i32, id: u32| {
+        format!(
+            "P~show~0~5~{x}~{y}~{rot}~gge{id}~0^^{x}~{y}^^M {x} {y} h 10~#880000^^1~{x}~{y}~0~GND~start~~~#0000FF^^1~{x}~{y}~0~5~end~~~#0000FF^^0~{x}~{y}^^0~M {x} {y}"
+        )
+    };
+    let shapes = [
+        gnd(380, 300, 180, 1),
+        gnd(380, 310, 180, 2),
+        gnd(420, 300, 0, 3),
+        gnd(400, 330, 90, 4),
+    ];
+    let shapes: Vec<&str> = shapes.iter().map(String::as_str).collect();
+    let symbol = convert_symbol(import_symbol(&symbol_data(&shapes)).unwrap()).unwrap();
+
+    assert_eq!(symbol.pins.len(), 4);
+    assert!(!symbol.pins[0].hidden);
+    for pin in &symbol.pins[1..] {
+        assert!(pin.hidden);
+        assert_eq!(pin.pos, symbol.pins[0].pos);
+        assert_eq!(pin.rotation, symbol.pins[0].rotation);
+    }
+    // A stack of identical pins is intentional and not worth a warning.
+    assert!(symbol.warnings.is_empty());
+    assert_eq!(symbol.to_kicad_lib_entry().matches(") hide\n").count(), 3);
+}
+
+#[test]
+fn test_3d_model_transform() {
+    let node = |attrs: &str| {
+        format!(
+            r#"SVGNODE~{{"gId":"g1","nodeName":"g","nodeType":1,"layerid":"19","attrs":{{"uuid":"u","title":"BGA","layerid":"19"{}}},"childNodes":[]}}"#,
+            attrs
+        )
+    };
+    let pads = [
+        "PAD~RECT~3990~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+    ];
+    let place = |attrs: &str| {
+        let svg_node = node(attrs);
+        let data = footprint_data(&[pads[0], pads[1], &svg_node]);
+        let mut ee_model = import_3d_model_info(&data).unwrap().unwrap();
+        ee_model.raw_obj = None;
+        let transform = ee_model.transform.clone();
+        let model = convert_3d_model(ee_model).unwrap();
+        let footprint = convert_footprint(import_footprint(&data).unwrap(), Some(model)).unwrap();
+        (transform, footprint.model_3d.unwrap())
+    };
+    let close = |a: Vec3, b: Vec3| (a - b).length() < 1e-3;
+
+    // Full placement: origin 10 units right of and 5 below the pads' center, lifted
+    // by `z`, turned 90° about Z.
+    let (transform, model) = place(
+        r#","c_origin":"4010,3005","z":"-7.874","c_rotation":"0,0,90","transform":"scale(1) translate(0, 0)""#,
+    );
+    assert_eq!(transform.origin, Some((4010.0, 3005.0)));
+    assert_eq!(transform.rotation, Some((0.0, 0.0, 90.0)));
+    assert_eq!(transform.matrix, Some([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]));
+    assert!(
+        close(model.offset, Vec3::new(2.54, -1.27, 2.0)),
+        "{:?}",
+        model.offset
+    );
+    assert!(
+        close(model.rotate, Vec3::new(0.0, 0.0, 270.0)),
+        "{:?}",
+        model.rotate
+    );
+    assert!(close(model.scale, Vec3::ONE));
+
+    // Only a transform: it supplies the origin, rotation and scale.
+    let (transform, model) = place(r#","transform":"translate(4000, 3000) rotate(-90) scale(2)""#);
+    assert_eq!(transform.origin, None);
+    assert!(close(model.offset, Vec3::ZERO), "{:?}", model.offset);
+    assert!(
+        close(model.rotate, Vec3::new(0.0, 0.0, 90.0)),
+        "{:?}",
+        model.rotate
+    );
+    assert!(
+        close(model.scale, Vec3::new(2.0, 2.0, 1.0)),
+        "{:?}",
+        model.scale
+    );
+
+    // Nothing at all, or nothing readable: the model stays where the footprint is.
+    for attrs in ["", r#","c_origin":"x","z":"","transform":"skew(3)""#] {
+        let (transform, model) = place(attrs);
+        assert_eq!(transform, Default::default());
+        assert!(close(model.offset, Vec3::ZERO) && close(model.rotate, Vec3::ZERO));
+    }
+}
+
+#[test]
+fn test_3d_model_matches_package_variant() {
+    let node = |uuid: &str, title: &str| {
+        format!(
+            r#"SVGNODE~{{"gId":"g1","nodeName":"g","nodeType":1,"layerid":"19","attrs":{{"c_width":"10","c_height":"10","c_rotation":"0,0,0","z":"0","c_origin":"4000,3000","uuid":"{}","c_etype":"outline3D","id":"g1","title":"{}","layerid":"19","transform":"scale(1) translate(0, 0)"}},"childNodes":[]}}"#,
+            uuid, title
+        )
+    };
+    let a = node("uuid-a", "SOT-23-3_L2.9-W1.3-P1.90-LS2.4-BR");
+    let b = node("uuid-b", "SOT-23-5_L3.0-W1.7-P0.95-LS2.8-BR");
+    let mut data = footprint_data(&[&a, &b]);
+    data["packageDetail"]["dataStr"]["head"]["c_para"] =
+        json!({ "package": "SOT-23-5_L3.0-W1.7-P0.95-LS2.8-BR" });
+
+    assert_eq!(import_3d_model_infos(&data).unwrap().len(), 2);
+    let chosen = import_3d_model_info(&data).unwrap().unwrap();
+    assert_eq!(chosen.uuid, "uuid-b");
+
+    // Without a usable variant the first model wins, as before.
+    data["packageDetail"]["dataStr"]["head"]["c_para"] = json!({});
+    data["packageDetail"]["title"] = json!("");
+    assert_eq!(import_3d_model_info(&data).unwrap().unwrap().uuid, "uuid-a");
+}
+
+#[test]
+fn test_thermal_relief_on_through_hole_pads() {
+    let data = footprint_data(&[
+        "PAD~ELLIPSE~3990~3000~6~6~11~~1~1.5~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+    ]);
+    let options = ConvertOptions {
+        thermal: Some(PadThermal {
+            width: Some(0.5),
+            gap: Some(0.3),
+        }),
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    let entry = footprint.to_kicad_mod_entry();
+
+    let th = entry.lines().find(|l| l.contains("(pad 1 ")).unwrap();
+    assert!(th.contains("(zone_connect 1) (thermal_width 0.5) (thermal_gap 0.3)"));
+    let smd = entry.lines().find(|l| l.contains("(pad 2 ")).unwrap();
+    assert!(!smd.contains("zone_connect"));
+
+    // Default: no thermal overrides at all.
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert!(!footprint.to_kicad_mod_entry().contains("zone_connect"));
+}
+
+#[test]
+fn test_pin_length_from_path() {
+    let pin = |rotation: &str, path: &str| {
+        format!(
+            "P~show~0~1~400~300~{}~gge1~0^^400~300^^{}~#880000^^1~0~0~0~A~start~~~#0000FF^^1~0~0~0~1~end~~~#0000FF^^0~0~0^^0~M 0 0",
+            rotation, path
+        )
+    };
+    let data = symbol_data(&[
+        &pin("180", "M 400 300 H 420"),
+        &pin("90", "M 400 300 L 400 285"),
+        &pin("180", "M400,300h10h5"),
+        &pin("", "M 400 300 v 25"),
+    ]);
+    let symbol = import_symbol(&data).unwrap();
+    let geometry: Vec<(f32, i32)> = symbol
+        .pins
+        .iter()
+        .map(|p| (p.pin_length, p.rotation))
+        .collect();
+    assert_eq!(
+        geometry,
+        [(20.0, 180), (15.0, 90), (15.0, 180), (25.0, 270)]
+    );
+}
+
+#[test]
+fn test_pin_rotation_snapping() {
+    let pin = |number: usize, rotation: &str| {
+        format!(
+            "P~show~0~{0}~400~{1}~{2}~gge{0}~0^^400~{1}^^M 400 {1} h 10~#880000^^1~0~0~0~A~start~~~#0000FF^^1~0~0~0~{0}~end~~~#0000FF^^0~0~0^^0~M 0 0",
+            number,
+            300 + number * 10,
+            rotation
+        )
+    };
+    let convert = |rotations: &[&str]| {
+        let pins: Vec<String> = rotations
+            .iter()
+            .enumerate()
+            .map(|(i, r)| pin(i + 1, r))
+            .collect();
+        let pins: Vec<&str> = pins.iter().map(String::as_str).collect();
+        convert_symbol(import_symbol(&symbol_data(&pins)).unwrap()).unwrap()
+    };
+
+    // Slightly off-axis and negative angles snap silently.
+    let symbol = convert(&["0", "91", "-90", "359.6"]);
+    let rotations: Vec<i32> = symbol.pins.iter().map(|p| p.rotation).collect();
+    assert_eq!(rotations, [180, 270, 90, 180]);
+    assert!(symbol.warnings.is_empty());
+
+    let symbol = convert(&["0", "45"]);
+    assert_eq!(symbol.pins[1].rotation, 270);
+    assert_eq!(symbol.warnings.len(), 1);
+    assert!(symbol.warnings[0].contains("pins 2 are not at a multiple of 90"));
+}
+
+#[test]
+fn test_footprint_arcs() {
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let arc = |path: &str| format!("ARC~1~3~~{}~~gge2~0", path);
+    let convert = |path: &str, arc_segments: usize| {
+        let data = footprint_data(&[pad, &arc(path)]);
+        let options = ConvertOptions {
+            arc_segments,
+            ..Default::default()
+        };
+        let footprint =
+            convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options)
+                .unwrap();
+        footprint
+            .graphics
+            .into_iter()
+            .filter(|g| !matches!(g.graphic_type, FpGraphicType::Circle { .. }))
+            .collect::<Vec<_>>()
+    };
+    let close = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+
+    // A circular quarter arc becomes a native fp_arc.
+    let graphics = convert("M 4000 3000 A 10 10 0 0 1 4010 3010", 0);
+    assert_eq!(graphics.len(), 1);
+    assert_eq!(graphics[0].layer, "F.SilkS");
+    let FpGraphicType::Arc { start, mid, end } = graphics[0].graphic_type else {
+        panic!("expected an arc: {:?}", graphics[0]);
+    };
+    assert!(close(start, (0.0, 0.0)));
+    assert!(close(mid, (1.7961, 0.7439)), "{:?}", mid);
+    assert!(close(end, (2.54, 2.54)));
+
+    // An elliptical arc can't be an fp_arc; it is tessellated.
+    let graphics = convert("M 4000 3000 A 20 10 0 0 1 4020 3010", 8);
+    assert_eq!(graphics.len(), 8);
+    assert!(
+        graphics
+            .iter()
+            .all(|g| matches!(g.graphic_type, FpGraphicType::Line { .. }))
+    );
+
+    // A zero-radius arc is a straight line.
+    let graphics = convert("M 4000 3000 A 0 0 0 0 1 4010 3000", 0);
+    assert!(matches!(
+        graphics[..],
+        [FpGraphic {
+            graphic_type: FpGraphicType::Line { .. },
+            ..
+        }]
+    ));
+
+    // Nothing readable at all: the arc is dropped, not the footprint.
+    assert!(convert("garbage", 0).is_empty());
+}
+
+#[test]
+fn test_footprint_conversion_warnings() {
+    let data = footprint_data(&[
+        "PAD~POLYGON~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "VIA~4010~3000~2~~1~gge2~0",
+        "VIA~4020~3000~2~~1~gge3~0",
+        "ARC~1~3~~garbage~~gge4~0",
+        "ARC~1~3~~M 4000 3000 A 20 10 0 0 1 4020 3010~~gge5~0",
+        "TRACK~1~3~~10000 3000 10010 3000~gge6~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let warnings = footprint.warnings.join("\n");
+    for expected in [
+        "pad '1' has unsupported shape 'POLYGON'; drawn as a rectangle",
+        "2 unsupported VIA shape(s) skipped",
+        "arc with unusable path 'garbage' was dropped",
+        "can't be drawn natively; approximated with line segments",
+        "1 oversized or far off-center graphic(s) dropped",
+    ] {
+        assert!(
+            warnings.contains(expected),
+            "missing {:?} in:\n{}",
+            expected,
+            warnings
+        );
+    }
+    assert!(
+        footprint
+            .warnings
+            .iter()
+            .all(|w| w.starts_with("Footprint 'TEST_FP': "))
+    );
+}
+
+#[test]
+fn test_default_reference_and_value() {
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let data = footprint_data(&[pad, "TRACK~1~3~~3990 2990 4010 2990~gge2~0"]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let field = |kind: &str| {
+        let texts: Vec<_> = footprint
+            .texts
+            .iter()
+            .filter(|t| t.text_type == kind)
+            .collect();
+        assert_eq!(texts.len(), 1, "expected one {} text", kind);
+        texts[0]
+    };
+    let reference = field("reference");
+    assert_eq!(reference.text, "REF**");
+    assert_eq!(reference.layer, "F.SilkS");
+    // Above the silk line at y = -2.54, below the pad.
+    assert!((reference.pos.1 - (-2.54 - 1.5)).abs() < 1e-4);
+    let value = field("value");
+    assert_eq!(value.text, "TEST_FP");
+    assert!((value.pos.1 - (0.508 + 1.5)).abs() < 1e-4);
+    assert!(reference.pos.0.abs() < 1e-4 && value.pos.0.abs() < 1e-4);
+
+    // Off-center parts get their fields centered over what they draw.
+    let data = footprint_data(&[pad, "TRACK~1~3~~4000 2990 4020 2990~gge2~0"]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    for text in &footprint.texts {
+        // From the pad's left edge at -0.508 to the line's end at 5.08.
+        assert!((text.pos.0 - 2.286).abs() < 1e-4);
+    }
+
+    // Texts EasyEDA did provide are kept as they are.
+    let data = footprint_data(&[
+        pad,
+        "TEXT~N~4000~2990~0.8~0~0~3~~4.5~U1~M 0 0~~gge3~~0~pinpart",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let references: Vec<_> = footprint
+        .texts
+        .iter()
+        .filter(|t| t.text_type == "reference")
+        .collect();
+    assert_eq!(references.len(), 1);
+    assert!((references[0].pos.1 + 2.54).abs() < 1e-4);
+    assert_eq!(footprint.texts.len(), 2);
+}
+
+#[test]
+fn test_reference_prefix() {
+    let reference = |prefix: Option<&str>, options: &ConvertOptions| {
+        let mut data = footprint_data(&[
+            "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+            "TEXT~N~4000~2990~0.8~0~0~3~~4.5~U1~M 0 0~~gge3~~0~pinpart",
+        ]);
+        if let Some(prefix) = prefix {
+            data["dataStr"] = json!({ "head": { "c_para": { "pre": prefix } }, "shape": [] });
+        }
+        let footprint =
+            convert_footprint_with_options(import_footprint(&data).unwrap(), None, options)
+                .unwrap();
+        footprint.to_kicad_mod_entry()
+    };
+    let defaults = ConvertOptions::default();
+
+    assert!(reference(None, &defaults).contains("(fp_text reference REF** "));
+    assert!(reference(Some("R?"), &defaults).contains("(fp_text reference R** "));
+    let options = ConvertOptions {
+        reference_prefix: Some("C".to_string()),
+        ..Default::default()
+    };
+    assert!(reference(Some("R?"), &options).contains("(fp_text reference C** "));
+}
+
+#[test]
+fn test_ellipse_pads() {
+    let data = footprint_data(&[
+        // Round: a circle.
+        "PAD~ELLIPSE~3990~3000~6~6~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        // Wider than high, and higher than wide after a quarter turn.
+        "PAD~ELLIPSE~4000~3000~8~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4000,3000",
+        "PAD~ELLIPSE~4010~3000~8~4~1~~3~0~~90~gge3~0~~Y~0~0~0.4~4010,3000",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let entry = footprint.to_kicad_mod_entry();
+    let pads: Vec<&str> = entry.lines().filter(|l| l.contains("(pad ")).collect();
+    assert!(pads[0].contains("smd circle"), "{}", pads[0]);
+    assert!(pads[1].contains("smd oval"), "{}", pads[1]);
+    assert!(pads[1].contains("(size 2.032 1.016)"), "{}", pads[1]);
+    assert!(pads[2].contains("smd oval"), "{}", pads[2]);
+    assert!(pads[2].contains("(size 1.016 2.032)"), "{}", pads[2]);
+}
+
+#[test]
+fn test_coincident_pads_are_merged() {
+    let data = footprint_data(&[
+        // Pad 1 on copper (layer 1) and again on paste (layer 5) at the same spot.
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "PAD~RECT~4000~3000~4~4~5~~1~0~~0~gge2~0~~Y~0~0~0.4~4000,3000",
+        // Same place and size but a different number: a separate pad.
+        "PAD~RECT~4000~3000~4~4~1~~2~0~~0~gge3~0~~Y~0~0~0.4~4000,3000",
+        "PAD~RECT~4020~3000~4~4~1~~3~0~~0~gge4~0~~Y~0~0~0.4~4020,3000",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let numbers: Vec<&str> = footprint.pads.iter().map(|p| p.number.as_str()).collect();
+    assert_eq!(numbers, ["1", "2", "3"]);
+    assert_eq!(footprint.pads[0].layers, ["F.Cu", "F.Paste", "F.Mask"]);
+    assert!(footprint.warnings.is_empty(), "{:?}", footprint.warnings);
+
+    // A paste-only pad gains copper and mask from its copper twin.
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~5~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge2~0~~Y~0~0~0.4~4000,3000",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.pads.len(), 1);
+    assert_eq!(footprint.pads[0].layers, ["F.Paste", "F.Cu", "F.Mask"]);
+}
+
+#[test]
+fn test_footprint_mounting_holes() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        // Two M3 holes (3.2 mm) either side of the pad.
+        "HOLE~3970~3000~6.2992~gge2~0",
+        "HOLE~4030~3000~6.2992~gge3~0",
+    ]);
+    let ee_footprint = import_footprint(&data).unwrap();
+    assert_eq!(ee_footprint.holes.len(), 2);
+
+    let footprint = convert_footprint(ee_footprint.clone(), None).unwrap();
+    assert!(footprint.warnings.is_empty(), "{:?}", footprint.warnings);
+    let holes: Vec<_> = footprint
+        .pads
+        .iter()
+        .filter(|p| p.pad_type == "np_thru_hole")
+        .collect();
+    assert_eq!(holes.len(), 2);
+    for (hole, x) in holes.iter().zip([-7.62, 7.62]) {
+        assert!((hole.pos.0 - x).abs() < 1e-3 && hole.pos.1.abs() < 1e-3);
+        assert!((hole.size.0 - 3.2).abs() < 1e-3 && hole.size.0 == hole.size.1);
+        assert_eq!(hole.drill, Some(hole.size.0));
+        assert_eq!(hole.layers, ["*.Cu", "*.Mask"]);
+    }
+
+    let entry = footprint.to_kicad_mod_entry();
+    assert_eq!(entry.matches("(pad \"\" np_thru_hole circle").count(), 2);
+
+    // Unnumbered holes aren't duplicate pad numbers.
+    let options = ConvertOptions {
+        duplicate_numbers_are_errors: true,
+        ..Default::default()
+    };
+    assert!(convert_footprint_with_options(ee_footprint, None, &options).is_ok());
+}
+
+#[test]
+fn test_zero_width_graphics_get_layer_default() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "TRACK~0~3~~3990 2990 4010 2990~gge2~0",
+        "TRACK~~13~~3990 3010 4010 3010~gge3~0",
+        "TRACK~0.001~3~~3990 2995 4010 2995~gge4~0",
+        "TRACK~1~3~~3990 3005 4010 3005~gge5~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let widths: Vec<(&str, f32)> = footprint
+        .graphics
+        .iter()
+        .filter(|g| matches!(g.graphic_type, FpGraphicType::Line { .. }))
+        .map(|g| (g.layer.as_str(), g.width))
+        .collect();
+    assert_eq!(
+        widths,
+        [
+            ("F.SilkS", 0.12),
+            ("F.Fab", 0.1),
+            ("F.SilkS", 0.01),
+            ("F.SilkS", 0.254)
+        ]
+    );
+}
+
+#[test]
+fn test_exclude_from_bom_and_pos() {
+    let pad = "PAD~ELLIPSE~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let convert = |title: &str, prefix: Option<&str>, options: &ConvertOptions| {
+        let mut data = footprint_data(&[pad]);
+        data["packageDetail"]["title"] = json!(title);
+        if let Some(prefix) = prefix {
+            data["dataStr"] = json!({ "head": { "c_para": { "pre": prefix } }, "shape": [] });
+        }
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, options).unwrap()
+    };
+    let defaults = ConvertOptions::default();
+
+    // Inferred from the symbol's prefix or the footprint name.
+    for (title, prefix) in [
+        ("TP_1.0MM", Some("TP?")),
+        ("Fiducial_1mm", None),
+        ("MOUNTING-HOLE_3.2MM", None),
+    ] {
+        let footprint = convert(title, prefix, &defaults);
+        assert!(
+            footprint.exclude_from_bom && footprint.exclude_from_pos,
+            "{}",
+            title
+        );
+        let entry = footprint.to_kicad_mod_entry();
+        assert!(entry.contains("(attr exclude_from_bom exclude_from_pos_files)"));
+    }
+
+    let footprint = convert("R0603", Some("R?"), &defaults);
+    assert!(!footprint.exclude_from_bom && !footprint.exclude_from_pos);
+    assert!(!footprint.to_kicad_mod_entry().contains("(attr"));
+
+    // Explicit options override the guess either way.
+    let options = ConvertOptions {
+        exclude_from_bom: Some(false),
+        exclude_from_pos: Some(true),
+        ..Default::default()
+    };
+    let footprint = convert("TP_1.0MM", Some("TP?"), &options);
+    assert!(
+        footprint
+            .to_kicad_mod_entry()
+            .contains("(attr exclude_from_pos_files)")
+    );
+}
+
+#[test]
+fn test_mirror_footprint_to_bottom() {
+    let data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~6~1~~1~0~~30~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~6~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+        "TRACK~1~3~~3980 2990 4005 2995~gge3~0",
+    ]);
+    let mut footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let pads_before: Vec<_> = footprint.pads.iter().map(|p| (p.pos, p.rotation)).collect();
+    footprint.mirror_to_bottom();
+
+    for (pad, (pos, rotation)) in footprint.pads.iter().zip(&pads_before) {
+        assert_eq!(pad.pos, (-pos.0, pos.1));
+        assert_eq!(pad.rotation, -rotation);
+        assert_eq!(pad.layers, ["B.Cu", "B.Paste", "B.Mask"]);
+    }
+    let line = footprint
+        .graphics
+        .iter()
+        .find(|g| matches!(g.graphic_type, FpGraphicType::Line { .. }))
+        .unwrap();
+    assert_eq!(line.layer, "B.SilkS");
+    let FpGraphicType::Line { start, end } = line.graphic_type else {
+        unreachable!()
+    };
+    assert!((start.0 - 5.08).abs() < 1e-3 && (end.0 + 1.27).abs() < 1e-3);
+
+    let entry = footprint.to_kicad_mod_entry();
+    assert!(entry.starts_with("(module TEST_FP (layer B.Cu)"));
+    assert!(!entry.contains("F.SilkS") && !entry.contains("F.Cu"));
+
+    // Already on the bottom: nothing changes.
+    footprint.mirror_to_bottom();
+    assert_eq!(footprint.to_kicad_mod_entry(), entry);
+}
+
+#[test]
+fn test_footprint_rectangles() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "RECT~3990~2995~20~10~0.5~gge2~15~0",
+    ]);
+    let ee_footprint = import_footprint(&data).unwrap();
+    assert_eq!(ee_footprint.rects.len(), 1);
+
+    let footprint = convert_footprint(ee_footprint, None).unwrap();
+    let rect = footprint
+        .graphics
+        .iter()
+        .find(|g| matches!(g.graphic_type, FpGraphicType::Rect { .. }))
+        .unwrap();
+    assert_eq!(rect.layer, "Dwgs.User");
+    let FpGraphicType::Rect { start, end } = rect.graphic_type else {
+        unreachable!()
+    };
+    let close = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+    assert!(close(start, (-2.54, -1.27)), "{:?}", start);
+    assert!(close(end, (2.54, 1.27)), "{:?}", end);
+    assert!(
+        footprint
+            .to_kicad_mod_entry()
+            .contains("(fp_rect (start -2.54")
+    );
+}
+
+#[test]
+fn test_footprint_arc_direction() {
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let close = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+    let arc_mid = |path: &str| {
+        let data = footprint_data(&[pad, &format!("ARC~1~3~~{}~~gge2~0", path)]);
+        let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+        let arcs: Vec<_> = footprint
+            .graphics
+            .iter()
+            .filter_map(|g| match g.graphic_type {
+                FpGraphicType::Arc { start, mid, end } => Some((start, mid, end)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(arcs.len(), 1, "{}", path);
+        let (start, mid, end) = arcs[0];
+        assert!(
+            close(start, (0.0, 0.0)) && close(end, (2.54, 2.54)),
+            "{}",
+            path
+        );
+        mid
+    };
+
+    // The same endpoints and radius give four different arcs depending on the flags.
+    let cases = [
+        ("0 0 1", (1.7961, 0.7439)),
+        ("0 0 0", (0.7439, 1.7961)),
+        // 270° arcs: the mid point lies on the far side of the chord.
+        ("0 1 1", (4.3361, -1.7961)),
+        ("0 1 0", (-1.7961, 4.3361)),
+    ];
+    for (flags, expected) in cases {
+        let path = format!("M 4000 3000 A 10 10 {} 4010 3010", flags);
+        let mid = arc_mid(&path);
+        assert!(close(mid, expected), "{}: {:?}", path, mid);
+    }
+
+    // Flags are single characters and may be packed against the next number.
+    let mid = arc_mid("M4000,3000A10,10,0,114010,3010");
+    assert!(close(mid, (4.3361, -1.7961)), "{:?}", mid);
+}
+
+#[test]
+fn test_symbol_pin_map() {
+    let pin = |number: &str, y: i32, name: &str| {
+        format!(
+            "P~show~0~{n}~380~{y}~180~gge{n}~0^^380~{y}^^M 380 {y} h 10~#880000^^1~393~{y}~0~{name}~start~~~#0000FF^^1~388~{y}~0~{n}~end~~~#0000FF^^0~387~{y}^^0~M 0 0",
+            n = number,
+            y = y,
+            name = name
+        )
+    };
+    let data = symbol_data(&[
+        &pin("10", 300, "OUT"),
+        &pin("2", 310, "IN-"),
+        &pin("EP", 320, "GND"),
+        &pin("1", 330, "IN+"),
+    ]);
+    let symbol = convert_symbol(import_symbol(&data).unwrap()).unwrap();
+    let map = symbol.pin_map();
+    let pairs: Vec<(&str, &str)> = map.iter().map(|(n, p)| (n.as_str(), p.as_str())).collect();
+    assert_eq!(
+        pairs,
+        [("1", "IN+"), ("2", "IN-"), ("10", "OUT"), ("EP", "GND")]
+    );
+
+    let json: Value = serde_json::from_str(&symbol.pin_map_json()).unwrap();
+    assert_eq!(json[2], json!({ "number": "10", "name": "OUT" }));
+}
+
+#[test]
+fn test_numeric_head_values() {
+    let pad = "PAD~RECT~4010~3005~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4010,3005";
+    let mut data = footprint_data(&[pad]);
+    data["packageDetail"]["dataStr"]["head"] = json!({ "x": 4000, "y": 3000.5 });
+    let footprint = import_footprint(&data).unwrap();
+    assert_eq!(footprint.bbox, (4000.0, 3000.5));
+
+    let mut data = symbol_data(&[]);
+    data["dataStr"]["head"]["x"] = json!(400);
+    data["dataStr"]["head"]["y"] = json!("300");
+    assert_eq!(import_symbol(&data).unwrap().bbox, (400.0, 300.0));
+}
+
+#[test]
+fn test_symbol_grid_snapping() {
+    let data = symbol_data(&[
+        "P~show~0~1~383~300~180~gge1~0^^383~300^^M 383 300 h 10~#880000^^1~393~304~0~A~start~~~#0000FF^^1~388~299~0~1~end~~~#0000FF^^0~387~300^^0~M 0 0",
+        "P~show~0~2~420~300~0~gge2~0^^420~300^^M 420 300 h -10~#880000^^1~407~304~0~B~end~~~#0000FF^^1~412~299~0~2~start~~~#0000FF^^0~413~300^^0~M 0 0",
+    ]);
+    let pin_x = |grid: Option<f32>| {
+        let options = ConvertOptions {
+            symbol_grid: grid,
+            symbol_origin: SymbolOrigin::Centroid,
+            ..Default::default()
+        };
+        let symbol = convert_symbol_with_options(import_symbol(&data).unwrap(), &options).unwrap();
+        symbol.pins.iter().map(|p| p.pos.0).collect::<Vec<_>>()
+    };
+    let assert_close = |got: Vec<f32>, expected: [f32; 2]| {
+        for (g, e) in got.iter().zip(expected) {
+            assert!((g - e).abs() < 1e-4, "{:?} != {:?}", got, expected);
+        }
+    };
+
+    assert_close(pin_x(None), [-3.81, 5.08]);
+    assert_close(pin_x(Some(2.54)), [-5.08, 5.08]);
+    // Snapping disabled: positions relative to the exact center.
+    assert_close(pin_x(Some(0.0)), [-4.699, 4.699]);
+}
+
+#[test]
+fn test_symbol_origin_from_head() {
+    // Two pins left of EasyEDA's origin (400, 300) and one right of it, so the
+    // centroid of the pins is not the origin.
+    let pin = |n: u32, x: i32, rot: i32| {
+        format!(
+            "P~show~0~{n}~{x}~300~{rot}~gge{n}~0^^{x}~300^^M {x} 300 h 10~#880000^^1~{x}~304~0~P{n}~start~~~#0000FF^^1~{x}~299~0~{n}~end~~~#0000FF^^0~{x}~300^^0~M 0 0"
+        )
+    };
+    let data = symbol_data(&[&pin(1, 380, 180), &pin(2, 370, 180), &pin(3, 410, 0)]);
+    let pin_x = |origin: SymbolOrigin, data: &Value| {
+        let options = ConvertOptions {
+            symbol_origin: origin,
+            ..Default::default()
+        };
+        let symbol = convert_symbol_with_options(import_symbol(data).unwrap(), &options).unwrap();
+        symbol.pins.iter().map(|p| p.pos.0).collect::<Vec<_>>()
+    };
+    let assert_close = |got: Vec<f32>, expected: [f32; 3]| {
+        for (g, e) in got.iter().zip(expected) {
+            assert!((g - e).abs() < 1e-4, "{:?} != {:?}", got, expected);
+        }
+    };
+
+    // Origin-based: positions are exactly where EasyEDA has them, relative to head.
+    assert_close(pin_x(SymbolOrigin::Head, &data), [-5.08, -7.62, 2.54]);
+    // Centroid-based: centered on the pins' extent (-7.62..2.54, center -2.54).
+    assert_close(pin_x(SymbolOrigin::Centroid, &data), [-2.54, -5.08, 5.08]);
+
+    // An origin far from every pin (e.g. missing from head) isn't trusted.
+    let mut no_head = data.clone();
+    no_head["dataStr"]["head"]["x"] = json!("0");
+    no_head["dataStr"]["head"]["y"] = json!("0");
+    assert_close(pin_x(SymbolOrigin::Head, &no_head), [-2.54, -5.08, 5.08]);
+}
+
+#[test]
+fn test_string_encoded_data_str() {
+    let mut symbol = symbol_data(&[
+        "P~show~0~1~380~300~180~gge1~0^^380~300^^M 380 300 h 10~#880000^^1~393~304~0~VCC~start~~~#0000FF^^1~388~299~0~1~end~~~#0000FF^^0~387~300^^0~M 0 0",
+    ]);
+    symbol["dataStr"] = json!(symbol["dataStr"].to_string());
+    let ee_symbol = import_symbol(&symbol).unwrap();
+    assert_eq!(ee_symbol.info.name, "TEST_SYM");
+    assert_eq!(ee_symbol.pins.len(), 1);
+
+    let mut footprint = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        r#"SVGNODE~{"gId":"g1","nodeName":"g","nodeType":1,"layerid":"19","attrs":{"uuid":"abc","title":"MODEL"},"childNodes":[]}"#,
+    ]);
+    let encoded = footprint["packageDetail"]["dataStr"].to_string();
+    footprint["packageDetail"]["dataStr"] = json!(encoded);
+    assert_eq!(import_footprint(&footprint).unwrap().pads.len(), 1);
+    assert_eq!(
+        import_3d_model_info(&footprint).unwrap().unwrap().uuid,
+        "abc"
+    );
+
+    footprint["packageDetail"]["dataStr"] = json!("{not json");
+    assert!(import_footprint(&footprint).is_err());
+}
+
+#[test]
+fn test_unmapped_layer_and_pin_type_fallbacks() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "TRACK~1~20~~3990 2990 4010 2990~gge2~0",
+        "TRACK~1~20~~3990 3010 4010 3010~gge3~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.warnings.len(), 1);
+    assert!(footprint.warnings[0].contains("layer 20"));
+    assert!(footprint.warnings[0].contains("F.Fab"));
+
+    let options = ConvertOptions {
+        fallback_layer: Some("Dwgs.User".to_string()),
+        default_pin_type: Some(KiPinType::Unspecified),
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    let lines: Vec<_> = footprint
+        .graphics
+        .iter()
+        .filter(|g| matches!(g.graphic_type, FpGraphicType::Line { .. }))
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|g| g.layer == "Dwgs.User"));
+
+    let data = symbol_data(&[
+        "P~show~9~1~380~300~180~gge1~0^^380~300^^M 380 300 h 10~#880000^^1~393~304~0~A~start~~~#0000FF^^1~388~299~0~1~end~~~#0000FF^^0~387~300^^0~M 0 0",
+        "P~show~0~2~380~310~180~gge2~0^^380~310^^M 380 310 h 10~#880000^^1~393~314~0~B~start~~~#0000FF^^1~388~309~0~2~end~~~#0000FF^^0~387~310^^0~M 0 0",
+    ]);
+    let symbol = convert_symbol_with_options(import_symbol(&data).unwrap(), &options).unwrap();
+    assert_eq!(symbol.pins[0].pin_type, KiPinType::Unspecified);
+    assert_eq!(symbol.warnings.len(), 1);
+    assert!(symbol.warnings[0].contains("pin type '9'"));
+}
+
+#[test]
+fn test_bga_and_unnumbered_pads() {
+    let pad = |x: i32, y: i32, number: &str| {
+        format!(
+            "PAD~ELLIPSE~{x}~{y}~2~2~1~~{n}~0~~0~gge{x}{y}~0~~Y~0~0~0.4~{x},{y}",
+            x = x,
+            y = y,
+            n = number
+        )
+    };
+    let bga = [
+        pad(3995, 2995, "A1"),
+        pad(4005, 2995, "A2"),
+        pad(3995, 3005, "B1"),
+        pad(4005, 3005, "B2"),
+    ];
+    let data = footprint_data(&bga.iter().map(String::as_str).collect::<Vec<_>>());
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let numbers: Vec<&str> = footprint.pads.iter().map(|p| p.number.as_str()).collect();
+    assert_eq!(numbers, ["A1", "A2", "B1", "B2"]);
+    assert!(footprint.warnings.is_empty());
+
+    // The index fallback must not collide with a real pad number.
+    let pads = [
+        pad(3990, 3000, ""),
+        pad(4000, 3000, "1"),
+        pad(4010, 3000, "0"),
+        pad(4020, 3000, ""),
+    ];
+    let data = footprint_data(&pads.iter().map(String::as_str).collect::<Vec<_>>());
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let numbers: Vec<&str> = footprint.pads.iter().map(|p| p.number.as_str()).collect();
+    assert_eq!(numbers, ["2", "1", "0", "4"]);
+    assert!(footprint.warnings.is_empty());
+}
+
+#[test]
+fn test_pad_clearance_from_net_rule() {
+    let mut data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~4~1~HV~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~4~1~GND~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+    ]);
+    data["packageDetail"]["dataStr"]["DRCRULE"] = json!({
+        "Default": { "trackWidth": 1, "clearance": 0.6 },
+        "HV": { "trackWidth": 1, "clearance": "10" },
+        "isRealtime": true,
+    });
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert!((footprint.pads[0].clearance.unwrap() - 2.54).abs() < 1e-4);
+    // The board default isn't copied onto every pad.
+    assert_eq!(footprint.pads[1].clearance, None);
+
+    let entry = footprint.to_kicad_mod_entry();
+    let hv = entry.lines().find(|l| l.contains("(pad 1 ")).unwrap();
+    assert!(hv.contains("(clearance 2.54)"), "{}", hv);
+    let gnd = entry.lines().find(|l| l.contains("(pad 2 ")).unwrap();
+    assert!(!gnd.contains("clearance"));
+}
+
+#[test]
+fn test_synthesized_connector_body() {
+    let pin = |n: u32, y: i32| {
+        format!(
+            "P~show~0~{n}~380~{y}~180~gge{n}~0^^380~{y}^^M 380 {y} h 10~#880000^^1~393~{y}~0~P{n}~start~~~#0000FF^^1~388~{y}~0~{n}~end~~~#0000FF^^0~387~{y}^^0~M 0 0",
+            n = n,
+            y = y
+        )
+    };
+    let data = symbol_data(&[&pin(1, 290), &pin(2, 300), &pin(3, 310)]);
+
+    let symbol = convert_symbol(import_symbol(&data).unwrap()).unwrap();
+    assert!(symbol.rectangles.is_empty());
+
+    let options = ConvertOptions {
+        synthesize_body: true,
+        symbol_origin: SymbolOrigin::Centroid,
+        ..Default::default()
+    };
+    let symbol = convert_symbol_with_options(import_symbol(&data).unwrap(), &options).unwrap();
+    assert_eq!(symbol.rectangles.len(), 1);
+    let rect = &symbol.rectangles[0];
+    let close = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4;
+    assert!(close(rect.start, (2.54, 3.81)), "{:?}", rect);
+    assert!(close(rect.end, (7.62, -3.81)), "{:?}", rect);
+}
+
+#[test]
+fn test_closed_track_as_poly() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "TRACK~1~3~~3990 2990 4010 2990 4010 3010 3990 3010 3990 2990~gge2~0",
+    ]);
+    let count = |g: &[FpGraphic], line: bool| {
+        g.iter()
+            .filter(|g| match g.graphic_type {
+                FpGraphicType::Line { .. } => line,
+                FpGraphicType::Poly { .. } => !line,
+                _ => false,
+            })
+            .count()
+    };
+
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(count(&footprint.graphics, true), 4);
+    assert_eq!(count(&footprint.graphics, false), 0);
+
+    let options = ConvertOptions {
+        closed_tracks_as_poly: true,
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    assert_eq!(count(&footprint.graphics, true), 0);
+    assert_eq!(count(&footprint.graphics, false), 1);
+    assert!(footprint.to_kicad_mod_entry().contains(
+        "(fp_poly (pts (xy -2.54 -2.54) (xy 2.54 -2.54) (xy 2.54 2.54) (xy -2.54 2.54)) (stroke (width 0.254) (type solid)) (fill none) (layer F.SilkS))"
+    ));
+}
+
+#[test]
+fn test_track_points_with_irregular_formatting() {
+    let data = footprint_data(&[
+        "TRACK~1~3~~  3990   2990\t4.01e3 +2990  4010,3010 ~gge1~0",
+        "TRACK~1~3~~3990 3010 4010 3010 oops 3020~gge2~0",
+    ]);
+    let footprint = import_footprint(&data).unwrap();
+    assert_eq!(
+        footprint.tracks[0].points,
+        [(3990.0, 2990.0), (4010.0, 2990.0), (4010.0, 3010.0)]
+    );
+    assert_eq!(
+        footprint.tracks[1].points,
+        [(3990.0, 3010.0), (4010.0, 3010.0)]
+    );
+}
+
+#[test]
+fn test_cutout_region_becomes_keepout_zone() {
+    let data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+        "SOLIDREGION~1~~M 3995 2995 L 4005 2995 L 4005 3005 L 3995 3005 Z~cutout~gge3~0",
+        "SOLIDREGION~1~~M 3995 2995 L 4005 2995 L 4005 3005 Z~solid~gge4~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.zones.len(), 1);
+    let zone = &footprint.zones[0];
+    assert_eq!(zone.layers, ["F.Cu"]);
+    assert_eq!(zone.points.len(), 4);
+    assert!(!zone.keepout.tracks && !zone.keepout.vias && !zone.keepout.copperpour);
+    assert!(zone.keepout.pads && zone.keepout.footprints);
+    assert!(
+        footprint
+            .warnings
+            .iter()
+            .any(|w| w.contains("solid region")),
+        "{:?}",
+        footprint.warnings
+    );
+
+    let entry = footprint.to_kicad_mod_entry();
+    assert!(entry.contains("(zone (net 0)"), "{}", entry);
+    assert!(entry.contains("(layers F.Cu)"), "{}", entry);
+    assert!(
+        entry.contains(
+            "(keepout (tracks not_allowed) (vias not_allowed) (pads allowed) \
+             (copperpour not_allowed) (footprints allowed))"
+        ),
+        "{}",
+        entry
+    );
+    assert!(entry.contains("(xy -1.27 -1.27)"), "{}", entry);
+
+    // A rounded edge follows the curve instead of being cut to its ends.
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "SOLIDREGION~1~~M 3995 2995 L 4005 2995 A 5 5 0 0 1 3995 2995 Z~cutout~gge2~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let points = &footprint.zones[0].points;
+    assert_eq!(points.len(), 13);
+    for &(x, y) in &points[2..] {
+        assert!((x.hypot(y + 1.27) - 1.27).abs() < 1e-3);
+        assert!(y > -1.27);
+    }
+
+    // Each closed shape of a region is a zone of its own.
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "SOLIDREGION~1~~M 3990 2995 L 3995 2995 L 3995 3005 L 3990 3005 Z \
+         M 4005 2995 L 4010 2995 L 4010 3005 L 4005 3005 Z~cutout~gge2~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.zones.len(), 2);
+    for (zone, x) in footprint.zones.iter().zip([-2.54, 1.27]) {
+        assert_eq!(zone.layers, ["F.Cu"]);
+        assert_eq!(zone.points.len(), 4);
+        assert_near(zone.points[0], (x, -1.27));
+    }
+
+    // Keepouts only apply to copper; a cutout on silkscreen is left out.
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "SOLIDREGION~3~~M 3995 2995 L 4005 2995 L 4005 3005 Z~cutout~gge2~0",
+        "SOLIDREGION~22~~M 3995 2995 L 4005 2995 L 4005 3005 Z~cutout~gge3~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.zones.len(), 1);
+    assert_eq!(footprint.zones[0].layers, ["In2.Cu"]);
+    assert!(
+        footprint
+            .warnings
+            .iter()
+            .any(|w| w.contains("non-copper layer 3")),
+        "{:?}",
+        footprint.warnings
+    );
+}
+
+#[test]
+fn test_missing_footprint() {
+    // Symbol-only parts have no packageDetail at all.
+    let data = symbol_data(&["R~380~290~~~40~20~#880000~1~0~none~gge1~0~"]);
+    assert!(import_symbol(&data).is_ok());
+    match import_footprint(&data) {
+        Err(Error::MissingData(reason)) => assert_eq!(reason, "no footprint"),
+        other => panic!("expected MissingData, got {:?}", other.map(|f| f.info.name)),
+    }
+    assert!(import_3d_model_info(&data).unwrap().is_none());
+}
+
+#[test]
+fn test_assembly_and_document_layers() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "TRACK~1~12~~3990 2990 4010 2990~gge2~0",
+        "TRACK~1~14~~3990 3010 4010 3010~gge3~0",
+        "TRACK~1~19~~3990 2990 3990 3010~gge4~0",
+        "TRACK~1~10~~4010 2990 4010 3010~gge5~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let layers: Vec<&str> = footprint
+        .graphics
+        .iter()
+        .map(|g| g.layer.as_str())
+        .collect();
+    assert_eq!(
+        layers[..4],
+        ["Cmts.User", "B.Fab", "Eco1.User", "Edge.Cuts"]
+    );
+    assert!(footprint.warnings.is_empty(), "{:?}", footprint.warnings);
+
+    let options = ConvertOptions {
+        layer_overrides: [(12, "User.1".to_string())].into_iter().collect(),
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    assert_eq!(footprint.graphics[0].layer, "User.1");
+    assert_eq!(footprint.graphics[1].layer, "B.Fab");
+}
+
+#[test]
+fn test_hidden_pin_numbers_and_names() {
+    let pin = |number: usize, name_shown: u8, number_shown: u8| {
+        format!(
+            "P~show~0~{0}~400~{1}~180~gge{0}~0^^400~{1}^^M 400 {1} h 10~#880000^^{2}~0~0~0~A{0}~start~~~#0000FF^^{3}~0~0~0~{0}~end~~~#0000FF^^0~0~0^^0~M 0 0",
+            number,
+            300 + number * 10,
+            name_shown,
+            number_shown
+        )
+    };
+    let convert = |pins: &[String]| {
+        let pins: Vec<&str> = pins.iter().map(String::as_str).collect();
+        convert_symbol(import_symbol(&symbol_data(&pins)).unwrap()).unwrap()
+    };
+
+    let symbol = convert(&[pin(1, 1, 1), pin(2, 1, 1)]);
+    assert!(!symbol.hide_pin_numbers && !symbol.hide_pin_names);
+    assert!(
+        symbol
+            .to_kicad_lib_entry()
+            .starts_with("(symbol \"TEST_SYM\" (in_bom yes)")
+    );
+
+    // Hidden on every pin: hidden for the symbol. One visible pin keeps them shown.
+    let symbol = convert(&[pin(1, 0, 0), pin(2, 0, 0)]);
+    assert!(symbol.hide_pin_numbers && symbol.hide_pin_names);
+    assert!(
+        symbol
+            .to_kicad_lib_entry()
+            .starts_with("(symbol \"TEST_SYM\" (pin_numbers hide) (pin_names hide) (in_bom yes)")
+    );
+    let symbol = convert(&[pin(1, 0, 0), pin(2, 1, 0)]);
+    assert!(symbol.hide_pin_numbers && !symbol.hide_pin_names);
+
+    // A single-pin symbol such as a test point hides its number anyway.
+    let symbol = convert(&[pin(1, 1, 1)]);
+    assert!(symbol.hide_pin_numbers && !symbol.hide_pin_names);
+}
+
+#[test]
+fn test_multi_line_footprint_text() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "TEXT~L~4000~2990~0.6~0~0~3~~4~PIN 1\nSIDE~M 0 0~~gge2~~0~pinpart",
+        "TEXT~L~4000~3010~0.6~0~0~3~~4~A\r\nB~M 0 0~~gge3~~0~pinpart",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let entry = footprint.to_kicad_mod_entry();
+    assert!(
+        entry.contains("(fp_text user \"PIN 1\\nSIDE\" "),
+        "{}",
+        entry
+    );
+    assert!(entry.contains("(fp_text user \"A\\nB\" "), "{}", entry);
+    // Every element stays on its own line, and the file parses.
+    assert!(
+        entry
+            .lines()
+            .all(|l| l.starts_with("  (") || l.starts_with("(module") || l == ")")
+    );
+    easyeda2kicad_rs::file_writer::validate_sexpr(&entry, &footprint.name).unwrap();
+}
+
+#[test]
+fn test_footprint_unit_sanity_check() {
+    // An 0402 drawn ten times too large: pads 19mm apart.
+    let mut data = footprint_data(&[
+        "PAD~RECT~3962.6~3000~20~24~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3962.6,3000",
+        "PAD~RECT~4037.4~3000~20~24~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4037.4,3000",
+    ]);
+    data["packageDetail"]["title"] = json!("C0402");
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(footprint.warnings.len(), 1, "{:?}", footprint.warnings);
+    assert!(
+        footprint.warnings[0].contains("implausible for a 1mm part")
+            && footprint.warnings[0].contains("unit_scale = 0.0254"),
+        "{}",
+        footprint.warnings[0]
+    );
+
+    let options = ConvertOptions {
+        unit_scale: Some(0.0254),
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    assert!(footprint.warnings.is_empty(), "{:?}", footprint.warnings);
+    assert!((footprint.pads[1].pos.0 - 0.95).abs() < 1e-4);
+    assert!((footprint.pads[1].size.0 - 0.508).abs() < 1e-4);
+
+    // Named by EasyEDA's L/W convention, and the right size.
+    data["packageDetail"]["title"] = json!("R0402_L1.0-W0.5");
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    assert!(footprint.warnings.is_empty(), "{:?}", footprint.warnings);
+}
+
+#[test]
+fn test_symbol_arcs() {
+    // A crystal: two pins, a body and two curved plates bulging away from it.
+    let data = symbol_data(&[
+        "P~show~0~1~370~300~180~gge1~0^^370~300^^M 370 300 h 10~#880000^^1~0~0~0~1~start~~~#0000FF^^1~0~0~0~1~end~~~#0000FF^^0~0~0^^0~M 0 0",
+        "P~show~0~2~430~300~0~gge2~0^^430~300^^M 430 300 h -10~#880000^^1~0~0~0~2~start~~~#0000FF^^1~0~0~0~2~end~~~#0000FF^^0~0~0^^0~M 0 0",
+        "R~396~292~~~8~16~#880000~1~0~none~gge3~0~",
+        "A~M 392 290 A 12 12 0 0 0 392 310~~#880000~1~0~none~gge4~0",
+        "A~M 408 290 A 12 12 0 0 1 408 310~~#880000~1~0~none~gge5~0",
+    ]);
+    let symbol = convert_symbol(import_symbol(&data).unwrap()).unwrap();
+    assert_eq!(symbol.arcs.len(), 2);
+    assert!(symbol.warnings.is_empty(), "{:?}", symbol.warnings);
+    let (left, right) = (&symbol.arcs[0], &symbol.arcs[1]);
+    assert!((left.start.0 - -2.032).abs() < 1e-4, "{:?}", left);
+    assert!((left.start.1 - 2.54).abs() < 1e-4, "{:?}", left);
+    assert!((left.end.1 - -2.54).abs() < 1e-4, "{:?}", left);
+    assert!(left.mid.0 < left.start.0 && right.mid.0 > right.start.0);
+    assert!(left.mid.1.abs() < 1e-4);
+    assert!(
+        symbol
+            .to_kicad_lib_entry()
+            .contains("    (arc (start -2.032 2.54) (mid "),
+        "{}",
+        symbol.to_kicad_lib_entry()
+    );
+
+    // Elliptical arcs have no KiCad equivalent and become a polyline.
+    let data = symbol_data(&["A~M 380 300 A 20 10 0 0 1 420 300~~#880000~1~0~none~gge1~0"]);
+    let symbol = convert_symbol(import_symbol(&data).unwrap()).unwrap();
+    assert!(symbol.arcs.is_empty());
+    assert_eq!(symbol.polylines.len(), 1);
+    assert!(symbol.polylines[0].len() > 2);
+    assert_eq!(symbol.warnings.len(), 1, "{:?}", symbol.warnings);
+    assert!(symbol.to_kicad_lib_entry().contains("(polyline (pts (xy "));
+}
+
+#[test]
+fn test_dimension_annotations() {
+    let data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+        // Extension lines, the measured line and its arrow heads.
+        "DIMENSION~12~M 3990 2990 L 3990 2980 M 4010 2990 L 4010 2980 M 3990 2982 L 4010 2982 M 3990 2982 L 3992 2981 M 4010 2982 L 4008 2981~gge3~0",
+        "PROTRACTOR~12~M 4000 3000 L 4010 3000 M 4000 3000 L 4010 2990~gge4~0",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let comments: Vec<&FpGraphic> = footprint
+        .graphics
+        .iter()
+        .filter(|g| g.layer == "Cmts.User")
+        .collect();
+    assert_eq!(comments.len(), 7);
+    let labels: Vec<&str> = footprint
+        .texts
+        .iter()
+        .filter(|t| t.layer == "Cmts.User")
+        .map(|t| t.text.as_str())
+        .collect();
+    assert_eq!(labels, ["5.08mm"]);
+    assert!(footprint.warnings.is_empty(), "{:?}", footprint.warnings);
+
+    let options = ConvertOptions {
+        skip_dimensions: true,
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    assert!(footprint.graphics.iter().all(|g| g.layer != "Cmts.User"));
+    assert!(footprint.texts.iter().all(|t| t.layer != "Cmts.User"));
+}
+
+#[test]
+fn test_kicad5_footprint_syntax() {
+    use easyeda2kicad_rs::kicad_models::KiCadFormat;
+
+    let data = footprint_data(&[
+        "ARC~1~3~~M 3990 3000 A 10 10 0 0 1 4010 3000~~gge1~0",
+        "SOLIDREGION~1~~M 3990 2990 L 4010 2990 L 4010 3010 Z~cutout~gge2~0",
+    ]);
+    let ee_footprint = import_footprint(&data).unwrap();
+    let mut footprint = convert_footprint(ee_footprint, None).unwrap();
+    footprint.graphics.push(FpGraphic {
+        graphic_type: FpGraphicType::Rect {
+            start: (-1.0, -1.0),
+            end: (1.0, 1.0),
+        },
+        layer: "F.Fab".to_string(),
+        width: 0.1,
+    });
+    footprint.exclude_from_pos = true;
+
+    let entry = footprint.to_kicad_mod_entry_with_format(
+        "../3dmodels.3dshapes",
+        Model3dFormat::default(),
+        KiCadFormat::V5,
+    );
+    assert!(entry.starts_with("(module TEST_FP (layer F.Cu) (tedit 0)\n  (attr virtual)\n"));
+    assert!(!entry.contains("(stroke"));
+    assert!(!entry.contains("fp_rect"));
+    assert!(!entry.contains("(zone"));
+    assert!(entry.contains("(fp_line (start -1 -1) (end 1 -1) (layer F.Fab) (width 0.1))"));
+    assert!(entry.contains("(fp_line (start -1 1) (end -1 -1) (layer F.Fab) (width 0.1))"));
+    assert!(entry.contains("(end -2.54 0) (angle 180) (layer F.SilkS) (width 0.254))"));
+
+    // Later formats are unaffected.
+    let modern = footprint.to_kicad_mod_entry_with_format(
+        "../3dmodels.3dshapes",
+        Model3dFormat::default(),
+        KiCadFormat::V6,
+    );
+    assert_eq!(modern, footprint.to_kicad_mod_entry());
+    assert!(modern.contains("(fp_rect") && modern.contains("(zone"));
+}
+
+#[test]
+fn test_footprint_rotation_is_composed_with_pads() {
+    let data = json!({
+        "packageDetail": {
+            "title": "ROTATED",
+            "dataStr": {
+                "head": { "x": "4000", "y": "3000", "rotation": "45" },
+                "shape": [
+                    "PAD~RECT~3990~3000~4~2~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+                    "PAD~RECT~4010~3000~4~2~1~~2~0~~30~gge2~0~~Y~0~0~0.4~4010,3000",
+                    "TRACK~1~3~~3990 3010 4010 3010~gge3~0",
+                ],
+            }
+        }
+    });
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+
+    // EasyEDA turns clockwise on screen, so with Y down pad 2 swings from the right
+    // to the lower right, and both pads turn by -45 on top of their own rotation.
+    let d = 2.54 * std::f32::consts::FRAC_1_SQRT_2;
+    let pad = |n: &str| footprint.pads.iter().find(|p| p.number == n).unwrap();
+    assert_near(pad("1").pos, (-d, -d));
+    assert_near(pad("2").pos, (d, d));
+    assert!((pad("1").rotation - 315.0).abs() < 1e-3);
+    assert!((pad("2").rotation - 285.0).abs() < 1e-3);
+
+    let track = footprint
+        .graphics
+        .iter()
+        .find_map(|g| match g.graphic_type {
+            FpGraphicType::Line { start, end } if g.layer == "F.SilkS" => Some((start, end)),
+            _ => None,
+        })
+        .unwrap();
+    assert_near(track.0, (-2.0 * d, 0.0));
+    assert_near(track.1, (0.0, 2.0 * d));
+}
+
+#[test]
+fn test_modern_footprint_token() {
+    use easyeda2kicad_rs::kicad_models::KiCadFormat;
+
+    let data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~2~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4010~3000~4~2~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    let entry = |format| {
+        footprint.to_kicad_mod_entry_with_format(
+            "../3dmodels.3dshapes",
+            Model3dFormat::default(),
+            format,
+        )
+    };
+
+    let v7 = entry(KiCadFormat::V7);
+    assert!(v7.starts_with(
+        "(footprint \"TEST_FP\" (version 20221018) (generator easyeda2kicad_rs)\n  (layer \"F.Cu\")\n  (tstamp "
+    ));
+    assert!(v7.contains("\n  (attr smd)\n"));
+    assert_eq!(v7, entry(KiCadFormat::V7), "the tstamp must be stable");
+
+    let v8 = entry(KiCadFormat::V8);
+    assert!(v8.starts_with(
+        "(footprint \"TEST_FP\" (version 20240108) (generator \"easyeda2kicad_rs\") (generator_version \"8.0\")\n"
+    ));
+    assert!(v8.contains("\n  (uuid \"") && !v8.contains("tstamp"));
+
+    // V6 output is unchanged.
+    assert!(entry(KiCadFormat::V6).starts_with("(module TEST_FP (layer F.Cu)\n"));
+    assert!(!entry(KiCadFormat::V6).contains("(attr"));
+}
+
+#[test]
+fn test_silk_width_options() {
+    let data = footprint_data(&[
+        "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000",
+        "TRACK~0~3~~3990 2990 4010 2990~gge2~0",
+        "TRACK~0.001~3~~3990 2995 4010 2995~gge3~0",
+        "TRACK~10~3~~3990 3005 4010 3005~gge4~0",
+        "TRACK~10~13~~3990 3010 4010 3010~gge5~0",
+        "TRACK~10~12~~3990 3015 4010 3015~gge6~0",
+    ]);
+    let options = ConvertOptions {
+        silk_width: Some(0.15),
+        silk_width_range: Some((0.1, 0.5)),
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    let widths: Vec<(&str, f32)> = footprint
+        .graphics
+        .iter()
+        .filter(|g| matches!(g.graphic_type, FpGraphicType::Line { .. }))
+        .map(|g| (g.layer.as_str(), g.width))
+        .collect();
+    assert_eq!(
+        widths,
+        [
+            ("F.SilkS", 0.15),
+            ("F.SilkS", 0.1),
+            ("F.SilkS", 0.5),
+            ("F.Fab", 0.5),
+            // Other layers aren't limited.
+            ("Cmts.User", 2.54),
+        ]
+    );
+}
+
+#[test]
+fn test_import_head() {
+    use easyeda2kicad_rs::{
+        easyeda_models::{EeBBox, EeHead},
+        importer::import_head,
+    };
+
+    let data_str = json!({
+        "head": { "x": "4000", "y": 3000.5, "rotation": "90" },
+        "canvas": "CA~1000~1000~#000000~yes~#FFFFFF~10~1000~1000~line~0.5~mil~1~45~visible~0.5~4000~3000~1~yes",
+        "BBox": { "x": 3990, "y": "2995", "width": 20, "height": "10" },
+    });
+    assert_eq!(
+        import_head(&data_str),
+        EeHead {
+            origin: (4000.0, 3000.5),
+            rotation: 90.0,
+            bbox: Some(EeBBox {
+                x: 3990.0,
+                y: 2995.0,
+                width: 20.0,
+                height: 10.0
+            }),
+            grid_size: Some(10.0),
+            unit: Some("mil".to_string()),
+        }
+    );
+
+    // Missing or unreadable values fall back instead of failing.
+    let head = import_head(&json!({
+        "head": { "x": "abc" },
+        "canvas": "CA~1000",
+        "BBox": { "x": 1, "y": 2 },
+    }));
+    assert_eq!(head, EeHead::default());
+    assert_eq!(import_head(&json!({})), EeHead::default());
+}
+
+#[test]
+fn test_footprint_primitives_convert_in_isolation() {
+    use easyeda2kicad_rs::{
+        converter::{FootprintContext, convert_circle, convert_hole, convert_pad, convert_track},
+        easyeda_models::{EeFootprintCircle, EeFootprintHole, EeFootprintPad, EeFootprintTrack},
+        kicad_models::FpShape,
+    };
+
+    let options = ConvertOptions::default();
+    // Canvas origin at (4000, 3000), footprint centered 1mm to the right of it.
+    let mut ctx = FootprintContext::new((4000.0, 3000.0), (1.0, 0.0), &options);
+
+    let pad = EeFootprintPad {
+        shape: "OVAL".to_string(),
+        center_x: 4010.0,
+        center_y: 3005.0,
+        width: 4.0,
+        height: 8.0,
+        layer_id: 11,
+        hole_radius: 1.0,
+        rotation: 90.0,
+        ..Default::default()
+    };
+    let ki_pad = convert_pad(&pad, "7".to_string(), &mut ctx);
+    assert_eq!(ki_pad.number, "7");
+    assert_eq!(ki_pad.pad_type, "thru_hole");
+    assert!(matches!(ki_pad.shape, FpShape::Oval));
+    assert_near(ki_pad.pos, (2.54 - 1.0, 1.27));
+    // A quarter turn is folded into the size.
+    assert_near(ki_pad.size, (2.032, 1.016));
+    assert_eq!(ki_pad.rotation, 0.0);
+    assert_eq!(ki_pad.drill, Some(0.508));
+
+    let hole = EeFootprintHole {
+        center_x: 3990.0,
+        center_y: 3000.0,
+        radius: 5.0,
+    };
+    let ki_hole = convert_hole(&hole, &ctx);
+    assert_eq!(ki_hole.pad_type, "np_thru_hole");
+    assert_near(ki_hole.pos, (-2.54 - 1.0, 0.0));
+    assert_eq!(ki_hole.drill, Some(2.54));
+
+    let track = EeFootprintTrack {
+        stroke_width: 1.0,
+        layer_id: 3,
+        points: vec![(4000.0, 3000.0), (4010.0, 3000.0), (4010.0, 3010.0)],
+    };
+    let lines = convert_track(&track, &mut ctx);
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|l| l.layer == "F.SilkS"));
+
+    // Circles off the copper and silkscreen layers are left out, without a note.
+    let circle = EeFootprintCircle {
+        center_x: 4000.0,
+        center_y: 3000.0,
+        radius: 5.0,
+        layer_id: 13,
+        ..Default::default()
+    };
+    assert!(convert_circle(&circle, &mut ctx).is_none());
+
+    // Graphics far off the footprint are counted as dropped.
+    let far = EeFootprintTrack {
+        points: vec![(9000.0, 3000.0), (9010.0, 3000.0)],
+        ..track
+    };
+    assert!(convert_track(&far, &mut ctx).is_empty());
+    assert_eq!(ctx.out_of_range, 1);
+    assert!(ctx.notes.is_empty());
+    assert!(ctx.unmapped_layers.is_empty());
+}
+
+#[test]
+fn test_pad_rotation_in_radians() {
+    let pad = |rotation: &str| {
+        format!(
+            "PAD~RECT~4000~3000~4~8~1~~1~0~~{}~gge1~0~~Y~0~0~0.4~4000,3000",
+            rotation
+        )
+    };
+    let rotation_of = |rotation: &str| {
+        let footprint = import_footprint(&footprint_data(&[&pad(rotation)])).unwrap();
+        footprint.pads[0].rotation
+    };
+    // Radians, as some footprints store them.
+    assert_eq!(rotation_of("1.5708"), 90.0);
+    assert_eq!(rotation_of("1.57"), 90.0);
+    assert_eq!(rotation_of("3.14159"), 180.0);
+    assert_eq!(rotation_of("0.785"), 45.0);
+    // Degrees, including fractional ones and small whole angles.
+    assert_eq!(rotation_of("90"), 90.0);
+    assert_eq!(rotation_of("1.5"), 1.5);
+    assert_eq!(rotation_of("6"), 6.0);
+    assert_eq!(rotation_of("0"), 0.0);
+
+    // A pad at π/2 comes out like one at 90°.
+    let size = |rotation: &str| {
+        let data = footprint_data(&[&pad(rotation)]);
+        convert_footprint(import_footprint(&data).unwrap(), None)
+            .unwrap()
+            .pads[0]
+            .size
+    };
+    assert_near(size("1.5708"), size("90"));
+    assert_near(size("1.5708"), (2.032, 1.016));
+}
+
+#[test]
+fn test_strict_import_rejects_unsupported_shapes() {
+    use easyeda2kicad_rs::importer::{import_footprint_with_options, import_symbol_with_options};
+
+    let strict = ConvertOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let data = footprint_data(&[pad, "VIA~4000~3000~2~~0.5~gge2~0"]);
+    // Skipped with a warning by default...
+    let lenient = import_footprint(&data).unwrap();
+    assert_eq!(lenient.unsupported_shapes, ["VIA"]);
+    // ...and an error in strict mode.
+    match import_footprint_with_options(&data, &strict) {
+        Err(Error::Unsupported(msg)) => assert!(msg.contains("'VIA'"), "{}", msg),
+        other => panic!("expected Unsupported, got {:?}", other.map(|_| ())),
+    }
+    assert!(import_footprint_with_options(&footprint_data(&[pad]), &strict).is_ok());
+
+    let symbol = |shapes: &[&str]| {
+        json!({
+            "dataStr": {
+                "head": { "x": "0", "y": "0", "c_para": { "name": "TEST" } },
+                "shape": shapes,
+            }
+        })
+    };
+    let rect = "R~-10~-10~2~2~20~20~#880000~1~0~none~gge1~0~";
+    let polyline = "PL~0 0 10 10~#880000~1~0~none~gge2~0";
+    assert!(import_symbol(&symbol(&[rect, polyline])).is_ok());
+    assert!(import_symbol_with_options(&symbol(&[rect]), &strict).is_ok());
+    assert!(matches!(
+        import_symbol_with_options(&symbol(&[rect, polyline]), &strict),
+        Err(Error::Unsupported(msg)) if msg.contains("'PL'")
+    ));
+}
+
+#[test]
+fn test_svgnode_silkscreen_logo() {
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let logo = r##"SVGNODE~{"gId":"g2","nodeName":"g","nodeType":1,"layerid":"3","attrs":{"id":"g2","layerid":"3"},"childNodes":[{"gId":"p1","nodeName":"path","nodeType":1,"attrs":{"d":"M 3990 2990 L 3994 2990 L 3992 2994 Z M 3996 2990 L 4000 2990 L 3998 2994 Z","fill":"#FFCC00","stroke":"none"}},{"gId":"p2","nodeName":"path","nodeType":1,"attrs":{"d":"M 3990 2996 L 4000 2996","fill":"none","stroke-width":"1"}}]}"##;
+    let disc = r##"SVGNODE~{"gId":"g3","nodeName":"g","nodeType":1,"layerid":"3","attrs":{"id":"g3","layerid":"3"},"childNodes":[{"gId":"p3","nodeName":"path","nodeType":1,"attrs":{"d":"M 3990 3004 A 2 2 0 0 1 3994 3004 A 2 2 0 0 1 3990 3004 Z","fill":"#FFCC00","stroke":"none"}}]}"##;
+    let model = r##"SVGNODE~{"gId":"g1","nodeName":"g","nodeType":1,"layerid":"19","attrs":{"uuid":"abc","title":"MODEL","c_etype":"outline3D","layerid":"19"},"childNodes":[{"nodeName":"path","attrs":{"d":"M 3990 2990 L 4010 2990","fill":"none"}}]}"##;
+    let data = footprint_data(&[pad, logo, disc, model]);
+
+    let ee_footprint = import_footprint(&data).unwrap();
+    assert!(ee_footprint.unsupported_shapes.is_empty());
+    // The model's own outline is not artwork.
+    assert_eq!(ee_footprint.svg_paths.len(), 3);
+    assert_eq!(ee_footprint.svg_paths[0].layer_id, 3);
+    assert!(ee_footprint.svg_paths[0].filled);
+    assert!(!ee_footprint.svg_paths[1].filled);
+    // Only the 3D node is a model.
+    let models = import_3d_model_infos(&data).unwrap();
+    assert_eq!(models.len(), 1);
+    assert_eq!(models[0].uuid, "abc");
+
+    let footprint = convert_footprint(ee_footprint, None).unwrap();
+    // Besides the pin 1 marker.
+    let silk: Vec<&FpGraphic> = footprint
+        .graphics
+        .iter()
+        .filter(|g| g.layer == "F.SilkS")
+        .filter(|g| !matches!(g.graphic_type, FpGraphicType::Circle { .. }))
+        .collect();
+    // Two filled triangles, one stroked line and a filled disc.
+    assert_eq!(silk.len(), 4);
+    for triangle in &silk[..2] {
+        match &triangle.graphic_type {
+            FpGraphicType::Poly { points, filled } => {
+                assert!(filled);
+                assert_eq!(points.len(), 3);
+            }
+            other => panic!("expected a filled polygon, got {:?}", other),
+        }
+    }
+    match &silk[0].graphic_type {
+        FpGraphicType::Poly { points, .. } => assert_near(points[2], (-2.032, -1.524)),
+        _ => unreachable!(),
+    }
+    match &silk[2].graphic_type {
+        FpGraphicType::Line { start, end } => {
+            assert_near(*start, (-2.54, -1.016));
+            assert_near(*end, (0.0, -1.016));
+        }
+        other => panic!("expected a line, got {:?}", other),
+    }
+    assert!((silk[2].width - 0.254).abs() < 1e-4);
+    // The disc's two half-circle arcs are traced along the curve, not cut to chords.
+    match &silk[3].graphic_type {
+        FpGraphicType::Poly { points, filled } => {
+            assert!(filled);
+            assert_eq!(points.len(), 24);
+            for &(x, y) in points {
+                assert!(((x + 2.032).hypot(y - 1.016) - 0.508).abs() < 1e-3);
+            }
+        }
+        other => panic!("expected a filled polygon, got {:?}", other),
+    }
+
+    let out = footprint.to_kicad_mod_entry();
+    assert!(out.contains("(fill solid)"), "{}", out);
+}
+
+#[test]
+fn test_footprint_netlist() {
+    let data = footprint_data(&[
+        "PAD~RECT~3990~3000~4~4~1~VIN~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000",
+        "PAD~RECT~4000~3000~4~4~1~~2~0~~0~gge2~0~~Y~0~0~0.4~4000,3000",
+        "PAD~RECT~4010~3000~4~4~1~ INT_SW ~3~0~~0~gge3~0~~Y~0~0~0.4~4010,3000",
+        // The same pad on the bottom layer, merged into pad 3.
+        "PAD~RECT~4010~3000~4~4~2~INT_SW~3~0~~0~gge4~0~~Y~0~0~0.4~4010,3000",
+    ]);
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert_eq!(
+        footprint.netlist(),
+        [
+            ("1".to_string(), "VIN".to_string()),
+            ("3".to_string(), "INT_SW".to_string())
+        ]
+    );
+    // Nets stay out of the footprint file itself.
+    assert!(!footprint.to_kicad_mod_entry().contains("VIN"));
+}
+
+#[test]
+fn test_symbol_rectangle_stroke_width() {
+    let stroke = |rect: &str| {
+        let symbol = convert_symbol(import_symbol(&symbol_data(&[rect])).unwrap()).unwrap();
+        symbol.rectangles[0].stroke_width
+    };
+    assert!((stroke("R~390~290~2~2~20~20~#880000~2~0~none~gge1~0~") - 0.508).abs() < 1e-4);
+    // A hairline outline stays thin, an absurdly bold one is clamped.
+    assert!((stroke("R~390~290~2~2~20~20~#880000~0.5~0~none~gge1~0~") - 0.127).abs() < 1e-4);
+    assert!((stroke("R~390~290~2~2~20~20~#880000~40~0~none~gge1~0~") - 1.27).abs() < 1e-4);
+    // No usable width: KiCad's usual 0.254mm.
+    assert!((stroke("R~390~290~2~2~20~20") - 0.254).abs() < 1e-4);
+    assert!((stroke("R~390~290~2~2~20~20~#880000~~0~none~gge1~0~") - 0.254).abs() < 1e-4);
+
+    let symbol = convert_symbol(
+        import_symbol(&symbol_data(&[
+            "R~390~290~2~2~20~20~#880000~2~0~none~gge1~0~",
+        ]))
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(
+        symbol
+            .to_kicad_lib_entry()
+            .contains("(stroke (width 0.508)")
+    );
+}
+
+#[test]
+fn test_symbol_rectangle_fill() {
+    use easyeda2kicad_rs::kicad_models::FillType;
+
+    let fill = |rect: &str| {
+        let symbol = convert_symbol(import_symbol(&symbol_data(&[rect])).unwrap()).unwrap();
+        symbol.rectangles[0].fill
+    };
+    assert_eq!(
+        fill("R~390~290~2~2~20~20~#880000~1~0~none~gge1~0~"),
+        FillType::None
+    );
+    assert_eq!(
+        fill("R~390~290~2~2~20~20~#880000~1~0~#880000~gge1~0~"),
+        FillType::Solid
+    );
+    assert_eq!(
+        fill("R~390~290~2~2~20~20~#880000~1~0~#FFFFFF~gge1~0~"),
+        FillType::Background
+    );
+    // Rectangles that don't say keep KiCad's usual body fill.
+    assert_eq!(fill("R~390~290~2~2~20~20"), FillType::Background);
+
+    let symbol = convert_symbol(
+        import_symbol(&symbol_data(&[
+            "R~390~290~2~2~20~20~#880000~1~0~none~gge1~0~",
+        ]))
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(symbol.to_kicad_lib_entry().contains("(fill (type none))"));
+}
+
+#[test]
+fn test_ipc_fab_outline_and_courtyard() {
+    let data = footprint_data(&[
+        "PAD~RECT~3990~2995~4~2~1~~1~0~~0~gge1~0~~Y~0~0~0.4~3990,2995",
+        "PAD~RECT~3990~3005~4~2~1~~2~0~~0~gge2~0~~Y~0~0~0.4~3990,3005",
+        "PAD~RECT~4010~3005~4~2~1~~3~0~~0~gge3~0~~Y~0~0~0.4~4010,3005",
+        "PAD~RECT~4010~2995~4~2~1~~4~0~~0~gge4~0~~Y~0~0~0.4~4010,2995",
+        // EasyEDA's own fab outline, replaced; its silkscreen stays.
+        "TRACK~1~13~~3980 2980 4020 2980~gge5~0",
+        "TRACK~1~3~~3995 2990 4005 2990~gge6~0",
+    ]);
+    let options = ConvertOptions {
+        fab_style: FabStyle::Ipc,
+        ..Default::default()
+    };
+    let footprint =
+        convert_footprint_with_options(import_footprint(&data).unwrap(), None, &options).unwrap();
+    let on = |layer: &str| -> Vec<&FpGraphic> {
+        footprint
+            .graphics
+            .iter()
+            .filter(|g| g.layer == layer)
+            .collect()
+    };
+
+    let fab = on("F.Fab");
+    assert_eq!(fab.len(), 1);
+    match &fab[0].graphic_type {
+        FpGraphicType::Poly { points, filled } => {
+            assert!(!filled);
+            // Pads reach ±3.048 x ±1.524; the pin 1 corner (top left) is cut by a
+            // quarter of the 3.048mm height.
+            let expected = [
+                (-2.286, -1.524),
+                (3.048, -1.524),
+                (3.048, 1.524),
+                (-3.048, 1.524),
+                (-3.048, -0.762),
+            ];
+            assert_eq!(points.len(), expected.len());
+            for (&point, &expected) in points.iter().zip(expected.iter()) {
+                assert_near(point, expected);
+            }
+        }
+        other => panic!("expected a chamfered outline, got {:?}", other),
+    }
+
+    let courtyard = on("F.CrtYd");
+    assert_eq!(courtyard.len(), 1);
+    match &courtyard[0].graphic_type {
+        FpGraphicType::Rect { start, end } => {
+            assert_near(*start, (-3.30, -1.78));
+            assert_near(*end, (3.30, 1.78));
+        }
+        other => panic!("expected a courtyard rectangle, got {:?}", other),
+    }
+    assert!(
+        on("F.SilkS")
+            .iter()
+            .any(|g| matches!(g.graphic_type, FpGraphicType::Line { .. }))
+    );
+
+    // The default keeps what EasyEDA drew.
+    let footprint = convert_footprint(import_footprint(&data).unwrap(), None).unwrap();
+    assert!(footprint.graphics.iter().all(|g| g.layer != "F.CrtYd"));
+    assert!(footprint.graphics.iter().any(|g| g.layer == "F.Fab"));
+}
+
+#[test]
+fn test_repeated_shapes_are_imported_once() {
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    // A second pad 1 that isn't a verbatim repeat is kept.
+    let other = "PAD~RECT~4010~3000~4~4~1~~1~0~~0~gge2~0~~Y~0~0~0.4~4010,3000";
+    let footprint = import_footprint(&footprint_data(&[pad, pad, other])).unwrap();
+    assert_eq!(footprint.pads.len(), 2);
+
+    let pin = "P~show~0~1~360~300~180~gge3~0^^360~300^^M 360 300 h 10~#880000^^1~373~304~0~A~start~~~#0000FF^^1~368~299~0~1~end~~~#0000FF^^0~363~300^^0~M 360 303 L 357 300 L 360 297";
+    let symbol = import_symbol(&symbol_data(&[pin, pin])).unwrap();
+    assert_eq!(symbol.pins.len(), 1);
+}
+
+#[test]
+fn test_filled_circles() {
+    let pad = "PAD~RECT~4000~3000~4~4~1~~1~0~~0~gge1~0~~Y~0~0~0.4~4000,3000";
+    let silk_dot = "CIRCLE~3~1~4010~3000~2~gge2~0~1";
+    let copper_disc = "CIRCLE~1~1~3990~3000~4~gge3~0~1";
+    let outline = "CIRCLE~3~1~4000~3010~5~gge4";
+    let ee = import_footprint(&footprint_data(&[pad, silk_dot, copper_disc, outline])).unwrap();
+    assert_eq!(
+        ee.circles.iter().map(|c| c.filled).collect::<Vec<_>>(),
+        [true, true, false]
+    );
+
+    let fp = convert_footprint(ee, None).unwrap();
+    let circles: Vec<_> = fp
+        .graphics
+        .iter()
+        .filter_map(|g| match g.graphic_type {
+            FpGraphicType::Circle { center, filled, .. } if g.layer == "F.SilkS" => {
+                Some((center, filled))
+            }
+            _ => None,
+        })
+        .collect();
+    assert!(circles.contains(&((2.54, 0.0), true)));
+    assert!(circles.contains(&((0.0, 2.54), false)));
+    assert!(!fp.graphics.iter().any(|g| g.layer == "F.Cu"));
+
+    let disc = fp.pads.iter().find(|p| p.number.is_empty()).unwrap();
+    assert_eq!(disc.pad_type, "smd");
+    assert_near(disc.pos, (-2.54, 0.0));
+    // Radius 4 plus half the 1-unit stroke, in mils.
+    assert_near(disc.size, (2.286, 2.286));
+    assert_eq!(disc.layers, ["F.Cu"]);
+
+    let content = fp.to_kicad_mod_entry();
+    assert_eq!(content.matches("(fill solid) (layer F.SilkS))").count(), 1);
+    assert!(
+        content.contains("(pad \"\" smd circle (at -2.54 0 0) (size 2.286 2.286) (layers F.Cu))")
+    );
+
+    // Several unnumbered discs aren't duplicate pad numbers.
+    let second_disc = "CIRCLE~1~1~4020~3000~4~gge5~0~1";
+    let ee = import_footprint(&footprint_data(&[pad, copper_disc, second_disc])).unwrap();
+    let options = ConvertOptions {
+        duplicate_numbers_are_errors: true,
+        ..Default::default()
+    };
+    let fp = convert_footprint_with_options(ee.clone(), None, &options).unwrap();
+    assert_eq!(fp.pads.iter().filter(|p| p.number.is_empty()).count(), 2);
+    let fp = convert_footprint(ee, None).unwrap();
+    assert!(fp.warnings.is_empty(), "{:?}", fp.warnings);
+}
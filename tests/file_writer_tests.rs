@@ -0,0 +1,490 @@
+use easyeda2kicad_rs::{
+    converter::{convert_footprint, convert_symbol},
+    error::Error,
+    file_writer::{KicadLibrary, validate_sexpr},
+    importer::{import_footprint, import_symbol},
+    kicad_models::{
+        FpText, Ki3dModel, KiCadFormat, KiFootprint, KiSymbol, LibraryLayout, Model3dFormat,
+        Model3dNaming, Model3dPathMode, SymbolLayout,
+    },
+};
+use glam::Vec3;
+use serde_json::json;
+use std::{fs, path::PathBuf};
+
+/// Returns a fresh, empty scratch directory for a test.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("easyeda2kicad_rs_{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Builds a minimal one-pin symbol through the normal import path.
+fn symbol(name: &str) -> KiSymbol {
+    let data = json!({
+        "dataStr": {
+            "head": { "x": "0", "y": "0", "c_para": { "name": name, "pre": "U?" } },
+            "shape": ["P~show~0~1~-10~0~180~gge1~0^^-10~0^^M -10 0 h 10~#880000^^1~-3~4~0~IN~start~~~#0000FF^^1~-8~-1~0~1~end~~~#0000FF^^0~-7~0^^0~M 0 3 L 3 0 L 0 -3"],
+        }
+    });
+    convert_symbol(import_symbol(&data).unwrap()).unwrap()
+}
+
+fn footprint_with_model(name: &str) -> KiFootprint {
+    KiFootprint {
+        name: name.to_string(),
+        pads: vec![],
+        texts: vec![],
+        graphics: vec![],
+        zones: vec![],
+        model_3d: Some(Ki3dModel {
+            name: "MODEL".to_string(),
+            wrl_data: None,
+            step_data: None,
+            offset: Vec3::ZERO,
+            scale: Vec3::ONE,
+            rotate: Vec3::ZERO,
+            origin: None,
+            warnings: vec![],
+        }),
+        bottom_side: false,
+        exclude_from_bom: false,
+        exclude_from_pos: false,
+        warnings: vec![],
+    }
+}
+
+#[test]
+fn test_model_path_modes() {
+    let footprint = footprint_with_model("FP");
+    assert!(
+        footprint
+            .to_kicad_mod_entry()
+            .contains("(model \"../3dmodels.3dshapes/MODEL.wrl\"")
+    );
+
+    let dir = scratch_dir("model_path_modes");
+    let mut lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+
+    lib.model_path_mode = Model3dPathMode::Absolute;
+    lib.add_footprint(&footprint).unwrap();
+    let content = fs::read_to_string(dir.join("footprints.pretty/FP.kicad_mod")).unwrap();
+    let expected = dir.join("3dmodels.3dshapes").canonicalize().unwrap();
+    assert!(content.contains(&format!(
+        "(model \"{}/MODEL.wrl\"",
+        expected.to_string_lossy().replace('\\', "/")
+    )));
+
+    lib.model_path_mode = Model3dPathMode::KiprojVar;
+    lib.add_footprint(&footprint).unwrap();
+    let content = fs::read_to_string(dir.join("footprints.pretty/FP.kicad_mod")).unwrap();
+    assert!(content.contains("(model \"${KIPRJMOD}/3dmodels.3dshapes/MODEL.wrl\""));
+}
+
+#[test]
+fn test_library_layout() {
+    let dir = scratch_dir("library_layout");
+    let mut lib = KicadLibrary::new(&dir);
+    lib.layout = LibraryLayout::with_nickname("acme");
+    lib.layout.symbols_dir = "acme_symbols".to_string();
+    lib.setup_directories().unwrap();
+    assert!(dir.join("acme.pretty").is_dir());
+    assert!(dir.join("acme.3dshapes").is_dir());
+    assert!(!dir.join("footprints.pretty").exists());
+
+    lib.add_footprint(&footprint_with_model("FP")).unwrap();
+    let content = fs::read_to_string(dir.join("acme.pretty/FP.kicad_mod")).unwrap();
+    assert!(content.contains("(model \"../acme.3dshapes/MODEL.wrl\""));
+    assert_eq!(
+        lib.symbol_lib_path(),
+        dir.join("acme_symbols/lib.kicad_sym")
+    );
+    assert_eq!(
+        lib.step_model_path("MODEL"),
+        dir.join("acme.3dshapes/MODEL.step")
+    );
+
+    lib.layout.model_ref = "${ACME_3DMODELS}".to_string();
+    lib.add_footprint(&footprint_with_model("FP")).unwrap();
+    let content = fs::read_to_string(dir.join("acme.pretty/FP.kicad_mod")).unwrap();
+    assert!(content.contains("(model \"${ACME_3DMODELS}/MODEL.wrl\""));
+
+    lib.model_path_mode = Model3dPathMode::KiprojVar;
+    lib.add_footprint(&footprint_with_model("FP")).unwrap();
+    let content = fs::read_to_string(dir.join("acme.pretty/FP.kicad_mod")).unwrap();
+    assert!(content.contains("/acme.3dshapes/MODEL.wrl\""));
+}
+
+#[test]
+fn test_add_step_file_moves_into_library() {
+    let dir = scratch_dir("step_file");
+    let lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+
+    let tmp = dir.join("download.part");
+    fs::write(&tmp, b"ISO-10303-21;").unwrap();
+    lib.add_3d_model_step_file("MODEL", &tmp).unwrap();
+
+    assert!(!tmp.exists());
+    assert_eq!(
+        fs::read(dir.join("3dmodels.3dshapes/MODEL.step")).unwrap(),
+        b"ISO-10303-21;"
+    );
+}
+
+#[test]
+fn test_add_raw_data() {
+    let dir = scratch_dir("raw_data");
+    let lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+
+    let data = json!({ "title": "NE555", "dataStr": { "shape": ["R~0~0"] } });
+    let path = lib.add_raw_data("C2040", &data).unwrap();
+    assert_eq!(path, dir.join("raw/C2040.json"));
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("\n  \"title\": \"NE555\""), "{}", saved);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&saved).unwrap(),
+        data
+    );
+}
+
+#[test]
+fn test_add_netlist() {
+    let dir = scratch_dir("netlist");
+    let lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+
+    let data = |nets: [&str; 2]| {
+        json!({
+            "packageDetail": {
+                "title": "MODULE",
+                "dataStr": {
+                    "head": { "x": "4000", "y": "3000" },
+                    "shape": [
+                        format!("PAD~RECT~3990~3000~4~4~1~{}~1~0~~0~gge1~0~~Y~0~0~0.4~3990,3000", nets[0]),
+                        format!("PAD~RECT~4010~3000~4~4~1~{}~2~0~~0~gge2~0~~Y~0~0~0.4~4010,3000", nets[1]),
+                    ],
+                }
+            }
+        })
+    };
+    let footprint = |nets| convert_footprint(import_footprint(&data(nets)).unwrap(), None).unwrap();
+
+    let path = lib.add_netlist(&footprint(["GND", "LX"])).unwrap().unwrap();
+    assert_eq!(path, dir.join("nets/MODULE.tsv"));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "pad\tnet\n1\tGND\n2\tLX\n"
+    );
+
+    // Nothing to save for footprints without nets.
+    fs::remove_dir_all(dir.join("nets")).unwrap();
+    assert_eq!(lib.add_netlist(&footprint(["", ""])).unwrap(), None);
+    assert!(!dir.join("nets").exists());
+}
+
+#[test]
+fn test_validate_sexpr() {
+    assert!(validate_sexpr("(a (b \"c)\" d) (e))", "ok").is_ok());
+    assert!(validate_sexpr("(a \"escaped \\\" quote\")", "ok").is_ok());
+
+    for bad in ["(a (b)", "(a))", "(a \"b)", ")("] {
+        match validate_sexpr(bad, "BAD_PART") {
+            Err(Error::ParseError(msg)) => assert!(msg.contains("BAD_PART"), "{}", msg),
+            other => panic!("{:?} accepted: {:?}", bad, other),
+        }
+    }
+}
+
+#[test]
+fn test_unbalanced_footprint_is_not_written() {
+    let dir = scratch_dir("unbalanced");
+    let lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+
+    // Text is quoted as needed, but layer names are written as they are.
+    let mut footprint = footprint_with_model("BROKEN");
+    footprint.texts.push(FpText {
+        text_type: "user".to_string(),
+        text: "oops".to_string(),
+        pos: (0.0, 0.0),
+        layer: "F.Fab(".to_string(),
+    });
+
+    assert!(matches!(
+        lib.add_footprint(&footprint),
+        Err(Error::ParseError(_))
+    ));
+    assert!(!dir.join("footprints.pretty/BROKEN.kicad_mod").exists());
+}
+
+#[test]
+fn test_merge_from_skips_duplicates() {
+    let master_dir = scratch_dir("merge_master");
+    let project_dir = scratch_dir("merge_project");
+    let master = KicadLibrary::new(&master_dir);
+    let project = KicadLibrary::new(&project_dir);
+    master.setup_directories().unwrap();
+    project.setup_directories().unwrap();
+
+    master.add_symbol(&symbol("SHARED")).unwrap();
+    master
+        .add_footprint(&footprint_with_model("SHARED_FP"))
+        .unwrap();
+    project.add_symbol(&symbol("SHARED")).unwrap();
+    project.add_symbol(&symbol("NEW")).unwrap();
+    project
+        .add_footprint(&footprint_with_model("SHARED_FP"))
+        .unwrap();
+    project
+        .add_footprint(&footprint_with_model("NEW_FP"))
+        .unwrap();
+    fs::write(
+        project_dir.join("3dmodels.3dshapes/MODEL.wrl"),
+        "#VRML V2.0 utf8\n",
+    )
+    .unwrap();
+
+    master.merge_from(&project_dir).unwrap();
+
+    let lib = fs::read_to_string(master_dir.join("symbols/lib.kicad_sym")).unwrap();
+    assert_eq!(lib.matches("(symbol \"SHARED\"").count(), 1);
+    assert_eq!(lib.matches("(symbol \"NEW\"").count(), 1);
+    // The unit sub-symbol travels inside its parent.
+    assert_eq!(lib.matches("(symbol \"NEW_1_1\"").count(), 1);
+    validate_sexpr(&lib, "lib").unwrap();
+
+    assert!(
+        master_dir
+            .join("footprints.pretty/NEW_FP.kicad_mod")
+            .exists()
+    );
+    assert!(master_dir.join("3dmodels.3dshapes/MODEL.wrl").exists());
+}
+
+#[test]
+fn test_lib_name_and_format() {
+    let dir = scratch_dir("lib_name_format");
+    let mut lib = KicadLibrary::new(&dir);
+    lib.lib_name = "parts".to_string();
+    lib.format = KiCadFormat::V8;
+    lib.setup_directories().unwrap();
+    lib.add_symbol(&symbol("PART")).unwrap();
+
+    let content = fs::read_to_string(dir.join("symbols/parts.kicad_sym")).unwrap();
+    assert!(content.starts_with("(kicad_symbol_lib (version 20231120)"));
+    assert_eq!(lib.symbol_lib_path(), dir.join("symbols/parts.kicad_sym"));
+
+    assert!(content.starts_with(
+        "(kicad_symbol_lib (version 20231120) (generator \"easyeda2kicad_rs\") (generator_version \"8.0\")\n"
+    ));
+
+    // The V6 header must stay exactly as it always was.
+    assert_eq!(
+        KiCadFormat::V6.symbol_lib_header(),
+        "(kicad_symbol_lib (version 20211014) (generator easyeda2kicad_rs)\n"
+    );
+    assert!(
+        !KiCadFormat::V7
+            .symbol_lib_header()
+            .contains("generator_version")
+    );
+
+    assert_eq!("7".parse::<KiCadFormat>(), Ok(KiCadFormat::V7));
+    assert_eq!("v5".parse::<KiCadFormat>(), Ok(KiCadFormat::V5));
+    assert!("4".parse::<KiCadFormat>().is_err());
+}
+
+#[test]
+fn test_per_component_symbol_layout() {
+    let dir = scratch_dir("per_component");
+    let mut lib = KicadLibrary::new(&dir);
+    lib.symbol_layout = SymbolLayout::PerComponent;
+    lib.setup_directories().unwrap();
+    lib.add_symbol(&symbol("ONE")).unwrap();
+    lib.add_symbol(&symbol("TWO")).unwrap();
+    lib.add_symbol(&symbol("ONE")).unwrap();
+
+    assert!(!lib.symbol_lib_path().exists());
+    for name in ["ONE", "TWO"] {
+        let content = fs::read_to_string(dir.join(format!("symbols/{}.kicad_sym", name))).unwrap();
+        assert!(content.starts_with("(kicad_symbol_lib "));
+        assert_eq!(content.matches("(symbol \"").count(), 2, "{}", content);
+        validate_sexpr(&content, name).unwrap();
+    }
+
+    // Per-component files merge into a shared library like any other.
+    let shared_dir = scratch_dir("per_component_shared");
+    let shared = KicadLibrary::new(&shared_dir);
+    shared.merge_from(&dir).unwrap();
+    let content = fs::read_to_string(shared.symbol_lib_path()).unwrap();
+    assert!(content.contains("(symbol \"ONE\"") && content.contains("(symbol \"TWO\""));
+}
+
+#[test]
+fn test_list_symbols() {
+    let dir = scratch_dir("list_symbols");
+    let mut lib = KicadLibrary::new(&dir);
+    assert!(lib.list_symbols().unwrap().is_empty());
+
+    lib.setup_directories().unwrap();
+    lib.add_symbol(&symbol("LED_1")).unwrap();
+    // Shares a prefix with the unit block "LED_1_1" inside the first symbol.
+    lib.add_symbol(&symbol("LED")).unwrap();
+    lib.add_symbol(&symbol("LED")).unwrap();
+    assert_eq!(lib.list_symbols().unwrap(), ["LED_1", "LED"]);
+
+    lib.symbol_layout = SymbolLayout::PerComponent;
+    lib.add_symbol(&symbol("ZENER")).unwrap();
+    lib.add_symbol(&symbol("DIODE")).unwrap();
+    // Every file in symbols/, the shared library included.
+    assert_eq!(
+        lib.list_symbols().unwrap(),
+        ["DIODE", "ZENER", "LED_1", "LED"]
+    );
+}
+
+#[test]
+fn test_model_format_selection() {
+    let mut footprint = footprint_with_model("FP_FORMAT");
+    let model = footprint.model_3d.as_mut().unwrap();
+    model.wrl_data = Some("#VRML V2.0 utf8\n".to_string());
+    model.step_data = Some(bytes::Bytes::from_static(b"ISO-10303-21;"));
+
+    for (format, wrl, step, reference) in [
+        (Model3dFormat::Both, true, true, "MODEL.wrl"),
+        (Model3dFormat::WrlOnly, true, false, "MODEL.wrl"),
+        (Model3dFormat::StepOnly, false, true, "MODEL.step"),
+    ] {
+        let dir = scratch_dir(&format!("model_format_{:?}", format));
+        let mut lib = KicadLibrary::new(&dir);
+        lib.model_format = format;
+        lib.setup_directories().unwrap();
+        lib.add_3d_model(footprint.model_3d.as_ref().unwrap())
+            .unwrap();
+        lib.add_footprint(&footprint).unwrap();
+
+        assert_eq!(dir.join("3dmodels.3dshapes/MODEL.wrl").exists(), wrl);
+        assert_eq!(dir.join("3dmodels.3dshapes/MODEL.step").exists(), step);
+        let content =
+            fs::read_to_string(dir.join("footprints.pretty/FP_FORMAT.kicad_mod")).unwrap();
+        assert!(content.contains(&format!("3dmodels.3dshapes/{}\"", reference)));
+    }
+}
+
+#[test]
+fn test_model_naming() {
+    for (naming, expected) in [
+        (Model3dNaming::Title, "model"),
+        (Model3dNaming::FootprintName, "SOT-23-3_L2.9-W1.3"),
+        (Model3dNaming::LcscId, "C2040"),
+    ] {
+        let name = naming.model_name("model", "SOT-23-3_L2.9-W1.3", "C2040");
+        assert_eq!(name, expected);
+
+        // The written file and the footprint's reference follow the chosen name.
+        let mut footprint = footprint_with_model("FP_NAMING");
+        let model = footprint.model_3d.as_mut().unwrap();
+        model.name = name;
+        model.wrl_data = Some("#VRML V2.0 utf8\n".to_string());
+        let dir = scratch_dir(&format!("model_naming_{:?}", naming));
+        let lib = KicadLibrary::new(&dir);
+        lib.setup_directories().unwrap();
+        lib.add_3d_model(footprint.model_3d.as_ref().unwrap())
+            .unwrap();
+        lib.add_footprint(&footprint).unwrap();
+
+        assert!(
+            dir.join(format!("3dmodels.3dshapes/{}.wrl", expected))
+                .exists()
+        );
+        let content =
+            fs::read_to_string(dir.join("footprints.pretty/FP_NAMING.kicad_mod")).unwrap();
+        assert!(content.contains(&format!("3dmodels.3dshapes/{}.wrl\"", expected)));
+    }
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn test_export_zip() {
+    let dir = scratch_dir("export_zip");
+    let lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+    let mut footprint = footprint_with_model("FP_ZIP");
+    let model = footprint.model_3d.as_mut().unwrap();
+    model.wrl_data = Some("#VRML V2.0 utf8\n".to_string());
+    lib.add_3d_model(footprint.model_3d.as_ref().unwrap())
+        .unwrap();
+    lib.add_footprint(&footprint).unwrap();
+    lib.add_symbol(&symbol("SYM_ZIP")).unwrap();
+
+    let zip_path = dir.with_extension("zip");
+    lib.export_zip(&zip_path).unwrap();
+    // Unfinished downloads stay out of the archive.
+    fs::write(dir.join("3dmodels.3dshapes/MODEL.step.part"), b"ISO").unwrap();
+    lib.export_zip(&zip_path).unwrap();
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+    assert_eq!(
+        archive.file_names().collect::<Vec<_>>(),
+        [
+            "symbols/lib.kicad_sym",
+            "footprints.pretty/FP_ZIP.kicad_mod",
+            "3dmodels.3dshapes/MODEL.wrl",
+        ]
+    );
+    let read = |archive: &mut zip::ZipArchive<fs::File>, name: &str| {
+        use std::io::Read;
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    };
+    assert!(
+        read(&mut archive, "footprints.pretty/FP_ZIP.kicad_mod")
+            .contains("(model \"../3dmodels.3dshapes/MODEL.wrl\"")
+    );
+    assert_eq!(
+        read(&mut archive, "3dmodels.3dshapes/MODEL.wrl"),
+        "#VRML V2.0 utf8\n"
+    );
+}
+
+#[test]
+fn test_remove_symbol_and_footprint() {
+    let dir = scratch_dir("remove");
+    let mut lib = KicadLibrary::new(&dir);
+    lib.setup_directories().unwrap();
+    for name in ["FIRST", "MIDDLE", "LAST"] {
+        lib.add_symbol(&symbol(name)).unwrap();
+    }
+    lib.add_footprint(&footprint_with_model("FP")).unwrap();
+
+    assert!(lib.remove_symbol("MIDDLE").unwrap());
+    assert!(!lib.remove_symbol("MIDDLE").unwrap());
+    assert_eq!(lib.list_symbols().unwrap(), ["FIRST", "LAST"]);
+    let content = fs::read_to_string(lib.symbol_lib_path()).unwrap();
+    validate_sexpr(&content, "lib").unwrap();
+    assert!(!content.contains("MIDDLE"));
+    // Removing every symbol leaves a valid, empty library.
+    assert!(lib.remove_symbol("FIRST").unwrap());
+    assert!(lib.remove_symbol("LAST").unwrap());
+    let content = fs::read_to_string(lib.symbol_lib_path()).unwrap();
+    validate_sexpr(&content, "lib").unwrap();
+    lib.add_symbol(&symbol("AGAIN")).unwrap();
+    assert_eq!(lib.list_symbols().unwrap(), ["AGAIN"]);
+
+    assert!(lib.remove_footprint("FP").unwrap());
+    assert!(!dir.join("footprints.pretty/FP.kicad_mod").exists());
+    assert!(!lib.remove_footprint("FP").unwrap());
+
+    lib.symbol_layout = SymbolLayout::PerComponent;
+    lib.add_symbol(&symbol("SOLO")).unwrap();
+    assert!(lib.remove_symbol("SOLO").unwrap());
+    assert!(!dir.join("symbols/SOLO.kicad_sym").exists());
+}
@@ -0,0 +1,103 @@
+use easyeda2kicad_rs::{
+    ImportOptions,
+    converter::FabStyle,
+    error::Error,
+    kicad_models::KiCadFormat,
+    manifest::{lock_file_contents, parse_manifest},
+    sha256_hex,
+};
+
+#[test]
+fn test_parse_manifest() {
+    let toml = r#"
+# Parts for the main board
+lib = "main_board"  # default library
+
+[[part]]
+lcsc = "C2040"
+
+[[part]]
+lcsc = " c8952 "
+lib = "connectors # and headers"
+skip_3d = true
+mirror_to_bottom = false
+format = "7"
+max_model_downloads = 2
+convert = { fab_style = "ipc", layer_overrides = { 13 = "F.Fab" } }
+"#;
+    let manifest = parse_manifest(toml).unwrap();
+    let ids: Vec<&str> = manifest.parts.iter().map(|p| p.lcsc_id.as_str()).collect();
+    assert_eq!(ids, ["C2040", "C8952"]);
+    assert!(manifest.parts[0].overrides.0.is_empty());
+
+    let mut options = ImportOptions::default();
+    options.convert.strict = true;
+    manifest.defaults.apply(&mut options).unwrap();
+    assert_eq!(options.lib_name.as_deref(), Some("main_board"));
+
+    // Part overrides win over the defaults; options they don't set are kept.
+    manifest.parts[1].overrides.apply(&mut options).unwrap();
+    assert_eq!(
+        options.lib_name.as_deref(),
+        Some("connectors # and headers")
+    );
+    assert!(options.skip_3d);
+    assert_eq!(options.format, KiCadFormat::V7);
+    assert_eq!(options.max_model_downloads, Some(2));
+    assert_eq!(options.convert.fab_style, FabStyle::Ipc);
+    assert_eq!(options.convert.layer_overrides[&13], "F.Fab");
+    assert!(options.convert.strict);
+}
+
+#[test]
+fn test_parse_manifest_errors() {
+    for (toml, expected) in [
+        ("[[part]]\nlib = \"x\"\n", "missing field `lcsc`"),
+        (
+            "[[part]]\nlcsc = \"C1\"\n[[part]]\nlcsc = \"c1\"\n",
+            "C1 is listed twice",
+        ),
+        (
+            "[[part]]\nlcsc = \"C1\"\nskip3d = true\n",
+            "part C1: unknown field `skip3d`",
+        ),
+        (
+            "[[part]]\nlcsc = \"C1\"\nconvert = { strickt = true }\n",
+            "part C1: unknown field `strickt`",
+        ),
+        ("skip_3d = \"yes\"\n", "defaults: invalid type"),
+        ("format = \"9\"\n", "defaults: unknown variant `9`"),
+        ("lcsc = \"C1\"\n", "'lcsc' belongs in a [[part]] table"),
+        ("[parts]\n", "defaults: unknown field `parts`"),
+        ("lib = my_parts\n", "line 1"),
+    ] {
+        match parse_manifest(toml) {
+            Err(Error::ParseError(msg)) => assert!(msg.contains(expected), "{}", msg),
+            other => panic!("expected a parse error for {:?}, got {:?}", toml, other),
+        }
+    }
+}
+
+#[test]
+fn test_sha256() {
+    assert_eq!(
+        sha256_hex(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // Two blocks of padding.
+    assert_eq!(
+        sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+}
+
+#[test]
+fn test_lock_file_contents() {
+    let lock = lock_file_contents("6.4.19.5", &[("C2040".to_string(), "ab12".to_string())]);
+    assert!(lock.contains("api_version = \"6.4.19.5\"\n"));
+    assert!(lock.ends_with("\n[[part]]\nlcsc = \"C2040\"\nsha256 = \"ab12\"\n"));
+}